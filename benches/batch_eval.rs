@@ -0,0 +1,56 @@
+// Batch evaluation of card masks against one `eval_hand` call per hand, over
+// every five-card hand. Run with `cargo bench`.
+
+use dealers_choice::card::mask;
+use dealers_choice::deck::Deck;
+use dealers_choice::evaluation::lowball_ato5::LowballAto5;
+use dealers_choice::evaluation::EvalHand;
+use dealers_choice::hand::Hand;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ROUNDS: u32 = 3;
+
+fn time<F: FnMut()>(name: &str, hands: usize, mut f: F) -> Duration {
+    // Best of a few rounds, after a warm-up
+    f();
+    let mut best = Duration::MAX;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        f();
+        best = best.min(start.elapsed());
+    }
+    println!(
+        "{:<24} {:>10.2?} {:>8.1} ns/hand",
+        name,
+        best,
+        best.as_nanos() as f64 / hands as f64
+    );
+    best
+}
+
+fn main() {
+    let hands: Vec<Hand> = Deck::new().combinations(5).collect();
+    let masks: Vec<u64> = hands.iter().map(|hand| mask(&hand.cards)).collect();
+    let mut scores: Vec<u128> = vec![0; masks.len()];
+    println!("{} five-card hands, A-5 lowball", hands.len());
+
+    let single = time("eval_hand", hands.len(), || {
+        for (hand, score) in hands.iter().zip(scores.iter_mut()) {
+            *score = LowballAto5::eval_hand(black_box(hand)).unwrap_or(0);
+        }
+        black_box(&scores);
+    });
+    let expected = scores.clone();
+
+    let batch = time("eval_masks", hands.len(), || {
+        LowballAto5::eval_masks(black_box(&masks), &mut scores);
+        black_box(&scores);
+    });
+    assert_eq!(scores, expected, "batch scores differ from eval_hand");
+
+    println!(
+        "speedup                  {:>10.2}x",
+        single.as_secs_f64() / batch.as_secs_f64()
+    );
+}
@@ -0,0 +1,222 @@
+// Card removal: how many opponent combos of each hand category our hole
+// cards block, e.g. the ace of spades on a board of three spades blocking
+// the nut flush
+
+use crate::card::{mask, Card};
+use crate::deck::{Combinations, Deck, CHUNKS};
+use crate::error::Error;
+use crate::evaluation::high::{Category, High};
+use crate::game::community::best_high;
+use crate::game::{Gameplay, Variant};
+use crate::hand::Hand;
+use crate::range::Range;
+
+const CATEGORIES: [Category; 9] = [
+    Category::HighCard,
+    Category::OnePair,
+    Category::TwoPairs,
+    Category::Trips,
+    Category::Straight,
+    Category::Flush,
+    Category::FullHouse,
+    Category::Quads,
+    Category::StraightFlush,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Removal {
+    pub category: Category,
+    // Opponent combos making it with only the board known, weighted by the
+    // range when one is given
+    pub combos: f64,
+    // Those holding one of our cards
+    pub blocked: f64,
+}
+
+impl Removal {
+    // Combos the opponent can still hold
+    pub fn remaining(&self) -> f64 {
+        self.combos - self.blocked
+    }
+
+    // Share of the combos blocked, from 0 to 1
+    pub fn share(&self) -> f64 {
+        self.blocked / self.combos
+    }
+}
+
+// Combos and blocked combos by category
+type Tally = [(f64, f64); 9];
+
+// Combos of each high hand category an opponent makes on the board, best
+// first, and how many of them hold one of our hole cards. Without a range
+// every hand of hole cards off the board counts once, in Omaha every one of
+// the four-card hands.
+pub fn removal(
+    variant: Variant,
+    hole: &Hand,
+    board: &Hand,
+    range: Option<&Range>,
+) -> Result<Vec<Removal>, Error> {
+    if variant.gameplay() != Gameplay::Community {
+        return Err(Error::UnsupportedGame);
+    }
+    let all = [&hole.cards[..], &board.cards[..]].concat();
+    if hole.cards.len() != variant.hole_cards()
+        || !(3..=5).contains(&board.cards.len())
+        || mask(&all).count_ones() as usize != all.len()
+    {
+        return Err(Error::InvalidHand);
+    }
+
+    let ours = mask(&hole.cards);
+    let mut tally: Tally = [(0.0, 0.0); 9];
+    match range {
+        Some(range) => {
+            for combo in range.combos() {
+                if combo.hand.cards.len() != variant.hole_cards() {
+                    return Err(Error::InvalidHand);
+                }
+                if mask(&combo.hand.cards) & mask(&board.cards) == 0 {
+                    add(
+                        &mut tally,
+                        variant,
+                        &combo.hand.cards,
+                        board,
+                        ours,
+                        combo.weight,
+                    )?;
+                }
+            }
+        }
+        None => {
+            let cards: Vec<Card> = Deck::new()
+                .cards
+                .into_iter()
+                .filter(|card| !board.cards.contains(card))
+                .collect();
+            let partials =
+                Combinations::new(&cards, variant.hole_cards()).map_chunks(CHUNKS, |mut hands| {
+                    let mut partial: Tally = [(0.0, 0.0); 9];
+                    while let Some(cards) = hands.next_cards() {
+                        add(&mut partial, variant, cards, board, ours, 1.0)?;
+                    }
+                    Ok(partial)
+                });
+            for partial in partials {
+                for (total, (combos, blocked)) in tally.iter_mut().zip(partial?) {
+                    total.0 += combos;
+                    total.1 += blocked;
+                }
+            }
+        }
+    }
+
+    Ok(CATEGORIES
+        .iter()
+        .rev()
+        .map(|&category| (category, tally[category as usize]))
+        .filter(|&(_, (combos, _))| combos > 0.0)
+        .map(|(category, (combos, blocked))| Removal {
+            category,
+            combos,
+            blocked,
+        })
+        .collect())
+}
+
+// Count an opponent hand under its category
+fn add(
+    tally: &mut Tally,
+    variant: Variant,
+    cards: &[Card],
+    board: &Hand,
+    ours: u64,
+    weight: f64,
+) -> Result<(), Error> {
+    let score = best_high(variant, &Hand::from(cards.to_vec()), board)?.score;
+    let (combos, blocked) = &mut tally[High::category(score) as usize];
+    *combos += weight;
+    if mask(cards) & ours != 0 {
+        *blocked += weight;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::hand;
+
+    fn category(removals: &[Removal], category: Category) -> Removal {
+        *removals
+            .iter()
+            .find(|removal| removal.category == category)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_flush_blockers() {
+        let board = hand("Ks 7s 2s Td 4h");
+        let removals = removal(Variant::Holdem, &hand("As Qc"), &board, None).unwrap();
+        // Every combo off the board, once
+        let total: f64 = removals.iter().map(|removal| removal.combos).sum();
+        assert_eq!(total, 1081.0);
+        assert!(removals
+            .windows(2)
+            .all(|pair| pair[0].category > pair[1].category));
+
+        // Two of the ten spades left, 45 combos, of which the ace of spades
+        // is in nine
+        let flushes = category(&removals, Category::Flush);
+        assert_eq!(flushes.combos, 45.0);
+        assert_eq!(flushes.blocked, 9.0);
+        assert_eq!(flushes.remaining(), 36.0);
+        assert_eq!(flushes.share(), 0.2);
+
+        // Without a spade no flush is blocked
+        let offsuit = removal(Variant::Holdem, &hand("Ad Qc"), &board, None).unwrap();
+        assert_eq!(category(&offsuit, Category::Flush).blocked, 0.0);
+    }
+
+    #[test]
+    fn test_range() {
+        let board = hand("Ks 7s 2s Td 4h");
+        let range = Range::try_from("AA, KK, AsQs").unwrap();
+        let removals = removal(Variant::Holdem, &hand("As Qc"), &board, Some(&range)).unwrap();
+        // Three kings make a set, the ace of spades blocks three of six aces
+        // and the only flush
+        assert_eq!(category(&removals, Category::Trips).combos, 3.0);
+        let aces = category(&removals, Category::OnePair);
+        assert_eq!((aces.combos, aces.blocked), (6.0, 3.0));
+        let flush = category(&removals, Category::Flush);
+        assert_eq!((flush.combos, flush.blocked), (1.0, 1.0));
+
+        // Half weight on the nut flush, which our ace of spades blocks
+        let mut omaha = Range::new();
+        omaha.add(hand("As Qs Jh 3d"), 0.5);
+        omaha.add(hand("Qs Js Jh 3d"), 1.0);
+        let removals = removal(Variant::Omaha, &hand("Ac Ad Ah As"), &board, Some(&omaha)).unwrap();
+        let flush = category(&removals, Category::Flush);
+        assert_eq!((flush.combos, flush.blocked), (1.5, 0.5));
+    }
+
+    #[test]
+    fn test_errors() {
+        let board = hand("Ks 7s 2s");
+        assert_eq!(
+            removal(Variant::Razz, &hand("As Qc"), &board, None),
+            Err(Error::UnsupportedGame)
+        );
+        assert_eq!(
+            removal(Variant::Omaha, &hand("As Qc"), &board, None),
+            Err(Error::InvalidHand)
+        );
+        let mut range = Range::new();
+        range.add(hand("Ah Qh Jh Th"), 1.0);
+        assert_eq!(
+            removal(Variant::Holdem, &hand("Ad Qc"), &board, Some(&range)),
+            Err(Error::InvalidHand)
+        );
+    }
+}
@@ -85,6 +85,18 @@ impl Rank {
     }
 }
 
+impl TryFrom<char> for Rank {
+    type Error = Error;
+
+    fn try_from(c: char) -> Result<Rank, Error> {
+        Rank::iter()
+            .iter()
+            .copied()
+            .find(|rank| rank.to_string().starts_with(c))
+            .ok_or(Error::InvalidCardNotation)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum OrderFirstBy {
     Suit,
@@ -174,6 +186,30 @@ impl PartialOrd for Card {
     }
 }
 
+// Set of cards as one bit per card, at its `ord_position` by rank
+pub fn mask(cards: &[Card]) -> u64 {
+    cards.iter().fold(0, |mask, card| {
+        mask | 1 << card.ord_position(OrderFirstBy::Rank)
+    })
+}
+
+// Serialized in card notation, e.g. "Ac"
+#[cfg(feature = "serde")]
+impl serde::Serialize for Card {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Card {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Card, D::Error> {
+        let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+        Card::try_from(s.as_str())
+            .map_err(|_| serde::de::Error::custom(format!("invalid card notation: {}", s)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -242,7 +278,7 @@ mod tests {
             suit: Suit::Diamonds,
             rank: Rank::Trey,
         };
-        assert_eq!(Card::try_from(card).unwrap(), card);
+        assert_eq!(Card::try_from(card.to_string().as_str()).unwrap(), card);
     }
 
     #[test]
@@ -252,15 +288,15 @@ mod tests {
         let card3 = Card::try_from("Ad").unwrap();
         let card4 = Card::try_from("2d").unwrap();
 
-        assert_eq!(card1.ord_position(OrderFirstBy::Suit), 0 * 13 + 12);
-        assert_eq!(card2.ord_position(OrderFirstBy::Suit), 3 * 13 + 0);
+        assert_eq!(card1.ord_position(OrderFirstBy::Suit), 12);
+        assert_eq!(card2.ord_position(OrderFirstBy::Suit), 3 * 13);
         assert_eq!(card3.ord_position(OrderFirstBy::Suit), 2 * 13 + 12);
-        assert_eq!(card4.ord_position(OrderFirstBy::Suit), 2 * 13 + 0);
+        assert_eq!(card4.ord_position(OrderFirstBy::Suit), 2 * 13);
 
-        assert_eq!(card1.ord_position(OrderFirstBy::Rank), 12 * 4 + 0);
-        assert_eq!(card2.ord_position(OrderFirstBy::Rank), 0 * 4 + 3);
+        assert_eq!(card1.ord_position(OrderFirstBy::Rank), 12 * 4);
+        assert_eq!(card2.ord_position(OrderFirstBy::Rank), 3);
         assert_eq!(card3.ord_position(OrderFirstBy::Rank), 12 * 4 + 2);
-        assert_eq!(card4.ord_position(OrderFirstBy::Rank), 0 * 4 + 2);
+        assert_eq!(card4.ord_position(OrderFirstBy::Rank), 2);
     }
 
     #[test]
@@ -336,4 +372,20 @@ mod tests {
         assert!(card1 < card3);
         assert!(card2 > card4);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_card_notation() {
+        let card = Card::new(Suit::Clubs, Rank::Ace);
+        assert_eq!(serde_json::to_string(&card).unwrap(), "\"Ac\"");
+        assert_eq!(serde_json::from_str::<Card>("\"Ac\"").unwrap(), card);
+        assert!(serde_json::from_str::<Card>("\"Ax\"").is_err());
+    }
+
+    #[test]
+    fn rank_from_char() {
+        assert_eq!(Rank::try_from('T'), Ok(Rank::Ten));
+        assert_eq!(Rank::try_from('2'), Ok(Rank::Deuce));
+        assert_eq!(Rank::try_from('x'), Err(Error::InvalidCardNotation));
+    }
 }
@@ -0,0 +1,222 @@
+// `deal`: shuffle with a seed and deal one hand of a variant, to set up
+// practice hands or reproduce a deal
+
+use super::Game;
+use clap::Args;
+use dealers_choice::card::Card;
+use dealers_choice::error::Error;
+use dealers_choice::game::table::{default_limit, default_stakes, GameHand};
+use dealers_choice::game::{Action, Event, Gameplay, Variant};
+use dealers_choice::hand::Hand;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::io::Write;
+
+#[derive(Args, Debug)]
+pub struct DealArgs {
+    #[arg(long, value_enum, default_value_t = Game::Holdem)]
+    game: Game,
+
+    #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u8).range(2..=10))]
+    players: u8,
+
+    /// Seed for the shuffle, random if not given
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Deal {
+    // Cards of every seat in the order dealt, with whether they are face up
+    seats: Vec<Vec<(Card, bool)>>,
+    // Board cards by street, e.g. "Flop"
+    board: Vec<(String, Hand)>,
+}
+
+pub fn run<W: Write>(args: &DealArgs, output: &mut W) -> Result<(), String> {
+    let variant: Variant = args.game.into();
+    let seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let deal = deal(variant, args.players as usize, seed).map_err(|e| e.to_string())?;
+
+    write_deal(output, variant, seed, &deal).map_err(|e| e.to_string())
+}
+
+fn write_deal<W: Write>(
+    output: &mut W,
+    variant: Variant,
+    seed: u64,
+    deal: &Deal,
+) -> std::io::Result<()> {
+    writeln!(
+        output,
+        "{}, {} players, seed {}",
+        variant,
+        deal.seats.len(),
+        seed
+    )?;
+    for (seat, cards) in deal.seats.iter().enumerate() {
+        writeln!(output, "Seat {}: {}", seat + 1, show(cards))?;
+    }
+    for (street, cards) in &deal.board {
+        writeln!(output, "{}: {}", street, cards)?;
+    }
+    Ok(())
+}
+
+// Plays the hand out with everyone checking and calling down, and standing
+// pat in draw games, so the cards and burns are those the engines deal
+fn deal(variant: Variant, players: usize, seed: u64) -> Result<Deal, Error> {
+    // Deep enough that no one is all in before the last card. The first
+    // card goes to seat 1, left of the button.
+    let stacks = vec![1_000_000; players];
+    let mut hand = GameHand::new(
+        variant,
+        default_limit(variant),
+        default_stakes(variant),
+        players - 1,
+        &stacks,
+        &mut StdRng::seed_from_u64(seed),
+    )?;
+    while hand.to_act().is_some() {
+        let action = match hand.options() {
+            _ if hand.is_drawing() => Action::Discard(Vec::new()),
+            Some(options) if options.can_check() => Action::Check,
+            Some(options) if options.bring_in.is_some() => Action::BringIn,
+            _ => Action::Call,
+        };
+        hand.act(action)?;
+    }
+
+    let mut seats: Vec<Vec<(Card, bool)>> = vec![Vec::new(); players];
+    let mut board: Vec<(String, Hand)> = Vec::new();
+    let mut street = None;
+    for event in hand.history() {
+        match event {
+            Event::Street(next) => street = Some(*next),
+            Event::Dealt {
+                seat,
+                cards,
+                face_up,
+            } => seats[*seat].extend(cards.cards.iter().map(|&card| (card, *face_up))),
+            // Stud deals a single community card when the deck runs out
+            Event::Board(cards) if variant.gameplay() == Gameplay::Stud => {
+                board.push(("Community".to_string(), cards.clone()))
+            }
+            Event::Board(cards) => {
+                let name = street.map_or(String::new(), |street| street.to_string());
+                board.push((name, cards.clone()));
+            }
+            _ => {}
+        }
+    }
+    Ok(Deal { seats, board })
+}
+
+// Cards of a seat with down cards in brackets when some are up, e.g.
+// "[Ah Kd] 7c 9s 2h Jd [4c]"
+fn show(cards: &[(Card, bool)]) -> String {
+    if !cards.iter().any(|&(_, face_up)| face_up) {
+        return Hand::from(cards.iter().map(|&(card, _)| card).collect::<Vec<Card>>()).to_string();
+    }
+    let mut groups: Vec<String> = Vec::new();
+    let mut down: Vec<Card> = Vec::new();
+    for &(card, face_up) in cards {
+        if face_up {
+            if !down.is_empty() {
+                groups.push(format!("[{}]", Hand::from(std::mem::take(&mut down))));
+            }
+            groups.push(card.to_string());
+        } else {
+            down.push(card);
+        }
+    }
+    if !down.is_empty() {
+        groups.push(format!("[{}]", Hand::from(down)));
+    }
+    groups.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dealers_choice::deck::Deck;
+
+    fn dealt(variant: Variant, players: usize) -> Deal {
+        deal(variant, players, 42).unwrap()
+    }
+
+    #[test]
+    fn test_deal_holdem() {
+        let deal = dealt(Variant::Holdem, 6);
+        assert!(deal.seats.iter().all(|seat| seat.len() == 2));
+        let streets: Vec<(&str, usize)> = deal
+            .board
+            .iter()
+            .map(|(street, cards)| (street.as_str(), cards.cards.len()))
+            .collect();
+        assert_eq!(streets, [("Flop", 3), ("Turn", 1), ("River", 1)]);
+
+        // Round robin: the first card of the deck goes to seat 1, the second to seat 2
+        let mut deck = Deck::new();
+        deck.shuffle_with_rng(&mut StdRng::seed_from_u64(42));
+        assert_eq!(deal.seats[0][0].0, deck.cards[0]);
+        assert_eq!(deal.seats[1][0].0, deck.cards[1]);
+        assert_eq!(deal.seats[0][1].0, deck.cards[6]);
+        // Burn, then the flop
+        assert_eq!(deal.board[0].1.cards[0], deck.cards[13]);
+    }
+
+    #[test]
+    fn test_deal_stud() {
+        let deal = dealt(Variant::SevenCardStud, 7);
+        let pattern: Vec<bool> = deal.seats[0].iter().map(|&(_, up)| up).collect();
+        assert_eq!(pattern, [false, false, true, true, true, true, false]);
+        assert!(deal.board.is_empty());
+        // A card is burnt before fourth street, as in a played hand
+        let mut deck = Deck::new();
+        deck.shuffle_with_rng(&mut StdRng::seed_from_u64(42));
+        assert_eq!(deal.seats[0][2].0, deck.cards[14]);
+        assert_eq!(deal.seats[0][3].0, deck.cards[22]);
+
+        // 8 players run the deck out before seventh street
+        let deal = dealt(Variant::Razz, 8);
+        assert!(deal.seats.iter().all(|seat| seat.len() == 6));
+        assert_eq!(deal.board[0].0, "Community");
+    }
+
+    #[test]
+    fn test_deal_draw() {
+        let badugi = dealt(Variant::Badugi, 4);
+        assert!(badugi.seats.iter().all(|seat| seat.len() == 4));
+        assert!(badugi.board.is_empty());
+
+        let omaha = deal(Variant::FiveCardOmaha, 10, 42);
+        assert_eq!(omaha, Err(Error::NotEnoughCards));
+    }
+
+    #[test]
+    fn test_show() {
+        let cards = Hand::try_from("Ah Kd 7c 9s 2h Jd 4c").unwrap().cards;
+        let up = [false, false, true, true, true, true, false];
+        let seat: Vec<(Card, bool)> = cards.into_iter().zip(up).collect();
+        assert_eq!(show(&seat), "[Ah Kd] 7c 9s 2h Jd [4c]");
+        assert_eq!(show(&seat[..2]), "Ah Kd");
+    }
+
+    #[test]
+    fn test_run_reproducible() {
+        let args = DealArgs {
+            game: Game::Stud,
+            players: 6,
+            seed: Some(42),
+        };
+        let mut first: Vec<u8> = Vec::new();
+        let mut second: Vec<u8> = Vec::new();
+        run(&args, &mut first).unwrap();
+        run(&args, &mut second).unwrap();
+        assert_eq!(first, second);
+        let output = String::from_utf8(first).unwrap();
+        assert!(output.starts_with("Seven Card Stud, 6 players, seed 42\nSeat 1: ["));
+        assert_eq!(output.lines().count(), 7);
+    }
+}
@@ -0,0 +1,202 @@
+// `equity`: equity of hands or ranges against each other, either exact or by
+// Monte Carlo sampling with a seed that is printed for reproducibility
+
+use super::{parse_cards, Game};
+use clap::Args;
+use dealers_choice::equity::{exact, monte_carlo, Equity};
+use dealers_choice::error::Error;
+use dealers_choice::hand::Hand;
+use dealers_choice::range::Range;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::io::Write;
+
+#[derive(Args, Debug)]
+pub struct EquityArgs {
+    /// Community card game
+    #[arg(long, value_enum, default_value_t = Game::Holdem)]
+    game: Game,
+
+    /// Board cards, e.g. "Ah 7d 2c"
+    #[arg(long, default_value = "")]
+    board: String,
+
+    /// Dead cards, e.g. folded or burnt cards known to be out
+    #[arg(long, default_value = "")]
+    dead: String,
+
+    /// Enumerate every matchup and board instead of sampling
+    #[arg(long, conflicts_with_all = ["iterations", "seed"])]
+    exact: bool,
+
+    /// Samples for Monte Carlo
+    #[arg(long, default_value_t = 100_000)]
+    iterations: usize,
+
+    /// Seed for Monte Carlo, random if not given
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Hand or range of each player, e.g. "AsKd", "As Kd" or "QQ+, AKs:0.5"
+    #[arg(num_args = 2.., required = true)]
+    players: Vec<String>,
+}
+
+pub fn run<W: Write>(args: &EquityArgs, output: &mut W) -> Result<(), String> {
+    let ranges = args
+        .players
+        .iter()
+        .map(|player| parse_player(player).map_err(|e| format!("{}: {}", player, e)))
+        .collect::<Result<Vec<Range>, String>>()?;
+    let board = Hand::from(parse_cards(&args.board).map_err(|e| format!("board: {}", e))?);
+    let dead = parse_cards(&args.dead).map_err(|e| format!("dead cards: {}", e))?;
+    let variant = args.game.into();
+
+    let (method, equities) = if args.exact {
+        ("exact".to_string(), exact(variant, &ranges, &board, &dead))
+    } else {
+        let seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let mut rng = StdRng::seed_from_u64(seed);
+        (
+            format!("{} iterations, seed {}", args.iterations, seed),
+            monte_carlo(variant, &ranges, &board, &dead, args.iterations, &mut rng),
+        )
+    };
+    let equities = equities.map_err(|e| e.to_string())?;
+    write_table(output, args, variant, &method, &equities).map_err(|e| e.to_string())
+}
+
+// A range, or cards of a single hand with spaces between them
+fn parse_player(s: &str) -> Result<Range, Error> {
+    match Range::try_from(s) {
+        Ok(range) => Ok(range),
+        Err(e) => {
+            let cards = parse_cards(s).map_err(|_| e)?;
+            let mut range = Range::new();
+            range.add(Hand::from(cards), 1.0);
+            Ok(range)
+        }
+    }
+}
+
+fn write_table<W: Write>(
+    output: &mut W,
+    args: &EquityArgs,
+    variant: dealers_choice::game::Variant,
+    method: &str,
+    equities: &[Equity],
+) -> std::io::Result<()> {
+    writeln!(output, "{}, {}", variant, method)?;
+    if !args.board.trim().is_empty() {
+        writeln!(output, "Board: {}", args.board.trim())?;
+    }
+    if !args.dead.trim().is_empty() {
+        writeln!(output, "Dead: {}", args.dead.trim())?;
+    }
+    let width = args
+        .players
+        .iter()
+        .map(|player| player.len())
+        .max()
+        .unwrap_or(0)
+        .max("Hand".len());
+    writeln!(
+        output,
+        "Player  {:<width$}  {:>7}  {:>7}  {:>7}",
+        "Hand", "Equity", "Win", "Tie"
+    )?;
+    for (i, (player, equity)) in args.players.iter().zip(equities).enumerate() {
+        writeln!(
+            output,
+            "{:<6}  {:<width$}  {:>6.2}%  {:>6.2}%  {:>6.2}%",
+            i + 1,
+            player,
+            equity.equity * 100.0,
+            equity.win * 100.0,
+            equity.tie * 100.0
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: EquityArgs,
+    }
+
+    fn equity(args: &[&str]) -> Result<String, String> {
+        let cli = Cli::try_parse_from(["equity"].iter().chain(args)).map_err(|e| e.to_string())?;
+        let mut output: Vec<u8> = Vec::new();
+        run(&cli.args, &mut output)?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_exact_river() {
+        let output = equity(&["--exact", "--board", "Ah Kd 7c 2s 3h", "AA", "Kh Ks"]).unwrap();
+        assert_eq!(
+            output,
+            "Hold'em, exact\n\
+             Board: Ah Kd 7c 2s 3h\n\
+             Player  Hand    Equity      Win      Tie\n\
+             1       AA     100.00%  100.00%    0.00%\n\
+             2       Kh Ks    0.00%    0.00%    0.00%\n"
+        );
+    }
+
+    #[test]
+    fn test_monte_carlo_seed() {
+        let args = [
+            "--seed",
+            "7",
+            "--iterations",
+            "2000",
+            "--dead",
+            "Qc",
+            "QQ+, AKs",
+            "JTs",
+        ];
+        let output = equity(&args).unwrap();
+        assert!(output.starts_with("Hold'em, 2000 iterations, seed 7\nDead: Qc\n"));
+        assert_eq!(output, equity(&args).unwrap());
+    }
+
+    #[test]
+    fn test_omaha() {
+        let output = equity(&[
+            "--game",
+            "omaha-hi-lo",
+            "--exact",
+            "--board",
+            "Ad Kd 7h 8s 9c",
+            "2c3cJhJd",
+            "KsKhQdQc",
+        ])
+        .unwrap();
+        assert!(output.starts_with("Omaha Hi-Lo 8 or Better, exact\n"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(equity(&["AA"]).is_err());
+        assert!(equity(&["--exact", "--seed", "1", "AA", "KK"]).is_err());
+        assert_eq!(
+            equity(&["AA", "K"]),
+            Err("K: invalid range notation".to_string())
+        );
+        assert_eq!(
+            equity(&["--board", "Ah Kd", "AA", "KK"]),
+            Err("invalid setup".to_string())
+        );
+        assert_eq!(
+            equity(&["--game", "razz", "AA", "KK"]),
+            Err("unsupported game".to_string())
+        );
+    }
+}
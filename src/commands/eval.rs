@@ -0,0 +1,242 @@
+// `eval`: score hands with one of the evaluators and pick the winners
+
+use super::input_lines;
+use clap::{Args, ValueEnum};
+use dealers_choice::evaluation::badugi::Badugi;
+use dealers_choice::evaluation::eight_or_better::EightOrBetter;
+use dealers_choice::evaluation::high::High;
+use dealers_choice::evaluation::lowball_2to7::Lowball2to7;
+use dealers_choice::evaluation::lowball_ato5::LowballAto5;
+use dealers_choice::evaluation::{EvalHand, Evaluation};
+use dealers_choice::hand::Hand;
+use std::io::{BufRead, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Evaluator {
+    High,
+    #[value(name = "a5-lowball")]
+    LowballAto5,
+    #[value(name = "27-lowball")]
+    Lowball2to7,
+    #[value(name = "8-or-better")]
+    EightOrBetter,
+    Badugi,
+}
+
+#[derive(Args, Debug)]
+pub struct EvalArgs {
+    /// Evaluator to score the hands with
+    #[arg(long, value_enum, default_value_t = Evaluator::High)]
+    game: Evaluator,
+
+    /// Print JSON instead of a table
+    #[cfg(feature = "serde")]
+    #[arg(long)]
+    json: bool,
+
+    /// Hands such as "Ac 2d 3h 4s 5c", read one per line from stdin if none;
+    /// longer hands play their best 5 cards, or 4 in Badugi
+    hands: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Scored {
+    hand: Hand,
+    best: Hand,
+    score: u128,
+    category: String,
+}
+
+pub fn run<R: BufRead, W: Write>(args: &EvalArgs, input: R, output: &mut W) -> Result<(), String> {
+    let lines = if args.hands.is_empty() {
+        input_lines(input)?
+    } else {
+        args.hands.clone()
+    };
+    let mut scored: Vec<Scored> = Vec::with_capacity(lines.len());
+    for line in &lines {
+        let hand = Hand::try_from(line.as_str()).map_err(|e| format!("{}: {}", line, e))?;
+        scored.push(score(args.game, hand).map_err(|e| format!("{}: {}", line, e))?);
+    }
+
+    let winners = winners(&scored);
+    #[cfg(feature = "serde")]
+    if args.json {
+        return write_json(output, &scored, &winners).map_err(|e| e.to_string());
+    }
+    write_table(output, &scored, &winners).map_err(|e| e.to_string())
+}
+
+fn score(game: Evaluator, hand: Hand) -> Result<Scored, dealers_choice::error::Error> {
+    match game {
+        Evaluator::High => score_with::<High>(hand, 5),
+        Evaluator::LowballAto5 => score_with::<LowballAto5>(hand, 5),
+        Evaluator::Lowball2to7 => score_with::<Lowball2to7>(hand, 5),
+        Evaluator::EightOrBetter => score_with::<EightOrBetter>(hand, 5),
+        Evaluator::Badugi => score_with::<Badugi>(hand, 4),
+    }
+}
+
+fn score_with<T: EvalHand>(
+    hand: Hand,
+    size: usize,
+) -> Result<Scored, dealers_choice::error::Error> {
+    let evaluation = if hand.cards.len() > size {
+        Evaluation::<T>::best_of(&hand.cards, size)?
+    } else {
+        Evaluation::<T>::try_from(hand.clone())?
+    };
+    Ok(Scored {
+        hand,
+        best: evaluation.hand,
+        score: evaluation.score,
+        category: T::describe(evaluation.score),
+    })
+}
+
+// Positions of the hands with the top score; none if no hand qualifies, i.e.
+// every score is 0 as for lows that are not 8 or better
+fn winners(scored: &[Scored]) -> Vec<usize> {
+    let top = scored.iter().map(|s| s.score).max().unwrap_or(0);
+    if top == 0 {
+        return Vec::new();
+    }
+    (0..scored.len())
+        .filter(|&i| scored[i].score == top)
+        .collect()
+}
+
+fn write_table<W: Write>(
+    output: &mut W,
+    scored: &[Scored],
+    winners: &[usize],
+) -> std::io::Result<()> {
+    let width = scored
+        .iter()
+        .map(|s| s.hand.to_string().len())
+        .max()
+        .unwrap_or(0);
+    for s in scored {
+        let mut line = format!("{:<width$}  {}", s.hand.to_string(), s.category);
+        if s.best != s.hand {
+            line.push_str(&format!(" ({})", s.best));
+        }
+        writeln!(output, "{}  {}", line, s.score)?;
+    }
+    let names: Vec<String> = winners
+        .iter()
+        .map(|&i| scored[i].hand.to_string())
+        .collect();
+    match names.len() {
+        0 => writeln!(output, "No winner"),
+        1 => writeln!(output, "Winner: {}", names[0]),
+        _ => writeln!(output, "Split: {}", names.join(", ")),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn write_json<W: Write>(
+    output: &mut W,
+    scored: &[Scored],
+    winners: &[usize],
+) -> std::io::Result<()> {
+    // Scores are strings as they do not fit in a double
+    let hands: Vec<serde_json::Value> = scored
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            serde_json::json!({
+                "hand": s.hand.to_string(),
+                "best": s.best.to_string(),
+                "category": s.category,
+                "score": s.score.to_string(),
+                "winner": winners.contains(&i),
+            })
+        })
+        .collect();
+    let json = serde_json::json!({ "hands": hands, "winners": winners });
+    writeln!(output, "{}", json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(game: Evaluator, hands: &[&str]) -> EvalArgs {
+        EvalArgs {
+            game,
+            #[cfg(feature = "serde")]
+            json: false,
+            hands: hands.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn eval(game: Evaluator, hands: &[&str], input: &str) -> Result<String, String> {
+        run_args(&args(game, hands), input)
+    }
+
+    fn run_args(args: &EvalArgs, input: &str) -> Result<String, String> {
+        let mut output: Vec<u8> = Vec::new();
+        run(args, input.as_bytes(), &mut output)?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_eval_args() {
+        let output = eval(
+            Evaluator::LowballAto5,
+            &["Ac 2d 3h 4s 5c", "2c 3d 4h 5s 6c"],
+            "",
+        )
+        .unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].starts_with("Ac 2d 3h 4s 5c  5 low  "));
+        assert!(lines[1].starts_with("2c 3d 4h 5s 6c  6 low  "));
+        assert_eq!(lines[2], "Winner: Ac 2d 3h 4s 5c");
+    }
+
+    #[test]
+    fn test_eval_stdin_best_of() {
+        let input = "As Ks Qs Js Ts 2c 2d\n\nAh Kh Qh Jh Th\n9c 9d 9h 2s 3s\n";
+        let output = eval(Evaluator::High, &[], input).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].contains("Straight flush (As Ks Qs Js Ts)"));
+        assert!(lines[2].contains("Three of a kind"));
+        assert_eq!(lines[3], "Split: As Ks Qs Js Ts 2c 2d, Ah Kh Qh Jh Th");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_eval_json() {
+        let mut args = args(Evaluator::EightOrBetter, &["Kc Qd Jh 9s 8c"]);
+        args.json = true;
+        let output: serde_json::Value =
+            serde_json::from_str(&run_args(&args, "").unwrap()).unwrap();
+        assert_eq!(
+            output,
+            serde_json::json!({
+                "hands": [{
+                    "hand": "Kc Qd Jh 9s 8c",
+                    "best": "Kc Qd Jh 9s 8c",
+                    "category": "No low",
+                    "score": "0",
+                    "winner": false,
+                }],
+                "winners": [],
+            })
+        );
+    }
+
+    #[test]
+    fn test_eval_errors() {
+        assert_eq!(
+            eval(Evaluator::High, &["Ac Xd"], ""),
+            Err("Ac Xd: invalid card notation".to_string())
+        );
+        assert_eq!(
+            eval(Evaluator::Badugi, &["Ac 2d"], ""),
+            Err("Ac 2d: invalid hand".to_string())
+        );
+    }
+}
@@ -0,0 +1,122 @@
+// Subcommands of the binary. Each takes its parsed arguments plus input and
+// output streams, and returns a message for the user on failure.
+
+use clap::ValueEnum;
+use dealers_choice::card::Card;
+use dealers_choice::error::Error;
+use dealers_choice::game::Variant;
+use std::io::BufRead;
+
+pub mod deal;
+pub mod equity;
+pub mod eval;
+pub mod play;
+
+// `--game` names of the variants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Game {
+    Holdem,
+    Omaha,
+    #[value(name = "omaha-hi-lo")]
+    OmahaHiLo,
+    #[value(name = "5-card-omaha")]
+    FiveCardOmaha,
+    #[value(name = "big-o")]
+    BigO,
+    Stud,
+    #[value(name = "stud-hi-lo")]
+    StudHiLo,
+    Razz,
+    #[value(name = "5-card-draw")]
+    FiveCardDraw,
+    #[value(name = "a5-triple-draw")]
+    AceToFiveTripleDraw,
+    #[value(name = "27-single-draw")]
+    DeuceToSevenSingleDraw,
+    #[value(name = "27-triple-draw")]
+    DeuceToSevenTripleDraw,
+    Badugi,
+    Badacey,
+    Badeucey,
+}
+
+impl From<Game> for Variant {
+    fn from(game: Game) -> Variant {
+        match game {
+            Game::Holdem => Variant::Holdem,
+            Game::Omaha => Variant::Omaha,
+            Game::OmahaHiLo => Variant::OmahaHiLo,
+            Game::FiveCardOmaha => Variant::FiveCardOmaha,
+            Game::BigO => Variant::BigO,
+            Game::Stud => Variant::SevenCardStud,
+            Game::StudHiLo => Variant::StudHiLo,
+            Game::Razz => Variant::Razz,
+            Game::FiveCardDraw => Variant::FiveCardDraw,
+            Game::AceToFiveTripleDraw => Variant::AceToFiveTripleDraw,
+            Game::DeuceToSevenSingleDraw => Variant::DeuceToSevenSingleDraw,
+            Game::DeuceToSevenTripleDraw => Variant::DeuceToSevenTripleDraw,
+            Game::Badugi => Variant::Badugi,
+            Game::Badacey => Variant::Badacey,
+            Game::Badeucey => Variant::Badeucey,
+        }
+    }
+}
+
+// Cards written with or without spaces, e.g. "Ah 7d 2c" or "Ah7d2c"
+fn parse_cards(s: &str) -> Result<Vec<Card>, Error> {
+    let compact: String = s.split_whitespace().collect();
+    if !compact.is_ascii() || !compact.len().is_multiple_of(2) {
+        return Err(Error::InvalidCardNotation);
+    }
+    (0..compact.len())
+        .step_by(2)
+        .map(|i| Card::try_from(&compact[i..i + 2]))
+        .collect()
+}
+
+// Non-empty trimmed lines of the input, e.g. hands piped through stdin
+fn input_lines<R: BufRead>(input: R) -> Result<Vec<String>, String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in input.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim();
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_lines() {
+        let input = "Ac 2d\n\n  Kh Ks  \n".as_bytes();
+        assert_eq!(input_lines(input).unwrap(), ["Ac 2d", "Kh Ks"]);
+    }
+
+    #[test]
+    fn test_game_names() {
+        let names: Vec<String> = Game::value_variants()
+            .iter()
+            .map(|game| game.to_possible_value().unwrap().get_name().to_string())
+            .collect();
+        assert_eq!(names.len(), Variant::ALL.len());
+        assert!(names.contains(&"27-triple-draw".to_string()));
+        // Every variant has a name
+        for (game, variant) in Game::value_variants().iter().zip(Variant::ALL) {
+            assert_eq!(Variant::from(*game), variant);
+        }
+    }
+
+    #[test]
+    fn test_parse_cards() {
+        let cards = parse_cards("Ah 7d2c").unwrap();
+        assert_eq!(cards.len(), 3);
+        assert_eq!(parse_cards("").unwrap(), Vec::new());
+        assert_eq!(parse_cards("Ah 7"), Err(Error::InvalidCardNotation));
+        assert_eq!(parse_cards("Ah Xd"), Err(Error::InvalidCardNotation));
+    }
+}
@@ -0,0 +1,450 @@
+// `play`: a dealer's choice table in the terminal, the user in the first seat
+// against bots, rotating through the selected games every hand
+
+use super::{parse_cards, Game};
+use clap::{Args, ValueEnum};
+use dealers_choice::game::betting::BettingOptions;
+use dealers_choice::game::strategy::{GameView, Strategy, ThresholdBot};
+use dealers_choice::game::table::{default_limit, default_stakes, describe, GameHand};
+use dealers_choice::game::{Action, Event, Gameplay, Variant};
+use dealers_choice::hand::Hand;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::io::{BufRead, Write};
+
+// Hand strength percentiles from which the bots call and raise
+const BOT_CALL: f64 = 60.0;
+const BOT_RAISE: f64 = 90.0;
+
+#[derive(Args, Debug)]
+pub struct PlayArgs {
+    /// Bots at the table
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(1..=7))]
+    bots: u8,
+
+    /// Games played in turn, e.g. holdem,razz,badugi
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = Game::value_variants().to_vec())]
+    games: Vec<Game>,
+
+    /// Starting stack of every player
+    #[arg(long, default_value_t = 200)]
+    stack: u64,
+
+    /// Stop after this many hands
+    #[arg(long)]
+    hands: Option<usize>,
+
+    /// Seed for the shuffles, random if not given
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+// What the user asked for at a prompt
+enum Input {
+    Act(Action),
+    Quit,
+}
+
+pub fn run<R: BufRead, W: Write>(args: &PlayArgs, input: R, output: &mut W) -> Result<(), String> {
+    play(args, input, output).map_err(|e| e.to_string())
+}
+
+fn play<R: BufRead, W: Write>(
+    args: &PlayArgs,
+    mut input: R,
+    output: &mut W,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = StdRng::seed_from_u64(seed);
+    let players = args.bots as usize + 1;
+    let mut stacks = vec![args.stack; players];
+    let mut bots: Vec<ThresholdBot> = (0..args.bots)
+        .map(|_| ThresholdBot::new(BOT_CALL, BOT_RAISE, rng.gen()))
+        .collect();
+    // One seat before the first so the button starts with the user
+    let mut button = players - 1;
+    writeln!(
+        output,
+        "Dealer's choice, you and {} bot{}, seed {}",
+        args.bots,
+        if args.bots == 1 { "" } else { "s" },
+        seed
+    )?;
+
+    let mut played = 0;
+    loop {
+        if stacks[0] == 0 {
+            writeln!(output, "You are out of chips")?;
+            break;
+        }
+        if stacks.iter().filter(|&&stack| stack > 0).count() < 2 {
+            writeln!(output, "You have won every chip")?;
+            break;
+        }
+        if args.hands.is_some_and(|hands| played >= hands) {
+            break;
+        }
+
+        button = (1..=players)
+            .map(|i| (button + i) % players)
+            .find(|&seat| stacks[seat] > 0)
+            .unwrap_or(0);
+        let variant: Variant = args.games[played % args.games.len()].into();
+        let limit = default_limit(variant);
+        let mut hand = GameHand::new(
+            variant,
+            limit,
+            default_stakes(variant),
+            button,
+            &stacks,
+            &mut rng,
+        )?;
+        played += 1;
+        writeln!(output)?;
+        match variant.gameplay() {
+            Gameplay::Stud => writeln!(output, "Hand {}: {}", played, variant)?,
+            _ => writeln!(output, "Hand {}: {} {}", played, limit, variant)?,
+        }
+        writeln!(output, "Button: {}", name(button))?;
+
+        let mut board = Hand::new();
+        while !hand.is_finished() {
+            for event in hand.take_events() {
+                show_event(output, &hand, &mut board, &event)?;
+            }
+            let (seat, action) = match hand.to_act() {
+                Some(0) => match prompt(&hand, &mut input, output)? {
+                    Input::Act(action) => (0, action),
+                    // Chips put in the pot are lost
+                    Input::Quit => {
+                        return finish(output, played, args.stack, hand.seats()[0].stack)
+                    }
+                },
+                Some(seat) => (seat, bots[seat - 1].act(&GameView::new(&hand, seat))),
+                None => break,
+            };
+            if let Err(e) = hand.act(action) {
+                if seat > 0 {
+                    return Err(format!("{} made an illegal move: {}", name(seat), e).into());
+                }
+                writeln!(output, "That is not a legal action")?;
+            }
+        }
+        for event in hand.take_events() {
+            show_event(output, &hand, &mut board, &event)?;
+        }
+
+        stacks = hand.seats().iter().map(|seat| seat.stack).collect();
+        let chips: Vec<String> = stacks
+            .iter()
+            .enumerate()
+            .map(|(seat, stack)| format!("{} {}", name(seat), stack))
+            .collect();
+        writeln!(output, "Stacks: {}", chips.join(", "))?;
+    }
+    finish(output, played, args.stack, stacks[0])
+}
+
+fn finish<W: Write>(
+    output: &mut W,
+    played: usize,
+    start: u64,
+    stack: u64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let result = stack as i64 - start as i64;
+    writeln!(
+        output,
+        "Played {} hands, you finished with {} ({:+})",
+        played, stack, result
+    )?;
+    Ok(())
+}
+
+fn name(seat: usize) -> String {
+    match seat {
+        0 => "You".to_string(),
+        _ => format!("Bot {}", seat),
+    }
+}
+
+// Events as the user sees them: their own cards, the up cards of the bots
+// and everything shown down
+fn show_event<W: Write>(
+    output: &mut W,
+    hand: &GameHand,
+    board: &mut Hand,
+    event: &Event,
+) -> std::io::Result<()> {
+    match event {
+        Event::Ante { seat, amount } => writeln!(output, "{}: antes {}", name(*seat), amount),
+        Event::Blind { seat, amount } => writeln!(output, "{}: posts {}", name(*seat), amount),
+        Event::Dealt {
+            seat,
+            cards,
+            face_up,
+        } => match (seat, face_up) {
+            (_, true) => writeln!(output, "{}: shows {}", name(*seat), cards),
+            (0, false) => writeln!(output, "You: dealt {}", cards),
+            _ => Ok(()),
+        },
+        Event::Street(street) => writeln!(output, "*** {} ***", street),
+        Event::Board(cards) => {
+            board.cards.extend(cards.cards.iter().copied());
+            writeln!(output, "Board: {}", board)
+        }
+        Event::Action { seat, action } => writeln!(output, "{}: {}", name(*seat), action),
+        Event::Returned { seat, amount } => {
+            writeln!(output, "{}: takes back {}", name(*seat), amount)
+        }
+        Event::Draw {
+            seat: 0,
+            discarded,
+            drawn,
+        } => {
+            if discarded.cards.is_empty() {
+                writeln!(output, "You: stand pat")
+            } else {
+                writeln!(output, "You: discard {}, draw {}", discarded, drawn)
+            }
+        }
+        Event::Draw {
+            seat, discarded, ..
+        } => match discarded.cards.len() {
+            0 => writeln!(output, "{}: stands pat", name(*seat)),
+            count => writeln!(output, "{}: draws {}", name(*seat), count),
+        },
+        Event::Reshuffle { cards } => {
+            writeln!(output, "Discards shuffled into a new stub of {}", cards)
+        }
+        // Stud shows only the down cards, the up cards are part of the hand
+        Event::Showdown { seat, cards } => {
+            match describe(hand.variant(), &hand.cards_of(*seat), hand.board()) {
+                Ok(made) => writeln!(output, "{}: shows {} ({})", name(*seat), cards, made),
+                Err(_) => writeln!(output, "{}: shows {}", name(*seat), cards),
+            }
+        }
+        Event::Award { seat, amount, low } => {
+            if *low {
+                writeln!(output, "{}: wins {} with low", name(*seat), amount)
+            } else {
+                writeln!(output, "{}: wins {}", name(*seat), amount)
+            }
+        }
+    }
+}
+
+// Asks until the user gives an action that parses, or quits at the end of
+// the input
+fn prompt<R: BufRead, W: Write>(
+    hand: &GameHand,
+    input: &mut R,
+    output: &mut W,
+) -> std::io::Result<Input> {
+    let seat = &hand.seats()[0];
+    let cards = if seat.up.cards.is_empty() {
+        seat.hole.to_string()
+    } else {
+        format!("[{}] {}", seat.hole, seat.up)
+    };
+    writeln!(
+        output,
+        "Your cards: {}, pot {}, stack {}",
+        cards,
+        hand.pot(),
+        seat.stack
+    )?;
+    let choices = match hand.options() {
+        _ if hand.is_drawing() => "cards to discard or pat".to_string(),
+        Some(options) => choices(&options),
+        None => String::new(),
+    };
+    loop {
+        write!(output, "{} > ", choices)?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            writeln!(output)?;
+            return Ok(Input::Quit);
+        }
+        let parsed = if hand.is_drawing() {
+            parse_discard(line.trim())
+        } else {
+            hand.options()
+                .and_then(|options| parse_action(line.trim(), &options))
+        };
+        match parsed {
+            Some(input) => return Ok(input),
+            None => writeln!(output, "Enter one of: {}, or quit", choices)?,
+        }
+    }
+}
+
+// The legal actions, e.g. "fold, call 4, raise 8-200"
+fn choices(options: &BettingOptions) -> String {
+    let mut choices: Vec<String> = Vec::new();
+    if let Some(bring_in) = options.bring_in {
+        choices.push(format!("bring in {}", bring_in));
+    } else if options.can_check() {
+        choices.push("check".to_string());
+    } else {
+        choices.push("fold".to_string());
+        choices.push(format!("call {}", options.to_call));
+    }
+    if let (Some(min), Some(max)) = (options.min_raise_to, options.max_raise_to) {
+        let verb = match options.current_bet {
+            _ if options.completes => "complete",
+            0 => "bet",
+            _ => "raise",
+        };
+        if min == max {
+            choices.push(format!("{} {}", verb, min));
+        } else {
+            choices.push(format!("{} {}-{}", verb, min, max));
+        }
+    }
+    choices.join(", ")
+}
+
+// "f", "c" to check or call, "b 10" or "r 10" for the total put in this
+// street, and "b" alone for the minimum
+fn parse_action(line: &str, options: &BettingOptions) -> Option<Input> {
+    let mut words = line.split_whitespace();
+    let word = words.next()?.to_lowercase();
+    let amount = match words.next() {
+        Some(amount) => Some(amount.parse::<u64>().ok()?),
+        None => None,
+    };
+    let action = match word.as_str() {
+        "q" | "quit" => return Some(Input::Quit),
+        "f" | "fold" => Action::Fold,
+        "k" | "check" | "c" | "call" => {
+            if options.can_check() {
+                Action::Check
+            } else if options.bring_in.is_some() {
+                Action::BringIn
+            } else {
+                Action::Call
+            }
+        }
+        "bring" | "bring-in" => Action::BringIn,
+        "complete" => Action::Complete,
+        "b" | "bet" | "r" | "raise" => options.bet_or_raise(amount.or(options.min_raise_to)?),
+        _ => return None,
+    };
+    Some(Input::Act(action))
+}
+
+// "pat" or the cards to throw away, e.g. "Kc Kd"
+fn parse_discard(line: &str) -> Option<Input> {
+    match line.to_lowercase().as_str() {
+        "" => None,
+        "q" | "quit" => Some(Input::Quit),
+        "pat" | "p" => Some(Input::Act(Action::Discard(Vec::new()))),
+        _ => parse_cards(line)
+            .ok()
+            .map(|cards| Input::Act(Action::Discard(cards))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: PlayArgs,
+    }
+
+    fn play(args: &[&str], input: &str) -> String {
+        let cli = Cli::try_parse_from(["play"].iter().chain(args)).unwrap();
+        let mut output: Vec<u8> = Vec::new();
+        run(&cli.args, input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_rotates_games() {
+        let input = "c\n".repeat(100);
+        let output = play(
+            &[
+                "--seed",
+                "1",
+                "--bots",
+                "2",
+                "--hands",
+                "3",
+                "--games",
+                "holdem,razz",
+            ],
+            &input,
+        );
+        assert!(output.starts_with("Dealer's choice, you and 2 bots, seed 1\n"));
+        assert!(output.contains("Hand 1: NL Hold'em\n"));
+        assert!(output.contains("Hand 2: Razz\n"));
+        assert!(output.contains("Bot 1: shows 9c 3d 8d (9 low)\n"));
+        assert!(output.contains("Hand 3: NL Hold'em\n"));
+        assert!(output.ends_with("Played 3 hands, you finished with 0 (-200)\n"));
+    }
+
+    #[test]
+    fn test_showdown_described() {
+        let input = "c\npat\n".repeat(20);
+        let output = play(
+            &[
+                "--seed", "3", "--bots", "1", "--hands", "1", "--games", "badugi",
+            ],
+            &input,
+        );
+        assert!(output.contains("Hand 1: FL Badugi\n"));
+        assert!(output.contains("You: stand pat\n"));
+        assert!(output.contains("Bot 1: draws 1\n"));
+        let shows: Vec<&str> = output
+            .lines()
+            .filter(|line| line.contains(": shows"))
+            .collect();
+        assert_eq!(shows.len(), 2);
+        assert!(shows.iter().all(|line| line.ends_with(")")));
+        assert!(output.contains("Played 1 hands"));
+    }
+
+    #[test]
+    fn test_invalid_input_and_quit() {
+        let output = play(&["--seed", "5", "--games", "holdem"], "x\nb abc\nr 1\nq\n");
+        assert!(output.contains("Enter one of: fold, call 2, raise 4-200, or quit"));
+        assert!(output.contains("That is not a legal action"));
+        assert!(output.ends_with("Played 1 hands, you finished with 200 (+0)\n"));
+
+        // Quitting mid-hand loses what is in the pot
+        let output = play(&["--seed", "5", "--games", "holdem"], "c\nq\n");
+        assert!(output.ends_with("Played 1 hands, you finished with 198 (-2)\n"));
+    }
+
+    #[test]
+    fn test_parse_action() {
+        let options = BettingOptions {
+            current_bet: 2,
+            to_call: 2,
+            min_raise_to: Some(4),
+            max_raise_to: Some(100),
+            seat: 0,
+            bring_in: None,
+            completes: false,
+        };
+        let action = |line: &str| match parse_action(line, &options) {
+            Some(Input::Act(action)) => Some(action),
+            _ => None,
+        };
+        assert_eq!(action("c"), Some(Action::Call));
+        assert_eq!(action("r 10"), Some(Action::Raise(10)));
+        assert_eq!(action("raise"), Some(Action::Raise(4)));
+        assert_eq!(action("fold"), Some(Action::Fold));
+        assert_eq!(action("r ten"), None);
+        assert_eq!(choices(&options), "fold, call 2, raise 4-100");
+        assert!(
+            matches!(parse_discard("Kc Kd"), Some(Input::Act(Action::Discard(cards))) if cards.len() == 2)
+        );
+        assert!(parse_discard("Kc K").is_none());
+    }
+}
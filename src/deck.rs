@@ -2,7 +2,10 @@
 // Include a struct for Deck and tests
 
 use crate::card::{Card, Rank, Suit};
+use crate::error::Error;
+use crate::hand::{binomial, Hand};
 use rand::seq::SliceRandom;
+use rand::Rng;
 
 #[derive(Debug)]
 pub struct Deck {
@@ -24,14 +27,198 @@ impl Deck {
     pub fn shuffle(&mut self) {
         self.cards.shuffle(&mut rand::thread_rng());
     }
+
+    // Shuffle with a given RNG, e.g. a seeded one for reproducible deals
+    pub fn shuffle_with_rng<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards.shuffle(rng);
+    }
+
+    // Deal the top card, i.e. the first in `cards`
+    pub fn deal_card(&mut self) -> Option<Card> {
+        if self.cards.is_empty() {
+            return None;
+        }
+        Some(self.cards.remove(0))
+    }
+
+    pub fn deal(&mut self, count: usize) -> Result<Hand, Error> {
+        if count > self.cards.len() {
+            return Err(Error::NotEnoughCards);
+        }
+        Ok(Hand::from(self.cards.drain(..count).collect::<Vec<Card>>()))
+    }
+
+    // Every k-card subset of the cards left in the deck
+    pub fn combinations(&self, k: usize) -> Combinations {
+        Combinations::new(&self.cards, k)
+    }
+
+    // Same, leaving out the dead cards
+    pub fn combinations_excluding(&self, k: usize, dead: &[Card]) -> Combinations {
+        let cards: Vec<Card> = self
+            .cards
+            .iter()
+            .copied()
+            .filter(|card| !dead.contains(card))
+            .collect();
+        Combinations::new(&cards, k)
+    }
+}
+
+// Chunks enumerations are split into for `Combinations::map_chunks`, run in
+// parallel with the `parallel` feature. A fixed number rather than one per
+// thread, so that results merged in chunk order do not depend on the machine.
+pub const CHUNKS: usize = 64;
+
+// k-card subsets of a set of cards in colex order of their positions in it.
+// `next_cards` reuses one buffer; iterating yields a `Hand` per subset.
+#[derive(Debug, Clone)]
+pub struct Combinations {
+    cards: Vec<Card>,
+    indices: Vec<usize>,
+    chosen: Vec<Card>,
+    remaining: u64,
+}
+
+impl Combinations {
+    pub fn new(cards: &[Card], k: usize) -> Combinations {
+        let total = binomial(cards.len(), k);
+        Combinations::starting_at(cards.to_vec(), k, 0, total)
+    }
+
+    // `count` subsets from the `start`-th one on
+    fn starting_at(cards: Vec<Card>, k: usize, start: u64, count: u64) -> Combinations {
+        let mut indices: Vec<usize> = (0..k).collect();
+        if count == 0 {
+            return Combinations {
+                cards,
+                indices,
+                chosen: Vec::new(),
+                remaining: 0,
+            };
+        }
+        let mut rest = start;
+        let mut position = cards.len();
+        for i in (0..k).rev() {
+            // Highest position left whose binomial still fits
+            position -= 1;
+            while binomial(position, i + 1) > rest {
+                position -= 1;
+            }
+            rest -= binomial(position, i + 1);
+            indices[i] = position;
+        }
+        Combinations {
+            cards,
+            indices,
+            chosen: Vec::with_capacity(k),
+            remaining: count,
+        }
+    }
+
+    // Split the remaining subsets into at most `parts` runs of about the same
+    // size, e.g. one per worker, which together yield the same subsets
+    pub fn chunks(self, parts: usize) -> Vec<Combinations> {
+        let k = self.indices.len();
+        let start = binomial(self.cards.len(), k) - self.remaining;
+        let parts = (parts.max(1) as u64).min(self.remaining.max(1));
+        let size = self.remaining / parts;
+        let extra = self.remaining % parts;
+
+        let mut chunks: Vec<Combinations> = Vec::new();
+        let mut offset = start;
+        for part in 0..parts {
+            let count = size + u64::from(part < extra);
+            chunks.push(Combinations::starting_at(
+                self.cards.clone(),
+                k,
+                offset,
+                count,
+            ));
+            offset += count;
+        }
+        chunks
+    }
+
+    // Run `f` on each of `parts` chunks, usually `CHUNKS`, and return the results in chunk order,
+    // on all cores with the `parallel` feature. The chunks do not depend on the
+    // number of threads, so neither do results merged in this order.
+    pub fn map_chunks<T, F>(self, parts: usize, f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(Combinations) -> T + Send + Sync,
+    {
+        let chunks = self.chunks(parts);
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            chunks.into_par_iter().map(f).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            chunks.into_iter().map(f).collect()
+        }
+    }
+
+    // Next subset without allocating
+    pub fn next_cards(&mut self) -> Option<&[Card]> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.chosen.clear();
+        self.chosen
+            .extend(self.indices.iter().map(|&i| self.cards[i]));
+        self.remaining -= 1;
+        if self.remaining > 0 {
+            // Colex successor: bump the lowest index that can move, reset those below
+            let mut i = 0;
+            while i + 1 < self.indices.len() && self.indices[i] + 1 == self.indices[i + 1] {
+                self.indices[i] = i;
+                i += 1;
+            }
+            self.indices[i] += 1;
+        }
+        Some(&self.chosen)
+    }
 }
 
+impl Iterator for Combinations {
+    type Item = Hand;
+
+    fn next(&mut self) -> Option<Hand> {
+        self.next_cards().map(|cards| Hand::from(cards.to_vec()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Combinations {}
+
 impl Default for Deck {
     fn default() -> Self {
         Self::new()
     }
 }
 
+// Serialized like a `Hand`, top card first
+#[cfg(feature = "serde")]
+impl serde::Serialize for Deck {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&Hand::from(self.cards.clone()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Deck {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Deck, D::Error> {
+        let hand = <Hand as serde::Deserialize>::deserialize(deserializer)?;
+        Ok(Deck { cards: hand.cards })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -63,4 +250,118 @@ mod tests {
                 || (deck.cards[50] != Card::new(Suit::Spades, Rank::King))
         );
     }
+
+    #[test]
+    fn deck_should_shuffle_reproducibly_with_seed() {
+        use rand::SeedableRng;
+
+        let mut deck1 = Deck::new();
+        let mut deck2 = Deck::new();
+        deck1.shuffle_with_rng(&mut rand::rngs::StdRng::seed_from_u64(42));
+        deck2.shuffle_with_rng(&mut rand::rngs::StdRng::seed_from_u64(42));
+        assert_eq!(deck1.cards, deck2.cards);
+        assert_ne!(deck1.cards, Deck::new().cards);
+    }
+
+    #[test]
+    fn deal_from_top() {
+        let mut deck = Deck::new();
+        assert_eq!(deck.deal_card(), Some(Card::new(Suit::Clubs, Rank::Deuce)));
+        assert_eq!(deck.deal(3).unwrap().to_string(), "3c 4c 5c");
+        assert_eq!(deck.cards.len(), 48);
+        assert_eq!(deck.deal(49), Err(Error::NotEnoughCards));
+        assert_eq!(deck.deal(48).unwrap().cards.len(), 48);
+        assert_eq!(deck.deal_card(), None);
+    }
+
+    #[test]
+    fn combinations_count() {
+        let deck = Deck::new();
+        assert_eq!(deck.combinations(2).len(), 1326);
+        assert_eq!(deck.combinations(5).count(), 2_598_960);
+        assert_eq!(
+            deck.combinations(0).collect::<Vec<Hand>>(),
+            vec![Hand::new()]
+        );
+        assert_eq!(Combinations::new(&deck.cards[..2], 3).count(), 0);
+    }
+
+    #[test]
+    fn combinations_order() {
+        let cards = Hand::try_from("2c 3c 4c 5c").unwrap().cards;
+        let hands: Vec<String> = Combinations::new(&cards, 2)
+            .map(|hand| hand.to_string())
+            .collect();
+        assert_eq!(
+            hands,
+            ["2c 3c", "2c 4c", "3c 4c", "2c 5c", "3c 5c", "4c 5c"]
+        );
+        // Over cards in `ord_position` order, the n-th subset is `Hand::from_index(n)`
+        let cards: Vec<Card> = (0..52)
+            .map(|index| Hand::from_index(index, 1).unwrap().cards[0])
+            .collect();
+        for (index, hand) in Combinations::new(&cards, 3).enumerate().step_by(97) {
+            assert_eq!(hand, Hand::from_index(index as u64, 3).unwrap());
+        }
+    }
+
+    #[test]
+    fn combinations_excluding_dead_cards() {
+        let dead = Hand::try_from("As Kd").unwrap().cards;
+        let mut combinations = Deck::new().combinations_excluding(2, &dead);
+        assert_eq!(combinations.len(), 1225);
+        while let Some(cards) = combinations.next_cards() {
+            assert!(!cards.iter().any(|card| dead.contains(card)));
+        }
+    }
+
+    #[test]
+    fn combinations_chunks() {
+        let deck = Deck::new();
+        let all: Vec<Hand> = deck.combinations(3).collect();
+        let chunks = deck.combinations(3).chunks(7);
+        assert_eq!(chunks.len(), 7);
+        let lengths: Vec<usize> = chunks.iter().map(|chunk| chunk.len()).collect();
+        assert!(lengths.iter().all(|&len| len == 3157 || len == 3158));
+        let joined: Vec<Hand> = chunks.into_iter().flatten().collect();
+        assert_eq!(joined, all);
+
+        // A partly used iterator splits what is left
+        let mut combinations = deck.combinations(2);
+        combinations.nth(1000);
+        let rest: Vec<Hand> = combinations.clone().collect();
+        let joined: Vec<Hand> = combinations.chunks(4).into_iter().flatten().collect();
+        assert_eq!(joined, rest);
+        assert_eq!(deck.combinations(2).chunks(5000).len(), 1326);
+        assert_eq!(Combinations::new(&[], 1).chunks(3).len(), 1);
+    }
+
+    #[test]
+    fn map_chunks_in_order() {
+        let deck = Deck::new();
+        let counts = deck.combinations(2).map_chunks(8, |chunk| chunk.count());
+        assert_eq!(counts.len(), 8);
+        assert_eq!(counts.iter().sum::<usize>(), 1326);
+
+        let firsts = deck.combinations(2).map_chunks(8, |mut chunk| chunk.next());
+        let expected: Vec<Option<Hand>> = deck
+            .combinations(2)
+            .chunks(8)
+            .into_iter()
+            .map(|mut chunk| chunk.next())
+            .collect();
+        assert_eq!(firsts, expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_deck() {
+        let mut deck = Deck::new();
+        deck.cards.truncate(3);
+        assert_eq!(serde_json::to_string(&deck).unwrap(), "\"2c 3c 4c\"");
+
+        let json = serde_json::to_string(&Deck::new()).unwrap();
+        let deck: Deck = serde_json::from_str(&json).unwrap();
+        assert_eq!(deck.cards, Deck::new().cards);
+    }
 }
@@ -0,0 +1,364 @@
+// Which cards to throw in draw games: every discard option with the chance
+// of each final hand, either exact for the last draw or sampled over several
+// draws, ranked by the expected strength of the final hand, i.e. the chance
+// of it beating or tying a random hand of the same size
+
+use crate::card::{mask, Card};
+use crate::deck::{Combinations, Deck, CHUNKS};
+use crate::error::Error;
+use crate::evaluation::badugi::Badugi;
+use crate::evaluation::high::High;
+use crate::evaluation::lowball_2to7::Lowball2to7;
+use crate::evaluation::lowball_ato5::LowballAto5;
+use crate::evaluation::strength::HandSpace;
+use crate::evaluation::EvalHand;
+use crate::game::strategy::discards;
+use crate::game::Variant;
+use crate::hand::Hand;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+// Final hands scored at once with `EvalHand::eval_masks`
+const BATCH: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscardOption {
+    // Empty to stand pat
+    pub discards: Hand,
+    // Chance of each final score, best first
+    pub scores: Vec<(u128, f64)>,
+    // Chance of each final hand in words, best first, e.g. ("7 low", 0.08)
+    pub outcomes: Vec<(String, f64)>,
+    // Expected percentile of the final hand among all hands of its size
+    pub strength: f64,
+}
+
+impl DiscardOption {
+    // Chance of finishing with `score` or better, e.g. the score of
+    // "7c 6d 5h 4s 3c" for any 7-low in A-5
+    pub fn at_least(&self, score: u128) -> f64 {
+        self.scores
+            .iter()
+            .take_while(|&&(final_score, _)| final_score >= score)
+            .map(|&(_, chance)| chance)
+            .sum()
+    }
+}
+
+// Every discard option for the last draw, enumerating every replacement from
+// the cards not in `hand` or `dead`. Supported are single-winner draw games:
+// 5-card draw, A-5 and 2-7 lowball and Badugi.
+pub fn exact(variant: Variant, hand: &Hand, dead: &[Card]) -> Result<Vec<DiscardOption>, Error> {
+    match variant {
+        Variant::FiveCardDraw => exact_with::<High>(variant, hand, dead),
+        Variant::AceToFiveTripleDraw => exact_with::<LowballAto5>(variant, hand, dead),
+        Variant::DeuceToSevenSingleDraw | Variant::DeuceToSevenTripleDraw => {
+            exact_with::<Lowball2to7>(variant, hand, dead)
+        }
+        Variant::Badugi => exact_with::<Badugi>(variant, hand, dead),
+        _ => Err(Error::UnsupportedGame),
+    }
+}
+
+// Every discard option with `draws` draws left, sampling `iterations` runs of
+// the draws each. Cards kept now are kept to the end, and later draws throw
+// the other cards by `strategy::discards`.
+pub fn monte_carlo<R: Rng + ?Sized>(
+    variant: Variant,
+    hand: &Hand,
+    dead: &[Card],
+    draws: u8,
+    iterations: usize,
+    rng: &mut R,
+) -> Result<Vec<DiscardOption>, Error> {
+    match variant {
+        Variant::FiveCardDraw => {
+            monte_carlo_with::<High, R>(variant, hand, dead, draws, iterations, rng)
+        }
+        Variant::AceToFiveTripleDraw => {
+            monte_carlo_with::<LowballAto5, R>(variant, hand, dead, draws, iterations, rng)
+        }
+        Variant::DeuceToSevenSingleDraw | Variant::DeuceToSevenTripleDraw => {
+            monte_carlo_with::<Lowball2to7, R>(variant, hand, dead, draws, iterations, rng)
+        }
+        Variant::Badugi => {
+            monte_carlo_with::<Badugi, R>(variant, hand, dead, draws, iterations, rng)
+        }
+        _ => Err(Error::UnsupportedGame),
+    }
+}
+
+fn exact_with<T: EvalHand + 'static>(
+    variant: Variant,
+    hand: &Hand,
+    dead: &[Card],
+) -> Result<Vec<DiscardOption>, Error> {
+    let unseen = prepare(variant, hand, dead)?;
+    let mut options: Vec<DiscardOption> = Vec::new();
+    for (kept, thrown) in subsets(hand) {
+        let kept = mask(&kept);
+        let partials = Combinations::new(&unseen, thrown.len()).map_chunks(CHUNKS, |mut draws| {
+            let mut tally = Tally::default();
+            let mut masks: Vec<u64> = Vec::with_capacity(BATCH);
+            let mut scores: Vec<u128> = vec![0; BATCH];
+            while let Some(drawn) = draws.next_cards() {
+                masks.push(kept | mask(drawn));
+                if masks.len() == BATCH {
+                    tally.add_masks::<T>(&masks, &mut scores);
+                    masks.clear();
+                }
+            }
+            tally.add_masks::<T>(&masks, &mut scores);
+            tally
+        });
+        let mut tally = Tally::default();
+        for partial in partials {
+            tally.merge(partial);
+        }
+        options.push(tally.finish::<T>(Hand::from(thrown), hand.cards.len())?);
+    }
+    Ok(rank(options))
+}
+
+fn monte_carlo_with<T: EvalHand + 'static, R: Rng + ?Sized>(
+    variant: Variant,
+    hand: &Hand,
+    dead: &[Card],
+    draws: u8,
+    iterations: usize,
+    rng: &mut R,
+) -> Result<Vec<DiscardOption>, Error> {
+    let mut stub = prepare(variant, hand, dead)?;
+    if draws == 0 || iterations == 0 {
+        return Err(Error::InvalidSetup);
+    }
+    let mut options: Vec<DiscardOption> = Vec::new();
+    for (kept, thrown) in subsets(hand) {
+        let mut tally = Tally::default();
+        for _ in 0..iterations {
+            stub.shuffle(rng);
+            let mut stub = stub.iter().copied();
+            let mut current: Vec<Card> = kept.clone();
+            current.extend(stub.by_ref().take(thrown.len()));
+            for _ in 1..draws {
+                let throw: Vec<Card> = discards(variant, &Hand::from(current.clone()))
+                    .into_iter()
+                    .filter(|card| !kept.contains(card))
+                    .collect();
+                current.retain(|card| !throw.contains(card));
+                current.extend(stub.by_ref().take(throw.len()));
+            }
+            if current.len() != hand.cards.len() {
+                return Err(Error::NotEnoughCards);
+            }
+            tally.add(T::eval_hand(&Hand::from(current))?);
+        }
+        options.push(tally.finish::<T>(Hand::from(thrown), hand.cards.len())?);
+    }
+    Ok(rank(options))
+}
+
+// Cards left to draw from, after checking the hand is a full one of the
+// variant without cards in common with `dead`
+fn prepare(variant: Variant, hand: &Hand, dead: &[Card]) -> Result<Vec<Card>, Error> {
+    let used = [&hand.cards[..], dead].concat();
+    if hand.cards.len() != variant.hole_cards() || mask(&used).count_ones() as usize != used.len() {
+        return Err(Error::InvalidHand);
+    }
+    Ok(Deck::new()
+        .cards
+        .into_iter()
+        .filter(|card| !used.contains(card))
+        .collect())
+}
+
+// Every split of the hand into kept and thrown cards, standing pat first
+fn subsets(hand: &Hand) -> Vec<(Vec<Card>, Vec<Card>)> {
+    let count = hand.cards.len();
+    let mut splits: Vec<u32> = (0..1u32 << count).collect();
+    splits.sort_by_key(|split| split.count_ones());
+    splits
+        .into_iter()
+        .map(|split| {
+            let pick = |thrown: bool| -> Vec<Card> {
+                (0..count)
+                    .filter(|i| (split & (1 << i) != 0) == thrown)
+                    .map(|i| hand.cards[i])
+                    .collect()
+            };
+            (pick(false), pick(true))
+        })
+        .collect()
+}
+
+// Strongest first, fewer discards first among equals
+fn rank(mut options: Vec<DiscardOption>) -> Vec<DiscardOption> {
+    options.sort_by(|a, b| b.strength.total_cmp(&a.strength));
+    options
+}
+
+// Final hands by score
+#[derive(Debug, Default)]
+struct Tally {
+    counts: HashMap<u128, u64>,
+    total: u64,
+}
+
+impl Tally {
+    fn add(&mut self, score: u128) {
+        *self.counts.entry(score).or_insert(0) += 1;
+        self.total += 1;
+    }
+
+    fn add_masks<T: EvalHand>(&mut self, masks: &[u64], scores: &mut [u128]) {
+        let scores = &mut scores[..masks.len()];
+        T::eval_masks(masks, scores);
+        for &score in scores.iter() {
+            self.add(score);
+        }
+    }
+
+    fn merge(&mut self, other: Tally) {
+        for (score, count) in other.counts {
+            *self.counts.entry(score).or_insert(0) += count;
+        }
+        self.total += other.total;
+    }
+
+    fn finish<T: EvalHand + 'static>(
+        self,
+        discards: Hand,
+        size: usize,
+    ) -> Result<DiscardOption, Error> {
+        let space = HandSpace::cached::<T>(size)?;
+        let total = self.total.max(1) as f64;
+        let mut scores: Vec<(u128, u64)> = self.counts.into_iter().collect();
+        scores.sort_unstable_by_key(|&(score, _)| Reverse(score));
+
+        let mut outcomes: Vec<(String, f64)> = Vec::new();
+        let mut strength = 0.0;
+        for &(score, count) in &scores {
+            let chance = count as f64 / total;
+            strength += space.percentile(score) * chance;
+            let description = T::describe(score);
+            match outcomes.last_mut() {
+                Some((last, sum)) if *last == description => *sum += chance,
+                _ => outcomes.push((description, chance)),
+            }
+        }
+        Ok(DiscardOption {
+            discards,
+            scores: scores
+                .into_iter()
+                .map(|(score, count)| (score, count as f64 / total))
+                .collect(),
+            outcomes,
+            strength,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::hand;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn option<'a>(options: &'a [DiscardOption], discards: &str) -> &'a DiscardOption {
+        options
+            .iter()
+            .find(|option| option.discards == hand(discards))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_subsets() {
+        let splits = subsets(&hand("As 2h 3d"));
+        assert_eq!(splits.len(), 8);
+        assert_eq!(splits[0], (hand("As 2h 3d").cards, Vec::new()));
+        assert_eq!(splits[7], (Vec::new(), hand("As 2h 3d").cards));
+    }
+
+    #[test]
+    fn test_exact_badugi() {
+        let options = exact(Variant::Badugi, &hand("As 2h 3d 3c"), &[]).unwrap();
+        assert_eq!(options.len(), 16);
+        let chances: f64 = options[0].outcomes.iter().map(|(_, chance)| chance).sum();
+        assert!((chances - 1.0).abs() < 1e-9);
+
+        // Drawing one to A-2-3: the 10 clubs from 4 up make a badugi
+        // Throwing either trey is as good
+        let draw = option(&options, "3c");
+        assert_eq!(options[0].strength, draw.strength);
+        assert_eq!(options[1].strength, draw.strength);
+        assert!(options[2].strength < draw.strength);
+        assert_eq!(draw.outcomes[0].0, "Badugi");
+        assert!((draw.outcomes[0].1 - 10.0 / 48.0).abs() < 1e-9);
+        let badugi = Badugi::eval_hand(&hand("Ks Qh Jd Tc")).unwrap();
+        assert!((draw.at_least(badugi) - 10.0 / 48.0).abs() < 1e-9);
+
+        // Standing pat keeps the 3-card hand
+        let pat = option(&options, "");
+        assert_eq!(pat.outcomes, [("3-card hand".to_string(), 1.0)]);
+        assert!(pat.strength < draw.strength);
+    }
+
+    #[test]
+    fn test_exact_lowball_dead_cards() {
+        // Every card from 9 up is out, so drawing to A-2-3-4 makes a low
+        let dead: Vec<Card> = Deck::new()
+            .cards
+            .into_iter()
+            .filter(|card| {
+                card.rank >= crate::card::Rank::Nine && card.rank != crate::card::Rank::Ace
+            })
+            .filter(|card| !hand("As 2h 3d 4c Kc").cards.contains(card))
+            .collect();
+        let options = exact(Variant::AceToFiveTripleDraw, &hand("As 2h 3d 4c Kc"), &dead).unwrap();
+        let draw = option(&options, "Kc");
+        assert_eq!(options[0], *draw);
+        // 4 fives, 4 sixes, 4 sevens and 4 eights out of 28 cards
+        let eight = LowballAto5::eval_hand(&hand("8c 7d 6h 5s 4c")).unwrap();
+        assert!((draw.at_least(eight) - 16.0 / 28.0).abs() < 1e-9);
+        assert_eq!(draw.outcomes[0], ("5 low".to_string(), 4.0 / 28.0));
+    }
+
+    #[test]
+    fn test_monte_carlo_more_draws_more_badugis() {
+        let cards = hand("As 2h 3d 3c");
+        let badugi = Badugi::eval_hand(&hand("Ks Qh Jd Tc")).unwrap();
+        let chance = |draws: u8| {
+            let mut rng = StdRng::seed_from_u64(11);
+            let options = monte_carlo(Variant::Badugi, &cards, &[], draws, 2000, &mut rng).unwrap();
+            option(&options, "3c").at_least(badugi)
+        };
+        let one = chance(1);
+        let three = chance(3);
+        assert!((one - 10.0 / 48.0).abs() < 0.05);
+        assert!(three > one + 0.2);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            exact(Variant::Holdem, &hand("As Kd"), &[]),
+            Err(Error::UnsupportedGame)
+        );
+        assert_eq!(
+            exact(Variant::Badugi, &hand("As 2h 3d"), &[]),
+            Err(Error::InvalidHand)
+        );
+        assert_eq!(
+            exact(Variant::Badugi, &hand("As 2h 3d 4c"), &hand("4c").cards),
+            Err(Error::InvalidHand)
+        );
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(
+            monte_carlo(Variant::Badugi, &hand("As 2h 3d 4c"), &[], 0, 10, &mut rng),
+            Err(Error::InvalidSetup)
+        );
+    }
+}
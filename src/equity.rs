@@ -0,0 +1,453 @@
+// Equity of weighted ranges against each other in community card games,
+// either by enumerating every combo matchup and board, or by sampling them
+
+use crate::card::{mask, Card};
+use crate::deck::{Deck, CHUNKS};
+use crate::error::Error;
+use crate::game::community::{best_high, best_low};
+use crate::game::{Gameplay, Variant};
+use crate::hand::Hand;
+use crate::range::{Combo, Range};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+// Consecutive rejected samples before giving up on ranges that block each other
+const MAX_REJECTIONS: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Equity {
+    // Expected share of the pot
+    pub equity: f64,
+    // Share of showdowns scooped alone
+    pub win: f64,
+    // Share of showdowns with part of the pot
+    pub tie: f64,
+}
+
+// Enumerate every combo matchup, weighted by the product of the combo
+// weights, and every way to complete the board
+pub fn exact(
+    variant: Variant,
+    ranges: &[Range],
+    board: &Hand,
+    dead: &[Card],
+) -> Result<Vec<Equity>, Error> {
+    let ranges = prepare(variant, ranges, board, dead)?;
+    let used = mask(&board.cards) | mask(dead);
+    let mut tally = Tally::new(ranges.len());
+    let mut chosen: Vec<&Combo> = Vec::new();
+    each_matchup(&ranges, used, &mut chosen, &mut |combos, used| {
+        let weight: f64 = combos.iter().map(|combo| combo.weight).product();
+        let dead: Vec<Card> = Deck::new()
+            .cards
+            .into_iter()
+            .filter(|&card| mask(&[card]) & used != 0)
+            .collect();
+        let partials = Deck::new()
+            .combinations_excluding(5 - board.cards.len(), &dead)
+            .map_chunks(CHUNKS, |mut boards| {
+                let mut partial = Tally::new(combos.len());
+                while let Some(extra) = boards.next_cards() {
+                    let board = Hand::from([&board.cards[..], extra].concat());
+                    partial.add(&showdown(variant, combos, &board)?, weight);
+                }
+                Ok(partial)
+            });
+        for partial in partials {
+            tally.merge(partial?);
+        }
+        Ok(())
+    })?;
+    tally.finish()
+}
+
+// Sample `iterations` matchups by weight, each with a random board completion
+pub fn monte_carlo<R: Rng + ?Sized>(
+    variant: Variant,
+    ranges: &[Range],
+    board: &Hand,
+    dead: &[Card],
+    iterations: usize,
+    rng: &mut R,
+) -> Result<Vec<Equity>, Error> {
+    let ranges = prepare(variant, ranges, board, dead)?;
+    let used = mask(&board.cards) | mask(dead);
+    let cumulative: Vec<Vec<f64>> = ranges
+        .iter()
+        .map(|range| {
+            range
+                .combos()
+                .iter()
+                .scan(0.0, |total, combo| {
+                    *total += combo.weight;
+                    Some(*total)
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut tally = Tally::new(ranges.len());
+    let mut combos: Vec<&Combo> = Vec::with_capacity(ranges.len());
+    for _ in 0..iterations {
+        let mut rejections = 0;
+        let held = loop {
+            combos.clear();
+            let mut held = used;
+            for (range, cumulative) in ranges.iter().zip(&cumulative) {
+                let total = cumulative[cumulative.len() - 1];
+                let x = rng.gen::<f64>() * total;
+                let i = cumulative
+                    .partition_point(|&c| c <= x)
+                    .min(cumulative.len() - 1);
+                let combo = &range.combos()[i];
+                let cards = mask(&combo.hand.cards);
+                if held & cards != 0 {
+                    break;
+                }
+                held |= cards;
+                combos.push(combo);
+            }
+            if combos.len() == ranges.len() {
+                break held;
+            }
+            rejections += 1;
+            if rejections == MAX_REJECTIONS {
+                return Err(Error::EmptyRange);
+            }
+        };
+
+        let mut rest: Vec<Card> = Deck::new()
+            .cards
+            .into_iter()
+            .filter(|&card| mask(&[card]) & held == 0)
+            .collect();
+        let (extra, _) = rest.partial_shuffle(rng, 5 - board.cards.len());
+        let board = Hand::from([&board.cards[..], extra].concat());
+        tally.add(&showdown(variant, &combos, &board)?, 1.0);
+    }
+    tally.finish()
+}
+
+// Ranges without blocked or zero-weight combos, after checking the setup
+fn prepare(
+    variant: Variant,
+    ranges: &[Range],
+    board: &Hand,
+    dead: &[Card],
+) -> Result<Vec<Range>, Error> {
+    if variant.gameplay() != Gameplay::Community {
+        return Err(Error::UnsupportedGame);
+    }
+    let blocked = [&board.cards[..], dead].concat();
+    if ranges.len() < 2
+        || ![0, 3, 4, 5].contains(&board.cards.len())
+        || mask(&blocked).count_ones() as usize != blocked.len()
+    {
+        return Err(Error::InvalidSetup);
+    }
+
+    let mut prepared: Vec<Range> = Vec::new();
+    for range in ranges {
+        let mut live = Range::new();
+        for combo in range.combos() {
+            if combo.hand.cards.len() != variant.hole_cards() {
+                return Err(Error::InvalidHand);
+            }
+            if combo.weight > 0.0 && mask(&combo.hand.cards) & mask(&blocked) == 0 {
+                live.add(combo.hand.clone(), combo.weight);
+            }
+        }
+        if live.is_empty() {
+            return Err(Error::EmptyRange);
+        }
+        prepared.push(live);
+    }
+    Ok(prepared)
+}
+
+// Call `f` with one combo from every range, none sharing a card
+fn each_matchup<'a, F>(
+    ranges: &'a [Range],
+    used: u64,
+    chosen: &mut Vec<&'a Combo>,
+    f: &mut F,
+) -> Result<(), Error>
+where
+    F: FnMut(&[&'a Combo], u64) -> Result<(), Error>,
+{
+    let range = match ranges.get(chosen.len()) {
+        Some(range) => range,
+        None => return f(chosen, used),
+    };
+    for combo in range.combos() {
+        let cards = mask(&combo.hand.cards);
+        if used & cards == 0 {
+            chosen.push(combo);
+            each_matchup(ranges, used | cards, chosen, f)?;
+            chosen.pop();
+        }
+    }
+    Ok(())
+}
+
+// Share of the pot of every player at the showdown
+fn showdown(variant: Variant, combos: &[&Combo], board: &Hand) -> Result<Vec<f64>, Error> {
+    let mut high: Vec<Option<u128>> = Vec::with_capacity(combos.len());
+    let mut low: Vec<Option<u128>> = Vec::with_capacity(combos.len());
+    for combo in combos {
+        high.push(Some(best_high(variant, &combo.hand, board)?.score));
+        low.push(if variant.is_split_pot() {
+            best_low(&combo.hand, board)?.map(|evaluation| evaluation.score)
+        } else {
+            None
+        });
+    }
+
+    let mut shares = vec![0.0; combos.len()];
+    let has_low = low.iter().any(Option::is_some);
+    split(&mut shares, &high, if has_low { 0.5 } else { 1.0 });
+    if has_low {
+        split(&mut shares, &low, 0.5);
+    }
+    Ok(shares)
+}
+
+// Split `amount` between the best scores
+fn split(shares: &mut [f64], scores: &[Option<u128>], amount: f64) {
+    let best = scores.iter().flatten().max().copied();
+    let winners = scores.iter().filter(|&&score| score == best).count();
+    for (share, &score) in shares.iter_mut().zip(scores) {
+        if best.is_some() && score == best {
+            *share += amount / winners as f64;
+        }
+    }
+}
+
+struct Tally {
+    equity: Vec<f64>,
+    win: Vec<f64>,
+    tie: Vec<f64>,
+    total: f64,
+}
+
+impl Tally {
+    fn new(players: usize) -> Tally {
+        Tally {
+            equity: vec![0.0; players],
+            win: vec![0.0; players],
+            tie: vec![0.0; players],
+            total: 0.0,
+        }
+    }
+
+    fn add(&mut self, shares: &[f64], weight: f64) {
+        for (i, &share) in shares.iter().enumerate() {
+            self.equity[i] += share * weight;
+            if share == 1.0 {
+                self.win[i] += weight;
+            } else if share > 0.0 {
+                self.tie[i] += weight;
+            }
+        }
+        self.total += weight;
+    }
+
+    fn merge(&mut self, other: Tally) {
+        for i in 0..self.equity.len() {
+            self.equity[i] += other.equity[i];
+            self.win[i] += other.win[i];
+            self.tie[i] += other.tie[i];
+        }
+        self.total += other.total;
+    }
+
+    fn finish(self) -> Result<Vec<Equity>, Error> {
+        if self.total == 0.0 {
+            return Err(Error::EmptyRange);
+        }
+        Ok((0..self.equity.len())
+            .map(|i| Equity {
+                equity: self.equity[i] / self.total,
+                win: self.win[i] / self.total,
+                tie: self.tie[i] / self.total,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn ranges(ranges: &[&str]) -> Vec<Range> {
+        ranges
+            .iter()
+            .map(|s| Range::try_from(*s).unwrap())
+            .collect()
+    }
+
+    fn board(s: &str) -> Hand {
+        Hand::try_from(s).unwrap()
+    }
+
+    #[test]
+    fn test_invalid_setup() {
+        let holdem = ranges(&["AA", "KK"]);
+        assert_eq!(
+            exact(Variant::Razz, &holdem, &board(""), &[]),
+            Err(Error::UnsupportedGame)
+        );
+        assert_eq!(
+            exact(Variant::Holdem, &holdem[..1], &board(""), &[]),
+            Err(Error::InvalidSetup)
+        );
+        assert_eq!(
+            exact(Variant::Holdem, &holdem, &board("2c 3c"), &[]),
+            Err(Error::InvalidSetup)
+        );
+        assert_eq!(
+            exact(Variant::Omaha, &holdem, &board("2c 3c 4c"), &[]),
+            Err(Error::InvalidHand)
+        );
+        // Every ace is dead
+        let dead = board("As Ah Ad Ac").cards;
+        assert_eq!(
+            exact(Variant::Holdem, &holdem, &board(""), &dead),
+            Err(Error::EmptyRange)
+        );
+    }
+
+    #[test]
+    fn test_exact_river() {
+        // A set of aces beats every kings
+        let result = exact(
+            Variant::Holdem,
+            &ranges(&["AA", "KK"]),
+            &board("As 2c 7d 9h Jc"),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(result[0].equity, 1.0);
+        assert_eq!(result[0].win, 1.0);
+        assert_eq!(result[1].equity, 0.0);
+
+        // Both make broadway
+        let result = exact(
+            Variant::Holdem,
+            &ranges(&["AKs", "AKo"]),
+            &board("Qc Jd Th 2s 3c"),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(result[0].equity, 0.5);
+        assert_eq!(result[0].tie, 1.0);
+    }
+
+    #[test]
+    fn test_exact_weights() {
+        // Aces beat KQs but not a set of eights
+        let board = board("Kd 8s 4c 2h 3d");
+        let even = exact(Variant::Holdem, &ranges(&["AA", "KQs, 88"]), &board, &[]).unwrap();
+        let weighted = exact(
+            Variant::Holdem,
+            &ranges(&["AA", "KQs, 88:0.5"]),
+            &board,
+            &[],
+        )
+        .unwrap();
+        assert!((even[0].equity - 0.5).abs() < 1e-9);
+        assert!((weighted[0].equity - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_exact_turn_three_way() {
+        let result = exact(
+            Variant::Holdem,
+            &ranges(&["AhAd", "KsKc", "7h6h"]),
+            &board("Kh 8h 5c 2d"),
+            &[],
+        )
+        .unwrap();
+        let total: f64 = result.iter().map(|equity| equity.equity).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        // Kings with a set ahead of the straight flush draw
+        assert!(result[1].equity > result[2].equity);
+        assert!(result[2].equity > result[0].equity);
+    }
+
+    #[test]
+    fn test_omaha_hi_lo_split() {
+        let result = exact(
+            Variant::OmahaHiLo,
+            &ranges(&["AhAd2c3c", "KsKhQdQc"]),
+            &board("As Kd 4h 5s 9c"),
+            &[],
+        )
+        .unwrap();
+        // Aces scoop with the wheel, high and low
+        assert_eq!(result[0].equity, 1.0);
+
+        let result = exact(
+            Variant::OmahaHiLo,
+            &ranges(&["2c3cJhJd", "KsKhQdQc"]),
+            &board("Ad Kd 7h 8s 9c"),
+            &[],
+        )
+        .unwrap();
+        // Low for one, set of kings for the other
+        assert_eq!(result[0].equity, 0.5);
+        assert_eq!(result[0].tie, 1.0);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_exact_same_on_any_thread_count() {
+        let ranges = ranges(&["AKs, QQ", "JTs, 77"]);
+        let board = board("Ah 7d 2c");
+        let run = |threads: usize| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap()
+                .install(|| exact(Variant::Holdem, &ranges, &board, &[]).unwrap())
+        };
+        assert_eq!(run(1), run(4));
+    }
+
+    #[test]
+    fn test_monte_carlo() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let result = monte_carlo(
+            Variant::Holdem,
+            &ranges(&["AA", "KK"]),
+            &board(""),
+            &[],
+            20_000,
+            &mut rng,
+        )
+        .unwrap();
+        // About 82% for aces against kings
+        assert!((result[0].equity - 0.82).abs() < 0.02);
+
+        // Reproducible with the same seed
+        let again = monte_carlo(
+            Variant::Holdem,
+            &ranges(&["AA", "KK"]),
+            &board(""),
+            &[],
+            20_000,
+            &mut StdRng::seed_from_u64(42),
+        )
+        .unwrap();
+        assert_eq!(result, again);
+
+        // Close to the exact equity on the turn
+        let ranges = ranges(&["QQ+, AKs", "JJ-99, AQs"]);
+        let turn = board("Jh 9c 3d 2s");
+        let exact = exact(Variant::Holdem, &ranges, &turn, &[]).unwrap();
+        let sampled = monte_carlo(Variant::Holdem, &ranges, &turn, &[], 20_000, &mut rng).unwrap();
+        assert!((exact[0].equity - sampled[0].equity).abs() < 0.02);
+    }
+}
@@ -3,4 +3,30 @@ pub enum Error {
     InvalidHand,
     InvalidCardNotation,
     InvalidHandNotation,
+    NotEnoughCards,
+    InvalidSetup,
+    InvalidAction,
+    InvalidHandHistory,
+    UnsupportedGame,
+    InvalidRangeNotation,
+    EmptyRange,
 }
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::InvalidHand => write!(f, "invalid hand"),
+            Error::InvalidCardNotation => write!(f, "invalid card notation"),
+            Error::InvalidHandNotation => write!(f, "invalid hand notation"),
+            Error::NotEnoughCards => write!(f, "not enough cards"),
+            Error::InvalidSetup => write!(f, "invalid setup"),
+            Error::InvalidAction => write!(f, "invalid action"),
+            Error::InvalidHandHistory => write!(f, "invalid hand history"),
+            Error::UnsupportedGame => write!(f, "unsupported game"),
+            Error::InvalidRangeNotation => write!(f, "invalid range notation"),
+            Error::EmptyRange => write!(f, "empty range"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,125 @@
+use super::*;
+
+// Badugi: the largest set of cards with no two of the same rank or suit,
+// then the lowest. Aces are low.
+#[derive(Default, Debug)]
+pub struct Badugi {}
+
+impl Badugi {
+    // Ace is low
+    const RANKS: [u128; 13] = [2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096, 1];
+    const ALL_RANKS: u128 = (1 << 13) - 1;
+    const SIZE_MULTIPLIER: u128 = 1 << 13;
+
+    // Number of cards playing in the hand's score, 4 being a badugi
+    pub fn size(score: u128) -> u32 {
+        (score / Self::SIZE_MULTIPLIER) as u32
+    }
+}
+
+impl EvalHand for Badugi {
+    fn eval_hand(hand: &Hand) -> Result<u128, Error> {
+        if hand.cards.len() != 4 {
+            return Err(Error::InvalidHand);
+        }
+
+        let mut best: u128 = 0;
+        for subset in 1..(1u8 << 4) {
+            let mut ranks: u128 = 0;
+            let mut suits: u8 = 0;
+            let mut valid = true;
+            for (index, card) in hand.cards.iter().enumerate() {
+                if subset & (1 << index) == 0 {
+                    continue;
+                }
+                let rank = Self::RANKS[card.rank as usize];
+                let suit = 1 << (card.suit as u8);
+                if ranks & rank != 0 || suits & suit != 0 {
+                    valid = false;
+                    break;
+                }
+                ranks |= rank;
+                suits |= suit;
+            }
+            if valid {
+                // More cards first, then the lower ones
+                let size = subset.count_ones() as u128;
+                best = best.max(size * Self::SIZE_MULTIPLIER + (Self::ALL_RANKS - ranks));
+            }
+        }
+        Ok(best)
+    }
+
+    fn describe(score: u128) -> String {
+        match Self::size(score) {
+            4 => "Badugi".to_string(),
+            size => format!("{}-card hand", size),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::Hand;
+    use std::cmp::Ordering;
+
+    fn size_of(s: &str) -> u32 {
+        Badugi::size(Badugi::eval_hand(&Hand::try_from(s).unwrap()).unwrap())
+    }
+
+    #[test]
+    fn test_eval_hand_invalid() {
+        assert_eq!(
+            Badugi::eval_hand(&Hand::try_from("Ac 2d 3h").unwrap()),
+            Err(Error::InvalidHand)
+        );
+        assert_eq!(
+            Badugi::eval_hand(&Hand::try_from("Ac 2d 3h 4s 5c").unwrap()),
+            Err(Error::InvalidHand)
+        );
+    }
+
+    #[test]
+    fn test_size() {
+        assert_eq!(size_of("Ac 2d 3h 4s"), 4);
+        assert_eq!(size_of("Ac 2c 3h 4s"), 3);
+        assert_eq!(size_of("Ac Ad 3h 4s"), 3);
+        assert_eq!(size_of("Ac 2c 3d 4d"), 2);
+        assert_eq!(size_of("Ac 2c 3c 4c"), 1);
+        assert_eq!(size_of("Kc Kd Kh Ks"), 1);
+    }
+
+    #[test]
+    fn test_compare_hands() {
+        let cmp = |a: &str, b: &str| {
+            Badugi::compare_hands(&Hand::try_from(a).unwrap(), &Hand::try_from(b).unwrap())
+        };
+
+        assert_eq!(cmp("Ac 2d 3h 4s", "Ac 2d 3h 5s"), Ordering::Greater);
+        assert_eq!(cmp("Kc Qd Jh Ts", "Ac 2d 3h 3s"), Ordering::Greater); // four cards beat three
+        assert_eq!(cmp("8c 2d 3h 4s", "7c 6d 5h 4s"), Ordering::Less); // highest card first
+        assert_eq!(cmp("Ac 2d 3h 3s", "Ac 2d 4h 4s"), Ordering::Greater);
+        // The best three cards play: A-2-3 from a hand with a paired suit
+        assert_eq!(cmp("Ac 2d 3h 4h", "Ac 2d 3h 3s"), Ordering::Equal);
+        assert_eq!(cmp("Ac 2d 3h 4s", "As 2h 3d 4c"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_direct_comparison() {
+        let h_badugi = Evaluation::<Badugi>::try_from("Kc Qd Jh Ts").unwrap();
+        let h_three = Evaluation::<Badugi>::try_from("Ac 2d 3h 3s").unwrap();
+        let h_two = Evaluation::<Badugi>::try_from("Ac 2c 3d 4d").unwrap();
+
+        assert!(h_badugi > h_three);
+        assert!(h_three > h_two);
+    }
+
+    #[test]
+    fn test_describe() {
+        let describe =
+            |s: &str| Badugi::describe(Badugi::eval_hand(&Hand::try_from(s).unwrap()).unwrap());
+        assert_eq!(describe("Ac 2d 3h 4s"), "Badugi");
+        assert_eq!(describe("Ac 2c 3h 4s"), "3-card hand");
+    }
+}
@@ -0,0 +1,93 @@
+use super::lowball_ato5::LowballAto5;
+use super::*;
+use crate::card::Rank;
+
+// A-5 low that only qualifies with five unpaired cards of eight or lower,
+// e.g. the low half of Omaha Hi-Lo and Seven Card Stud Hi-Lo.
+// A non-qualifying hand scores 0, below every qualifying low.
+#[derive(Default, Debug)]
+pub struct EightOrBetter {}
+
+impl EightOrBetter {
+    pub fn qualifies(hand: &Hand) -> bool {
+        let mut seen: [bool; 13] = [false; 13];
+        for card in &hand.cards {
+            let index = card.rank as usize;
+            if seen[index] || (card.rank > Rank::Eight && card.rank != Rank::Ace) {
+                return false;
+            }
+            seen[index] = true;
+        }
+        true
+    }
+}
+
+impl EvalHand for EightOrBetter {
+    fn eval_hand(hand: &Hand) -> Result<u128, Error> {
+        let score = LowballAto5::eval_hand(hand)?;
+        if !Self::qualifies(hand) {
+            return Ok(0);
+        }
+        Ok(score)
+    }
+
+    fn describe(score: u128) -> String {
+        match score {
+            0 => "No low".to_string(),
+            _ => LowballAto5::describe(score),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::Hand;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn test_eval_hand() {
+        assert_eq!(
+            EightOrBetter::eval_hand(&Hand::try_from("Ac 2c 3d 4h").unwrap()),
+            Err(Error::InvalidHand)
+        );
+        assert_eq!(
+            EightOrBetter::eval_hand(&Hand::try_from("Ac 2c 3d 4h 5s").unwrap()),
+            LowballAto5::eval_hand(&Hand::try_from("Ac 2c 3d 4h 5s").unwrap())
+        );
+        assert_eq!(
+            EightOrBetter::eval_hand(&Hand::try_from("Ac 2c 3d 4h 9s").unwrap()),
+            Ok(0)
+        );
+        assert_eq!(
+            EightOrBetter::eval_hand(&Hand::try_from("Ac 2c 3d 3h 5s").unwrap()),
+            Ok(0)
+        );
+        assert!(EightOrBetter::eval_hand(&Hand::try_from("8c 7c 6d 4h 5s").unwrap()).unwrap() > 0);
+    }
+
+    #[test]
+    fn test_compare_hands() {
+        let h_wheel = Hand::try_from("Ac 2c 3d 4h 5s").unwrap();
+        let h_eight = Hand::try_from("8c 7c 6d 4h 5s").unwrap();
+        let h_nine = Hand::try_from("9c 2c 3d 4h 5s").unwrap();
+
+        assert_eq!(
+            EightOrBetter::compare_hands(&h_wheel, &h_eight),
+            Ordering::Greater
+        );
+        assert_eq!(
+            EightOrBetter::compare_hands(&h_eight, &h_nine),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn test_describe() {
+        let describe = |s: &str| {
+            EightOrBetter::describe(EightOrBetter::eval_hand(&Hand::try_from(s).unwrap()).unwrap())
+        };
+        assert_eq!(describe("8s 2d 3c 4d As"), "8 low");
+        assert_eq!(describe("9s 2d 3c 4d As"), "No low");
+    }
+}
@@ -0,0 +1,238 @@
+use super::*;
+
+// Standard high poker hand, e.g. Hold'em, Omaha, Stud, 5-Card Draw
+#[derive(Default, Debug)]
+pub struct High {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    HighCard,
+    OnePair,
+    TwoPairs,
+    Trips,
+    Straight,
+    Flush,
+    FullHouse,
+    Quads,
+    StraightFlush,
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Category::HighCard => write!(f, "High card"),
+            Category::OnePair => write!(f, "One pair"),
+            Category::TwoPairs => write!(f, "Two pairs"),
+            Category::Trips => write!(f, "Three of a kind"),
+            Category::Straight => write!(f, "Straight"),
+            Category::Flush => write!(f, "Flush"),
+            Category::FullHouse => write!(f, "Full house"),
+            Category::Quads => write!(f, "Four of a kind"),
+            Category::StraightFlush => write!(f, "Straight flush"),
+        }
+    }
+}
+
+impl High {
+    // Ace is high
+    const RANKS: [u128; 13] = [1, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096];
+    const ONE_PAIR_MULTIPLIER: u128 = 1 << 13;
+    const TRIPS_MULTIPLIER: u128 = 1 << (13 * 2);
+    const QUADS_MULTIPLIER: u128 = 1 << (13 * 3);
+    const CATEGORY_MULTIPLIER: u128 = 1 << (13 * 4);
+    // A-2-3-4-5, the wheel
+    const WHEEL: u128 = 0b1_0000_0000_1111;
+
+    pub fn category(score: u128) -> Category {
+        match score / Self::CATEGORY_MULTIPLIER {
+            0 => Category::HighCard,
+            1 => Category::OnePair,
+            2 => Category::TwoPairs,
+            3 => Category::Trips,
+            4 => Category::Straight,
+            5 => Category::Flush,
+            6 => Category::FullHouse,
+            7 => Category::Quads,
+            _ => Category::StraightFlush,
+        }
+    }
+
+    // Bit of the highest card of a straight, if the ranks form one
+    fn straight_high(ranks: u128, wheel: bool) -> Option<u128> {
+        if wheel && ranks == Self::WHEEL {
+            return Some(Self::RANKS[3]);
+        }
+        let low = ranks & ranks.wrapping_neg();
+        if ranks == low * 0b11111 {
+            return Some(low << 4);
+        }
+        None
+    }
+}
+
+impl EvalHand for High {
+    fn eval_hand(hand: &Hand) -> Result<u128, Error> {
+        Self::score(hand, true)
+    }
+
+    fn describe(score: u128) -> String {
+        Self::category(score).to_string()
+    }
+}
+
+impl High {
+    // `wheel` counts A-2-3-4-5 as a straight, which 2-7 lowball does not
+    pub(crate) fn score(hand: &Hand, wheel: bool) -> Result<u128, Error> {
+        if hand.cards.len() != 5 {
+            return Err(Error::InvalidHand);
+        }
+
+        let mut frequencies: [u8; 13] = [0; 13];
+        for card in &hand.cards {
+            frequencies[card.rank as usize] += 1;
+        }
+
+        let mut singles: u128 = 0;
+        let mut pairs: u128 = 0;
+        let mut pairs_count: u8 = 0;
+        let mut trips: u128 = 0;
+        let mut quads: u128 = 0;
+        for (index, &freq) in frequencies.iter().enumerate() {
+            match freq {
+                0 => (),
+                1 => singles += Self::RANKS[index],
+                2 => {
+                    pairs += Self::RANKS[index];
+                    pairs_count += 1;
+                }
+                3 => trips += Self::RANKS[index],
+                4 => quads += Self::RANKS[index],
+                _ => return Err(Error::InvalidHand),
+            }
+        }
+
+        let flush = hand
+            .cards
+            .iter()
+            .all(|card| card.suit == hand.cards[0].suit);
+        let straight = if singles.count_ones() == 5 {
+            Self::straight_high(singles, wheel)
+        } else {
+            None
+        };
+
+        let (category, score) = match (straight, flush) {
+            (Some(high), true) => (Category::StraightFlush, high),
+            _ if quads > 0 => (Category::Quads, quads * Self::QUADS_MULTIPLIER + singles),
+            _ if trips > 0 && pairs > 0 => (
+                Category::FullHouse,
+                trips * Self::TRIPS_MULTIPLIER + pairs * Self::ONE_PAIR_MULTIPLIER,
+            ),
+            (_, true) => (Category::Flush, singles),
+            (Some(high), false) => (Category::Straight, high),
+            _ if trips > 0 => (Category::Trips, trips * Self::TRIPS_MULTIPLIER + singles),
+            _ if pairs_count == 2 => (
+                Category::TwoPairs,
+                pairs * Self::ONE_PAIR_MULTIPLIER + singles,
+            ),
+            _ if pairs_count == 1 => (
+                Category::OnePair,
+                pairs * Self::ONE_PAIR_MULTIPLIER + singles,
+            ),
+            _ => (Category::HighCard, singles),
+        };
+
+        Ok(category as u128 * Self::CATEGORY_MULTIPLIER + score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::Hand;
+    use std::cmp::Ordering;
+
+    fn category_of(s: &str) -> Category {
+        High::category(High::eval_hand(&Hand::try_from(s).unwrap()).unwrap())
+    }
+
+    #[test]
+    fn test_eval_hand_invalid() {
+        assert_eq!(
+            High::eval_hand(&Hand::try_from("Ac 2c 3d 4h").unwrap()),
+            Err(Error::InvalidHand)
+        );
+        assert_eq!(
+            High::eval_hand(&Hand::try_from("Ac 2c 3d 4h 5c 6d").unwrap()),
+            Err(Error::InvalidHand)
+        );
+        assert_eq!(
+            High::eval_hand(&Hand::try_from("Ac Ad Ac Ah As").unwrap()),
+            Err(Error::InvalidHand)
+        );
+    }
+
+    #[test]
+    fn test_category() {
+        assert_eq!(category_of("Ac 2c 3c 4c 5c"), Category::StraightFlush);
+        assert_eq!(category_of("9h 9c 9d 9s Kh"), Category::Quads);
+        assert_eq!(category_of("9h 9c 9d Ks Kh"), Category::FullHouse);
+        assert_eq!(category_of("2h 9h Jh Kh 7h"), Category::Flush);
+        assert_eq!(category_of("Ah Kc Qd Js Th"), Category::Straight);
+        assert_eq!(category_of("Ah 2c 3d 4s 5h"), Category::Straight);
+        assert_eq!(category_of("Qh 2c 3d 4s 5h"), Category::HighCard);
+        assert_eq!(category_of("Kh Ac 2d 3s 4h"), Category::HighCard);
+        assert_eq!(category_of("7h 7c 7d 3s 4h"), Category::Trips);
+        assert_eq!(category_of("7h 7c 3d 3s 4h"), Category::TwoPairs);
+        assert_eq!(category_of("7h 7c 2d 3s 4h"), Category::OnePair);
+        assert_eq!(category_of("7h 8c 2d 3s 4h"), Category::HighCard);
+        assert_eq!(Category::FullHouse.to_string(), "Full house");
+    }
+
+    #[test]
+    fn test_compare_hands() {
+        let cmp = |a: &str, b: &str| {
+            High::compare_hands(&Hand::try_from(a).unwrap(), &Hand::try_from(b).unwrap())
+        };
+
+        // Category order
+        assert_eq!(cmp("6h 2h 3h 4h 5h", "Ah Ad Ac As Kh"), Ordering::Greater);
+        assert_eq!(cmp("2h 2d 2c 2s 3h", "Ah Ad Ac Ks Kh"), Ordering::Greater);
+        assert_eq!(cmp("2h 2d 2c 3s 3h", "Ah Kh Qh Jh 9h"), Ordering::Greater);
+        assert_eq!(cmp("2h 3h 4h 5h 7h", "Ah Kd Qc Js Th"), Ordering::Greater);
+        assert_eq!(cmp("Ah 2d 3c 4s 5h", "Ah Ad Ac Ks Qh"), Ordering::Greater);
+        assert_eq!(cmp("2h 2d 2c 3s 4h", "Ah Ad Kc Ks Qh"), Ordering::Greater);
+        assert_eq!(cmp("2h 2d 3c 3s 4h", "Ah Ad Kc Qs Jh"), Ordering::Greater);
+        assert_eq!(cmp("2h 2d 3c 4s 5h", "Ah Kd Qc Js 9h"), Ordering::Greater);
+
+        // Within category
+        assert_eq!(cmp("Ah 2d 3c 4s 5h", "2h 3d 4c 5s 6h"), Ordering::Less); // wheel is lowest
+        assert_eq!(cmp("Ah Kd Qc Js Th", "Kh Qd Jc Ts 9h"), Ordering::Greater);
+        assert_eq!(cmp("Ah Ad Kc Ks 2h", "Ah Ad Qc Qs Jh"), Ordering::Greater);
+        assert_eq!(cmp("Ah Ad Kc Ks 3h", "Ac As Kh Kd 2s"), Ordering::Greater);
+        assert_eq!(cmp("3h 3d 3c Ks Kh", "2h 2d 2c As Ah"), Ordering::Greater);
+        assert_eq!(cmp("Qh Qd 9c 8s 7h", "Qc Qs 9h 8d 6c"), Ordering::Greater);
+        assert_eq!(cmp("Ah Kh 9h 8h 7h", "Ad Kd 9d 8d 7d"), Ordering::Equal); // suits do not matter
+        assert_eq!(cmp("Ah Kh 9h 8h 7h", "Ad Qd Jd Td 8d"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_direct_comparison() {
+        let h_royal = Evaluation::<High>::try_from("Ts Js Qs Ks As").unwrap();
+        let h_quads = Evaluation::<High>::try_from("As Ad Ac Ah Ks").unwrap();
+        let h_pair = Evaluation::<High>::try_from("2s 2d 3c 4h 5s").unwrap();
+        let h_high = Evaluation::<High>::try_from("As Kd Qc Jh 9s").unwrap();
+
+        assert!(h_royal > h_quads);
+        assert!(h_quads > h_pair);
+        assert!(h_pair > h_high);
+    }
+
+    #[test]
+    fn test_describe() {
+        let describe =
+            |s: &str| High::describe(High::eval_hand(&Hand::try_from(s).unwrap()).unwrap());
+        assert_eq!(describe("Ah Ad Kc Kd Ks"), "Full house");
+        assert_eq!(describe("Ah 2d 3c 4d 5s"), "Straight");
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::card::Rank;
 
 // Simple evaluation algorithm mainly for testing
 #[derive(Default, Debug)]
@@ -11,6 +12,13 @@ impl EvalHand for Highcard {
         }
         Ok(hand.cards[0].rank as u128)
     }
+
+    fn describe(score: u128) -> String {
+        match Rank::iter().get(score as usize) {
+            Some(rank) => format!("{} high", rank),
+            None => String::new(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -79,4 +87,9 @@ mod tests {
         assert!(h_6 == h_6d);
         assert!(h_j < h_a);
     }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(Highcard::describe(11), "K high");
+    }
 }
@@ -0,0 +1,85 @@
+use super::high::{Category, High};
+use super::*;
+use crate::card::Rank;
+
+const MAX_SCORE: u128 = u128::MAX;
+
+// Deuce-to-seven lowball: the worst high hand wins. Aces are high only,
+// straights and flushes count against the hand.
+#[derive(Default, Debug)]
+pub struct Lowball2to7 {}
+
+impl EvalHand for Lowball2to7 {
+    fn eval_hand(hand: &Hand) -> Result<u128, Error> {
+        // Reverse the high score, A-2-3-4-5 is not a straight
+        Ok(MAX_SCORE - High::score(hand, false)?)
+    }
+
+    fn describe(score: u128) -> String {
+        let high = MAX_SCORE - score;
+        match High::category(high) {
+            Category::HighCard => match (0..13).rev().find(|&i| high & (1 << i) != 0) {
+                Some(index) => format!("{} low", Rank::iter()[index]),
+                None => String::new(),
+            },
+            category => category.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::Hand;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn test_eval_hand_invalid() {
+        assert_eq!(
+            Lowball2to7::eval_hand(&Hand::try_from("7c 5d 4h 3s").unwrap()),
+            Err(Error::InvalidHand)
+        );
+        assert_eq!(
+            Lowball2to7::eval_hand(&Hand::try_from("7c 5d 4h 3s 2c 8d").unwrap()),
+            Err(Error::InvalidHand)
+        );
+    }
+
+    #[test]
+    fn test_compare_hands() {
+        let cmp = |a: &str, b: &str| {
+            Lowball2to7::compare_hands(&Hand::try_from(a).unwrap(), &Hand::try_from(b).unwrap())
+        };
+
+        // Number one, 7-5-4-3-2
+        assert_eq!(cmp("7c 5d 4h 3s 2c", "7c 6d 4h 3s 2c"), Ordering::Greater);
+        assert_eq!(cmp("7c 5d 4h 3s 2c", "8c 5d 4h 3s 2c"), Ordering::Greater);
+        // Straights and flushes count
+        assert_eq!(cmp("8c 5d 4h 3s 2c", "6c 5d 4h 3s 2c"), Ordering::Greater);
+        assert_eq!(cmp("8c 5d 4h 3s 2c", "7c 5c 4c 3c 2c"), Ordering::Greater);
+        // Aces are high, A-2-3-4-5 is just ace high
+        assert_eq!(cmp("Kc Qd Jh 9s 8c", "Ac 2d 3h 4s 5c"), Ordering::Greater);
+        assert_eq!(cmp("Ac 2d 3h 4s 5c", "2c 2d 3h 4s 5c"), Ordering::Greater);
+        assert_eq!(cmp("7c 5d 4h 3s 2c", "7d 5h 4s 3c 2d"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_direct_comparison() {
+        let h_7543 = Evaluation::<Lowball2to7>::try_from("7c 5d 4h 3s 2c").unwrap();
+        let h_8643 = Evaluation::<Lowball2to7>::try_from("8c 6d 4h 3s 2c").unwrap();
+        let h_pair = Evaluation::<Lowball2to7>::try_from("2c 2d 4h 3s 5c").unwrap();
+
+        assert!(h_7543 > h_8643);
+        assert!(h_8643 > h_pair);
+    }
+
+    #[test]
+    fn test_describe() {
+        let describe = |s: &str| {
+            Lowball2to7::describe(Lowball2to7::eval_hand(&Hand::try_from(s).unwrap()).unwrap())
+        };
+        assert_eq!(describe("7s 5d 4c 3d 2s"), "7 low");
+        assert_eq!(describe("As 5d 4c 3d 2s"), "A low");
+        assert_eq!(describe("6s 5d 4c 3d 2s"), "Straight");
+    }
+}
@@ -1,8 +1,9 @@
 use super::*;
+use crate::card::Rank;
 
 const MAX_SCORE: u128 = u128::MAX;
 
-#[derive(Default)]
+#[derive(Default, Debug)]
 pub struct LowballAto5 {}
 
 impl LowballAto5 {
@@ -54,6 +55,65 @@ impl EvalHand for LowballAto5 {
         let low_score: u128 = MAX_SCORE - score;
         Ok(low_score)
     }
+
+    // Same scores as `eval_hand`, counting each rank from its four suit bits in
+    // a fixed loop without early exits
+    fn eval_masks(hands: &[u64], scores: &mut [u128]) {
+        assert_eq!(hands.len(), scores.len(), "one score per hand");
+        for (&hand, score) in hands.iter().zip(scores.iter_mut()) {
+            let mut counts: [u32; 13] = [0; 13];
+            let mut pairs: u32 = 0;
+            for (rank, count) in counts.iter_mut().enumerate() {
+                *count = (hand >> (rank * 4) & 0xf).count_ones();
+                pairs += u32::from(*count == 2);
+            }
+            let pair_multiplier = match pairs {
+                1 => Self::ONE_PAIR_MULTIPLIER,
+                2 => Self::TWO_PAIRS_MULTIPLIER,
+                _ => 0,
+            };
+            let multipliers = [
+                0,
+                1,
+                pair_multiplier,
+                Self::TRIPS_MULTIPLIER,
+                Self::QUADS_MULTIPLIER,
+            ];
+            let sum: u128 = counts
+                .iter()
+                .zip(Self::RANKS)
+                .map(|(&count, rank)| multipliers[count as usize] * rank)
+                .sum();
+            // Five cards from the deck only, as `eval_hand` requires
+            let valid = hand.count_ones() == 5 && hand >> 52 == 0;
+            *score = if valid { MAX_SCORE - sum } else { 0 };
+        }
+    }
+
+    fn describe(score: u128) -> String {
+        let score = MAX_SCORE - score;
+        if score >= Self::QUADS_MULTIPLIER {
+            return "Four of a kind".to_string();
+        }
+        if score >= Self::TRIPS_MULTIPLIER {
+            return "Three of a kind".to_string();
+        }
+        if score >= Self::TWO_PAIRS_MULTIPLIER {
+            return "Two pairs".to_string();
+        }
+        if score >= Self::ONE_PAIR_MULTIPLIER {
+            return "One pair".to_string();
+        }
+        if score == 0 {
+            return String::new();
+        }
+        // Highest card of an unpaired low
+        let top = 1 << (127 - score.leading_zeros());
+        match Self::RANKS.iter().position(|&rank| rank == top) {
+            Some(index) => format!("{} low", Rank::iter()[index]),
+            None => String::new(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -95,6 +155,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_eval_masks() {
+        // Every 11th five-card hand scores the same as one by one
+        let hands: Vec<Hand> = crate::deck::Deck::new()
+            .combinations(5)
+            .step_by(11)
+            .collect();
+        let masks: Vec<u64> = hands
+            .iter()
+            .map(|hand| crate::card::mask(&hand.cards))
+            .collect();
+        let mut scores: Vec<u128> = vec![0; masks.len()];
+        LowballAto5::eval_masks(&masks, &mut scores);
+        for (hand, &score) in hands.iter().zip(&scores) {
+            assert_eq!(LowballAto5::eval_hand(hand), Ok(score));
+        }
+
+        let invalid = [
+            crate::card::mask(&Hand::try_from("Ac 2c 3d 4h").unwrap().cards),
+            crate::card::mask(&Hand::try_from("Ac 2c 3d 4h 5c 6d").unwrap().cards),
+            1 << 60 | 0xf,
+        ];
+        let mut scores: [u128; 3] = [1; 3];
+        LowballAto5::eval_masks(&invalid, &mut scores);
+        assert_eq!(scores, [0; 3]);
+    }
+
     #[test]
     fn test_compare_hands() {
         // High-card hands
@@ -264,4 +351,15 @@ mod tests {
         assert!(h_a2345 > h_a2346);
         assert!(h_a2346 > h_kkjqt);
     }
+
+    #[test]
+    fn test_describe() {
+        let describe = |s: &str| {
+            LowballAto5::describe(LowballAto5::eval_hand(&Hand::try_from(s).unwrap()).unwrap())
+        };
+        assert_eq!(describe("As 2d 3c 4d 5s"), "5 low");
+        assert_eq!(describe("7s 2d 3c 4d As"), "7 low");
+        assert_eq!(describe("Ks Kd 3c 4d As"), "One pair");
+        assert_eq!(describe("Ks Kd Kc 4d 4s"), "Three of a kind");
+    }
 }
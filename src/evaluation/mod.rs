@@ -1,17 +1,64 @@
+use crate::card::{Card, OrderFirstBy};
+use crate::deck::{Combinations, Deck, CHUNKS};
 use crate::error::Error;
 use crate::hand::Hand;
 
+pub mod badugi;
+pub mod eight_or_better;
+pub mod high;
 pub mod highcard;
+pub mod lowball_2to7;
 pub mod lowball_ato5;
+pub mod split;
+pub mod strength;
 
-pub trait EvalHand: Default {
+// Send + Sync so that evaluators can be used from worker threads
+pub trait EvalHand: Default + Send + Sync {
     fn eval_hand(hand: &Hand) -> Result<u128, Error>;
 
+    // Hand category of a score in words, e.g. "Full house" or "7 low".
+    // Evaluators without categories describe a hand by its score.
+    fn describe(score: u128) -> String {
+        score.to_string()
+    }
+
     fn compare_hands(hand1: &Hand, hand2: &Hand) -> std::cmp::Ordering {
         let score1 = Self::eval_hand(hand1);
         let score2 = Self::eval_hand(hand2);
         score1.cmp(&score2)
     }
+
+    // Score hands given as card sets from `card::mask` into `scores`.
+    // Callers must pass only valid hands, e.g. subsets from `Combinations`:
+    // an invalid hand scores 0, which `EightOrBetter` also gives a hand with
+    // no low, so the two cannot be told apart. Evaluators can override this
+    // with a loop that works on the masks directly.
+    fn eval_masks(hands: &[u64], scores: &mut [u128]) {
+        assert_eq!(hands.len(), scores.len(), "one score per hand");
+        for (&hand, score) in hands.iter().zip(scores.iter_mut()) {
+            *score = Self::eval_hand(&Hand::from_mask(hand)).unwrap_or(0);
+        }
+    }
+}
+
+// Score of every `size`-card hand, indexed by `Hand::to_index`
+pub fn score_table<T: EvalHand>(size: usize) -> Result<Vec<u128>, Error> {
+    // Cards in `ord_position` order, so that subsets come in index order
+    let mut cards = Hand::from(Deck::new().cards);
+    cards.sort_cards(OrderFirstBy::Rank);
+    let chunks = Combinations::new(&cards.cards, size).map_chunks(CHUNKS, |mut hands| {
+        let mut scores: Vec<u128> = Vec::with_capacity(hands.len());
+        while let Some(chosen) = hands.next_cards() {
+            scores.push(T::eval_hand(&Hand::from(chosen.to_vec()))?);
+        }
+        Ok(scores)
+    });
+
+    let mut table: Vec<u128> = Vec::with_capacity(Hand::index_count(size) as usize);
+    for chunk in chunks {
+        table.extend(chunk?);
+    }
+    Ok(table)
 }
 
 #[derive(Debug)]
@@ -24,6 +71,48 @@ where
     pub score: u128,
 }
 
+impl<T> Evaluation<T>
+where
+    T: EvalHand,
+{
+    // Best `size`-card hand that can be made from any of the cards, e.g. 5 of 7 in Hold'em
+    pub fn best_of(cards: &[Card], size: usize) -> Result<Self, Error> {
+        if size == 0 || cards.len() < size {
+            return Err(Error::InvalidHand);
+        }
+
+        let mut best: Option<Self> = None;
+        let mut combinations = Combinations::new(cards, size);
+        while let Some(chosen) = combinations.next_cards() {
+            let evaluation = Self::try_from(Hand::from(chosen.to_vec()))?;
+            if best.as_ref().is_none_or(|b| evaluation > *b) {
+                best = Some(evaluation);
+            }
+        }
+        best.ok_or(Error::InvalidHand)
+    }
+
+    // Omaha rule: exactly two cards from the hole and three from the board
+    pub fn best_omaha(hole: &[Card], board: &[Card]) -> Result<Self, Error> {
+        if hole.len() < 2 || board.len() < 3 {
+            return Err(Error::InvalidHand);
+        }
+
+        let mut best: Option<Self> = None;
+        let mut hole_pairs = Combinations::new(hole, 2);
+        while let Some(pair) = hole_pairs.next_cards() {
+            let mut board_triples = Combinations::new(board, 3);
+            while let Some(triple) = board_triples.next_cards() {
+                let evaluation = Self::try_from(Hand::from([pair, triple].concat()))?;
+                if best.as_ref().is_none_or(|b| evaluation > *b) {
+                    best = Some(evaluation);
+                }
+            }
+        }
+        best.ok_or(Error::InvalidHand)
+    }
+}
+
 impl<T> TryFrom<Hand> for Evaluation<T>
 where
     T: EvalHand,
@@ -79,3 +168,135 @@ where
         Some(self.cmp(other))
     }
 }
+
+// Serialized as the hand, its score and category. Only the hand is read
+// back, the score is evaluated again.
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for Evaluation<T>
+where
+    T: EvalHand,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Evaluation", 3)?;
+        state.serialize_field("hand", &self.hand)?;
+        // As a string, as lowball scores do not fit in a u64
+        state.serialize_field("score", &self.score.to_string())?;
+        state.serialize_field("category", &T::describe(self.score))?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct EvaluationNotation {
+    hand: Hand,
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for Evaluation<T>
+where
+    T: EvalHand,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let notation = <EvaluationNotation as serde::Deserialize>::deserialize(deserializer)?;
+        Self::try_from(notation.hand)
+            .map_err(|_| serde::de::Error::custom("hand cannot be evaluated"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::high::High;
+    use super::*;
+
+    #[test]
+    fn test_best_of() {
+        let cards = Hand::try_from("Ah Kh 2c 2d Qh Jh Th").unwrap().cards;
+        let best = Evaluation::<High>::best_of(&cards, 5).unwrap();
+        assert_eq!(best.hand.to_string(), "Ah Kh Qh Jh Th");
+        assert_eq!(
+            High::category(best.score),
+            super::high::Category::StraightFlush
+        );
+
+        assert_eq!(
+            Evaluation::<High>::best_of(&cards[..4], 5).unwrap_err(),
+            Error::InvalidHand
+        );
+    }
+
+    #[test]
+    fn test_score_table() {
+        let table = score_table::<highcard::Highcard>(1).unwrap();
+        assert_eq!(table.len(), 52);
+
+        let table = score_table::<badugi::Badugi>(4).unwrap();
+        assert_eq!(table.len(), 270_725);
+        for index in (0..table.len() as u64).step_by(1009) {
+            let hand = Hand::from_index(index, 4).unwrap();
+            assert_eq!(
+                table[index as usize],
+                badugi::Badugi::eval_hand(&hand).unwrap()
+            );
+        }
+
+        assert_eq!(score_table::<badugi::Badugi>(3), Err(Error::InvalidHand));
+    }
+
+    #[test]
+    fn test_eval_masks() {
+        let hands = ["As 2d 3h 4c", "As Ad 3h 4c", "As 2d 3h"];
+        let masks: Vec<u64> = hands
+            .iter()
+            .map(|hand| crate::card::mask(&Hand::try_from(*hand).unwrap().cards))
+            .collect();
+        let mut scores: Vec<u128> = vec![0; masks.len()];
+        badugi::Badugi::eval_masks(&masks, &mut scores);
+        for (hand, &score) in hands.iter().zip(&scores).take(2) {
+            let hand = Hand::try_from(*hand).unwrap();
+            assert_eq!(badugi::Badugi::eval_hand(&hand), Ok(score));
+        }
+        assert_eq!(scores[2], 0);
+    }
+
+    #[test]
+    fn test_best_omaha() {
+        // Four hearts on board but only one in the hole: no flush in Omaha
+        let hole = Hand::try_from("Ah As Kc Qd").unwrap().cards;
+        let board = Hand::try_from("2h 7h 9h Jh 3c").unwrap().cards;
+        let best = Evaluation::<High>::best_omaha(&hole, &board).unwrap();
+        assert_eq!(High::category(best.score), super::high::Category::OnePair);
+        assert_eq!(best.hand.cards[..2], hole[..2]);
+
+        let holdem = Evaluation::<High>::best_of(&[&hole[..], &board[..]].concat(), 5).unwrap();
+        assert_eq!(High::category(holdem.score), super::high::Category::Flush);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_evaluation() {
+        let evaluation = Evaluation::<High>::try_from("Ah Ad Kc Kd Ks").unwrap();
+        let json = serde_json::to_value(&evaluation).unwrap();
+        assert_eq!(json["hand"], "Ah Ad Kc Kd Ks");
+        assert_eq!(json["category"], "Full house");
+        assert_eq!(json["score"], evaluation.score.to_string());
+
+        let read: Evaluation<High> = serde_json::from_value(json).unwrap();
+        assert_eq!(read.hand, evaluation.hand);
+        assert_eq!(read.score, evaluation.score);
+
+        // Lowball scores are above u64::MAX
+        let low = Evaluation::<lowball_ato5::LowballAto5>::try_from("7c 5d 4h 3s 2c").unwrap();
+        assert!(low.score > u128::from(u64::MAX));
+        let json = serde_json::to_value(&low).unwrap();
+        assert_eq!(json["score"], low.score.to_string());
+        let read: Evaluation<lowball_ato5::LowballAto5> = serde_json::from_value(json).unwrap();
+        assert_eq!(read.hand, low.hand);
+        assert_eq!(read.score, low.score);
+
+        let json = serde_json::json!({"hand": "Ah Ad Kc"});
+        assert!(serde_json::from_value::<Evaluation<High>>(json).is_err());
+    }
+}
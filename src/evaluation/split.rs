@@ -0,0 +1,104 @@
+use super::badugi::Badugi;
+use super::lowball_2to7::Lowball2to7;
+use super::lowball_ato5::LowballAto5;
+use super::*;
+
+// Both halves of a Badugi split-pot hand: the best 4-card badugi and the
+// 5-card low `L` from the same cards
+#[derive(Debug)]
+pub struct SplitEvaluation<L>
+where
+    L: EvalHand,
+{
+    pub hand: Hand,
+    pub badugi: Evaluation<Badugi>,
+    pub low: Evaluation<L>,
+}
+
+// Badugi and A-5 low
+pub type Badacey = SplitEvaluation<LowballAto5>;
+
+// Badugi and 2-7 low
+pub type Badeucey = SplitEvaluation<Lowball2to7>;
+
+impl<L> SplitEvaluation<L>
+where
+    L: EvalHand,
+{
+    // Badugi score and low score, each winning half the pot
+    pub fn scores(&self) -> (u128, u128) {
+        (self.badugi.score, self.low.score)
+    }
+}
+
+impl<L> TryFrom<Hand> for SplitEvaluation<L>
+where
+    L: EvalHand,
+{
+    type Error = Error;
+
+    fn try_from(hand: Hand) -> Result<Self, Error> {
+        if hand.cards.len() != 5 {
+            return Err(Error::InvalidHand);
+        }
+        Ok(Self {
+            badugi: Evaluation::best_of(&hand.cards, 4)?,
+            low: Evaluation::try_from(hand.clone())?,
+            hand,
+        })
+    }
+}
+
+impl<L> TryFrom<&str> for SplitEvaluation<L>
+where
+    L: EvalHand,
+{
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self, Error> {
+        let hand = Hand::try_from(s)?;
+        Self::try_from(hand)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_hand() {
+        assert_eq!(
+            Badacey::try_from("Ac 2d 3h 4s").unwrap_err(),
+            Error::InvalidHand
+        );
+        assert_eq!(
+            Badeucey::try_from("Ac 2d 3h 4s 5c 6d").unwrap_err(),
+            Error::InvalidHand
+        );
+    }
+
+    #[test]
+    fn test_badacey() {
+        let evaluation = Badacey::try_from("Ac 2d 3h 4s 4c").unwrap();
+        assert_eq!(evaluation.badugi.hand.to_string(), "Ac 2d 3h 4s");
+        assert_eq!(Badugi::size(evaluation.badugi.score), 4);
+
+        // The wheel is the best A-5 low, and A-3-4-5 a 4-card badugi, the 2c
+        // sharing a suit with the Ac, that A-2-3-4 beats
+        let wheel = Badacey::try_from("Ac 2c 3h 4s 5d").unwrap();
+        assert_eq!(wheel.badugi.hand.to_string(), "Ac 3h 4s 5d");
+        assert_eq!(Badugi::size(wheel.badugi.score), 4);
+        assert!(wheel.badugi < evaluation.badugi);
+        assert!(wheel.low > evaluation.low);
+    }
+
+    #[test]
+    fn test_badeucey() {
+        // Aces are low in badugi but high in 2-7
+        let ace = Badeucey::try_from("Ac 2d 3h 4s 7c").unwrap();
+        let king = Badeucey::try_from("Kc 2d 3h 4s 7c").unwrap();
+        assert!(ace.badugi > king.badugi);
+        assert!(ace.low < king.low);
+        assert_eq!(ace.scores(), (ace.badugi.score, ace.low.score));
+    }
+}
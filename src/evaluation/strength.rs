@@ -0,0 +1,192 @@
+// Strength of a score among every hand of the same size, e.g. the 2,598,960
+// five-card hands, which are enumerated once per evaluator and cached
+
+use super::*;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Strength {
+    // 1 for the nuts, counting hands of equal score once
+    pub rank: usize,
+    // Number of distinct scores, i.e. the rank of the worst hand
+    pub distinct: usize,
+    // Hands that beat this one and hands that tie it, itself included
+    pub better: u64,
+    pub ties: u64,
+    pub total: u64,
+    // Share of all hands this one beats or ties, from 0 to 100
+    pub percentile: f64,
+}
+
+// Hand spaces by evaluator and hand size
+type Cache = Mutex<HashMap<(TypeId, usize), Arc<HandSpace>>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandSpace {
+    // Distinct scores from the best down, with the number of hands of each
+    scores: Vec<u128>,
+    counts: Vec<u64>,
+    // Hands that beat each score
+    better: Vec<u64>,
+    total: u64,
+}
+
+impl HandSpace {
+    // Score every `size`-card hand with `T`
+    pub fn new<T: EvalHand>(size: usize) -> Result<HandSpace, Error> {
+        let mut table = score_table::<T>(size)?;
+        table.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut scores: Vec<u128> = Vec::new();
+        let mut counts: Vec<u64> = Vec::new();
+        for score in table {
+            match scores.last() {
+                Some(&last) if last == score => *counts.last_mut().unwrap() += 1,
+                _ => {
+                    scores.push(score);
+                    counts.push(1);
+                }
+            }
+        }
+        let better: Vec<u64> = counts
+            .iter()
+            .scan(0, |seen, &count| {
+                let better = *seen;
+                *seen += count;
+                Some(better)
+            })
+            .collect();
+        let total = counts.iter().sum();
+        Ok(HandSpace {
+            scores,
+            counts,
+            better,
+            total,
+        })
+    }
+
+    // Same as `new`, built on first use and shared afterwards
+    pub fn cached<T: EvalHand + 'static>(size: usize) -> Result<Arc<HandSpace>, Error> {
+        static CACHE: OnceLock<Cache> = OnceLock::new();
+        let key = (TypeId::of::<T>(), size);
+        let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+        if let Some(space) = cache.lock().unwrap().get(&key) {
+            return Ok(Arc::clone(space));
+        }
+        // Built without holding the lock; a racing thread builds the same space
+        let space = Arc::new(HandSpace::new::<T>(size)?);
+        let mut cache = cache.lock().unwrap();
+        Ok(Arc::clone(cache.entry(key).or_insert(space)))
+    }
+
+    pub fn distinct(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    // None if no hand has this score
+    pub fn strength(&self, score: u128) -> Option<Strength> {
+        let index = self
+            .scores
+            .binary_search_by(|probe| score.cmp(probe))
+            .ok()?;
+        let better = self.better[index];
+        Some(Strength {
+            rank: index + 1,
+            distinct: self.scores.len(),
+            better,
+            ties: self.counts[index],
+            total: self.total,
+            percentile: 100.0 * (self.total - better) as f64 / self.total as f64,
+        })
+    }
+
+    // Share of hands a score beats or ties, from 0 to 100. Unlike `strength`
+    // it takes any score, e.g. of a hand with more cards than this space.
+    pub fn percentile(&self, score: u128) -> f64 {
+        let index = match self.scores.binary_search_by(|probe| score.cmp(probe)) {
+            Ok(index) | Err(index) => index,
+        };
+        let better = self.better.get(index).copied().unwrap_or(self.total);
+        100.0 * (self.total - better) as f64 / self.total as f64
+    }
+}
+
+// Strength of a hand among all hands of its size, from the cached hand space
+pub fn strength<T: EvalHand + 'static>(hand: &Hand) -> Result<Strength, Error> {
+    let score = T::eval_hand(hand)?;
+    HandSpace::cached::<T>(hand.cards.len())?
+        .strength(score)
+        .ok_or(Error::InvalidHand)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::badugi::Badugi;
+    use super::high::High;
+    use super::lowball_ato5::LowballAto5;
+    use super::*;
+
+    #[test]
+    fn test_high_hand_space() {
+        let space = HandSpace::cached::<High>(5).unwrap();
+        assert_eq!(space.distinct(), 7462);
+        assert_eq!(space.total(), 2_598_960);
+
+        let royal = strength::<High>(&Hand::try_from("As Ks Qs Js Ts").unwrap()).unwrap();
+        assert_eq!(royal.rank, 1);
+        assert_eq!(royal.better, 0);
+        assert_eq!(royal.ties, 4);
+        assert_eq!(royal.percentile, 100.0);
+
+        let worst = strength::<High>(&Hand::try_from("7c 5d 4h 3s 2c").unwrap()).unwrap();
+        assert_eq!(worst.rank, 7462);
+        assert_eq!(worst.ties, 1020);
+        assert_eq!(worst.better, 2_598_960 - 1020);
+    }
+
+    #[test]
+    fn test_lowball_ato5_strength() {
+        let wheel = strength::<LowballAto5>(&Hand::try_from("Ac 2d 3h 4s 5c").unwrap()).unwrap();
+        assert_eq!(wheel.rank, 1);
+        assert_eq!(wheel.ties, 1024);
+        assert_eq!(wheel.distinct, 6175);
+
+        // 6-4 is the second best low, behind the wheel only
+        let six = strength::<LowballAto5>(&Hand::try_from("6c 4d 3h 2s Ac").unwrap()).unwrap();
+        assert_eq!(six.rank, 2);
+        assert_eq!(six.better, 1024);
+        assert!(six.percentile < wheel.percentile);
+
+        let space = HandSpace::cached::<LowballAto5>(5).unwrap();
+        assert_eq!(space.strength(0), None);
+        assert_eq!(space.percentile(0), 0.0);
+        assert_eq!(space.percentile(u128::MAX), 100.0);
+        let score = LowballAto5::eval_hand(&Hand::try_from("6c 4d 3h 2s Ac").unwrap()).unwrap();
+        assert_eq!(space.percentile(score), six.percentile);
+    }
+
+    #[test]
+    fn test_badugi_strength() {
+        let space = HandSpace::cached::<Badugi>(4).unwrap();
+        assert_eq!(space.total(), 270_725);
+        let best = strength::<Badugi>(&Hand::try_from("As 2h 3d 4c").unwrap()).unwrap();
+        assert_eq!(best.rank, 1);
+        assert_eq!(best.ties, 24);
+
+        // Cached spaces are shared
+        assert!(Arc::ptr_eq(
+            &space,
+            &HandSpace::cached::<Badugi>(4).unwrap()
+        ));
+        assert_eq!(
+            strength::<Badugi>(&Hand::try_from("As 2h 3d").unwrap()),
+            Err(Error::InvalidHand)
+        );
+    }
+}
@@ -0,0 +1,429 @@
+// A single round of betting, shared by all engines
+
+use super::{Action, Limit, Seat};
+use crate::error::Error;
+
+// Bets and raises allowed per round in fixed limit
+pub const FIXED_LIMIT_CAP: u8 = 4;
+
+// What the player to act may do. Raising is allowed when `min_raise_to` is
+// set, any total from `min_raise_to` to `max_raise_to` is accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BettingOptions {
+    pub seat: usize,
+    pub current_bet: u64,
+    // Chips needed to call, capped at the player's stack
+    pub to_call: u64,
+    pub min_raise_to: Option<u64>,
+    pub max_raise_to: Option<u64>,
+    // Set when the player must bring in for this amount or complete
+    pub bring_in: Option<u64>,
+    // Set when a bet of `min_raise_to` would complete a fixed limit bring-in
+    pub completes: bool,
+}
+
+impl BettingOptions {
+    pub fn can_check(&self) -> bool {
+        self.to_call == 0 && self.bring_in.is_none()
+    }
+
+    // The action putting in `to` chips in total on this street
+    pub fn bet_or_raise(&self, to: u64) -> Action {
+        if self.completes && Some(to) == self.min_raise_to {
+            Action::Complete
+        } else if self.current_bet == 0 {
+            Action::Bet(to)
+        } else {
+            Action::Raise(to)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BettingRound {
+    limit: Limit,
+    // Minimum bet, and the only bet size in fixed limit
+    unit: u64,
+    current_bet: u64,
+    min_raise: u64,
+    // Opening bet allowed instead of `unit`, e.g. with an open pair on fourth street
+    double_bet: Option<u64>,
+    bets: u8,
+    acted: Vec<bool>,
+    to_act: Option<usize>,
+}
+
+impl BettingRound {
+    // Start a round with whatever is already bet (blinds), `first` acts first
+    pub fn new(limit: Limit, unit: u64, seats: &[Seat], first: usize) -> BettingRound {
+        let current_bet = seats.iter().map(|seat| seat.bet).max().unwrap_or(0);
+        let mut round = BettingRound {
+            limit,
+            unit,
+            current_bet,
+            min_raise: unit,
+            double_bet: None,
+            bets: u8::from(current_bet > 0),
+            acted: vec![false; seats.len()],
+            to_act: None,
+        };
+        round.to_act = round.next_to_act(seats, first);
+        round
+    }
+
+    pub fn to_act(&self) -> Option<usize> {
+        self.to_act
+    }
+
+    pub fn current_bet(&self) -> u64 {
+        self.current_bet
+    }
+
+    // Allow opening for `amount`, after which raises are of that size too
+    pub fn allow_double_bet(&mut self, amount: u64) {
+        self.double_bet = Some(amount);
+    }
+
+    // Forced bet of the player to act, such as a stud bring-in, which counts
+    // as their action
+    pub fn force_bet(&mut self, seats: &mut [Seat], amount: u64) -> Result<(), Error> {
+        let seat_index = self.to_act.ok_or(Error::InvalidAction)?;
+        let seat = &mut seats[seat_index];
+        seat.put(amount.saturating_sub(seat.bet));
+        if seat.bet >= self.unit {
+            self.bets += 1;
+        }
+        self.current_bet = self.current_bet.max(seat.bet);
+        self.acted[seat_index] = true;
+        self.to_act = self.next_to_act(seats, (seat_index + 1) % seats.len());
+        Ok(())
+    }
+
+    // A fixed limit bet below the full bet, which the next bet completes
+    fn is_incomplete(&self) -> bool {
+        self.limit == Limit::FixedLimit && self.current_bet < self.unit
+    }
+
+    // Smallest total that makes a full bet or raise
+    fn full_raise_to(&self) -> u64 {
+        if self.current_bet == 0 || self.is_incomplete() {
+            self.unit
+        } else {
+            self.current_bet + self.min_raise
+        }
+    }
+
+    fn needs_action(&self, seats: &[Seat], i: usize) -> bool {
+        let seat = &seats[i];
+        if !seat.can_act() {
+            return false;
+        }
+        let facing_bet = seat.bet < self.current_bet;
+        let others_live = seats
+            .iter()
+            .enumerate()
+            .any(|(j, other)| j != i && other.can_act());
+        let others_in = seats
+            .iter()
+            .enumerate()
+            .any(|(j, other)| j != i && other.in_hand());
+        others_in && (facing_bet || (!self.acted[i] && others_live))
+    }
+
+    fn next_to_act(&self, seats: &[Seat], from: usize) -> Option<usize> {
+        (0..seats.len())
+            .map(|offset| (from + offset) % seats.len())
+            .find(|&i| self.needs_action(seats, i))
+    }
+
+    // `pot` is everything committed so far, including this street's bets
+    pub fn options(&self, seats: &[Seat], pot: u64) -> Option<BettingOptions> {
+        let seat_index = self.to_act?;
+        let seat = &seats[seat_index];
+        let to_call = (self.current_bet - seat.bet).min(seat.stack);
+        let all_in_to = seat.bet + seat.stack;
+
+        let others_live = seats
+            .iter()
+            .enumerate()
+            .any(|(j, other)| j != seat_index && other.can_act());
+        let can_raise = !self.acted[seat_index]
+            && seat.stack > to_call
+            && others_live
+            && !(self.limit == Limit::FixedLimit && self.bets >= FIXED_LIMIT_CAP);
+
+        let (min_raise_to, max_raise_to) = if can_raise {
+            let min_to = self.full_raise_to();
+            let max_to = match self.limit {
+                Limit::FixedLimit if self.current_bet == 0 => self.double_bet.unwrap_or(min_to),
+                Limit::FixedLimit => min_to,
+                Limit::PotLimit => {
+                    let full_call = self.current_bet - seat.bet;
+                    (self.current_bet + pot + full_call).max(min_to)
+                }
+                Limit::NoLimit => all_in_to,
+            };
+            (Some(min_to.min(all_in_to)), Some(max_to.min(all_in_to)))
+        } else {
+            (None, None)
+        };
+
+        Some(BettingOptions {
+            seat: seat_index,
+            current_bet: self.current_bet,
+            to_call,
+            min_raise_to,
+            max_raise_to,
+            bring_in: None,
+            completes: self.current_bet > 0 && self.is_incomplete(),
+        })
+    }
+
+    // Apply the action of the player to act
+    pub fn act(&mut self, seats: &mut [Seat], action: &Action, pot: u64) -> Result<(), Error> {
+        let options = self.options(seats, pot).ok_or(Error::InvalidAction)?;
+        let seat_index = options.seat;
+
+        match *action {
+            Action::Fold => seats[seat_index].folded = true,
+            Action::Check => {
+                if !options.can_check() {
+                    return Err(Error::InvalidAction);
+                }
+            }
+            Action::Call => {
+                if options.can_check() {
+                    return Err(Error::InvalidAction);
+                }
+                seats[seat_index].put(options.to_call);
+            }
+            Action::Bet(_) | Action::Raise(_) | Action::Complete => {
+                let to = match *action {
+                    Action::Bet(to) | Action::Raise(to) => to,
+                    _ => options.min_raise_to.ok_or(Error::InvalidAction)?,
+                };
+                if *action != options.bet_or_raise(to) {
+                    return Err(Error::InvalidAction);
+                }
+                match (options.min_raise_to, options.max_raise_to) {
+                    // Fixed limit only allows the exact sizes
+                    (Some(min_to), Some(max_to)) if self.limit == Limit::FixedLimit => {
+                        if to != min_to && to != max_to {
+                            return Err(Error::InvalidAction);
+                        }
+                    }
+                    (Some(min_to), Some(max_to)) if to >= min_to && to <= max_to => (),
+                    _ => return Err(Error::InvalidAction),
+                }
+                self.raise_to(seats, seat_index, to);
+            }
+            Action::BringIn | Action::Discard(_) => return Err(Error::InvalidAction),
+        }
+
+        self.acted[seat_index] = true;
+        self.to_act = self.next_to_act(seats, (seat_index + 1) % seats.len());
+        Ok(())
+    }
+
+    // Put the seat's total bet on this street to `to`, reopening the action
+    // for everyone else on a full raise
+    pub fn raise_to(&mut self, seats: &mut [Seat], seat_index: usize, to: u64) {
+        let seat = &mut seats[seat_index];
+        seat.put(to - seat.bet);
+        if self.current_bet == 0 && Some(to) == self.double_bet {
+            self.unit = to;
+        }
+        if to >= self.full_raise_to() {
+            let raise = to - self.current_bet;
+            self.min_raise = match self.limit {
+                Limit::FixedLimit => self.unit,
+                _ => raise,
+            };
+            self.bets += 1;
+            for (i, acted) in self.acted.iter_mut().enumerate() {
+                if i != seat_index {
+                    *acted = false;
+                }
+            }
+        }
+        self.current_bet = to;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seats(stacks: &[u64]) -> Vec<Seat> {
+        stacks.iter().map(|&stack| Seat::new(stack)).collect()
+    }
+
+    #[test]
+    fn test_check_around() {
+        let mut seats = seats(&[100, 100, 100]);
+        let mut round = BettingRound::new(Limit::NoLimit, 2, &seats, 1);
+        assert_eq!(round.to_act(), Some(1));
+        round.act(&mut seats, &Action::Check, 0).unwrap();
+        round.act(&mut seats, &Action::Check, 0).unwrap();
+        assert_eq!(round.to_act(), Some(0));
+        assert_eq!(
+            round.act(&mut seats, &Action::Call, 0),
+            Err(Error::InvalidAction)
+        );
+        round.act(&mut seats, &Action::Check, 0).unwrap();
+        assert_eq!(round.to_act(), None);
+    }
+
+    #[test]
+    fn test_blinds_and_big_blind_option() {
+        let mut seats = seats(&[100, 100, 100]);
+        seats[1].put(1);
+        seats[2].put(2);
+        let mut round = BettingRound::new(Limit::NoLimit, 2, &seats, 0);
+
+        let options = round.options(&seats, 3).unwrap();
+        assert_eq!(options.to_call, 2);
+        assert_eq!(options.min_raise_to, Some(4));
+        assert_eq!(options.max_raise_to, Some(100));
+        assert_eq!(
+            round.act(&mut seats, &Action::Bet(4), 3),
+            Err(Error::InvalidAction)
+        );
+        round.act(&mut seats, &Action::Call, 3).unwrap();
+        round.act(&mut seats, &Action::Call, 5).unwrap();
+        // Big blind gets the option
+        assert_eq!(round.to_act(), Some(2));
+        round.act(&mut seats, &Action::Raise(10), 6).unwrap();
+        assert_eq!(round.options(&seats, 14).unwrap().min_raise_to, Some(18));
+        round.act(&mut seats, &Action::Fold, 14).unwrap();
+        round.act(&mut seats, &Action::Call, 14).unwrap();
+        assert_eq!(round.to_act(), None);
+        assert_eq!(seats[1].committed, 10);
+    }
+
+    #[test]
+    fn test_pot_limit() {
+        let mut seats = seats(&[1000, 1000, 1000]);
+        seats[1].put(1);
+        seats[2].put(2);
+        let mut round = BettingRound::new(Limit::PotLimit, 2, &seats, 0);
+        // Call 2 makes the pot 5, raise by 5 to 7
+        assert_eq!(round.options(&seats, 3).unwrap().max_raise_to, Some(7));
+        assert_eq!(
+            round.act(&mut seats, &Action::Raise(8), 3),
+            Err(Error::InvalidAction)
+        );
+        round.act(&mut seats, &Action::Raise(7), 3).unwrap();
+        // Small blind calls 6 making the pot 16, raise by 16 to 23
+        assert_eq!(round.options(&seats, 10).unwrap().max_raise_to, Some(23));
+    }
+
+    #[test]
+    fn test_fixed_limit_cap() {
+        let mut seats = seats(&[1000, 1000]);
+        let mut round = BettingRound::new(Limit::FixedLimit, 10, &seats, 0);
+        assert_eq!(
+            round.act(&mut seats, &Action::Bet(20), 0),
+            Err(Error::InvalidAction)
+        );
+        round.act(&mut seats, &Action::Bet(10), 0).unwrap();
+        round.act(&mut seats, &Action::Raise(20), 10).unwrap();
+        round.act(&mut seats, &Action::Raise(30), 30).unwrap();
+        round.act(&mut seats, &Action::Raise(40), 60).unwrap();
+        let options = round.options(&seats, 100).unwrap();
+        assert_eq!(options.to_call, 10);
+        assert_eq!(options.min_raise_to, None);
+        round.act(&mut seats, &Action::Call, 100).unwrap();
+        assert_eq!(round.to_act(), None);
+    }
+
+    #[test]
+    fn test_bring_in_and_complete() {
+        let mut seats = seats(&[100, 100, 100]);
+        let mut round = BettingRound::new(Limit::FixedLimit, 4, &seats, 1);
+        round.force_bet(&mut seats, 1).unwrap();
+        assert_eq!(round.to_act(), Some(2));
+
+        let options = round.options(&seats, 1).unwrap();
+        assert_eq!(options.to_call, 1);
+        assert!(options.completes);
+        assert_eq!(options.min_raise_to, Some(4));
+        assert_eq!(options.bet_or_raise(4), Action::Complete);
+        assert_eq!(
+            round.act(&mut seats, &Action::Raise(4), 1),
+            Err(Error::InvalidAction)
+        );
+        round.act(&mut seats, &Action::Complete, 1).unwrap();
+
+        let options = round.options(&seats, 5).unwrap();
+        assert!(!options.completes);
+        assert_eq!(options.min_raise_to, Some(8));
+        round.act(&mut seats, &Action::Call, 5).unwrap();
+        // The bring-in acts again after the completion
+        assert_eq!(round.to_act(), Some(1));
+        round.act(&mut seats, &Action::Call, 9).unwrap();
+        assert_eq!(round.to_act(), None);
+        assert_eq!(seats[1].committed, 4);
+    }
+
+    #[test]
+    fn test_called_bring_in_has_no_option() {
+        let mut seats = seats(&[100, 100]);
+        let mut round = BettingRound::new(Limit::FixedLimit, 4, &seats, 0);
+        round.force_bet(&mut seats, 1).unwrap();
+        round.act(&mut seats, &Action::Call, 1).unwrap();
+        assert_eq!(round.to_act(), None);
+    }
+
+    #[test]
+    fn test_double_bet() {
+        let mut seats = seats(&[100, 100]);
+        let mut round = BettingRound::new(Limit::FixedLimit, 4, &seats, 0);
+        round.allow_double_bet(8);
+        let options = round.options(&seats, 0).unwrap();
+        assert_eq!(
+            (options.min_raise_to, options.max_raise_to),
+            (Some(4), Some(8))
+        );
+        assert_eq!(
+            round.act(&mut seats, &Action::Bet(6), 0),
+            Err(Error::InvalidAction)
+        );
+        round.act(&mut seats, &Action::Bet(8), 0).unwrap();
+        let options = round.options(&seats, 8).unwrap();
+        assert_eq!(
+            (options.min_raise_to, options.max_raise_to),
+            (Some(16), Some(16))
+        );
+    }
+
+    #[test]
+    fn test_short_all_in_does_not_reopen_action() {
+        let mut seats = seats(&[100, 25, 100]);
+        let mut round = BettingRound::new(Limit::NoLimit, 2, &seats, 0);
+        round.act(&mut seats, &Action::Bet(20), 0).unwrap();
+        // All-in for less than a full raise
+        assert_eq!(round.options(&seats, 20).unwrap().min_raise_to, Some(25));
+        round.act(&mut seats, &Action::Raise(25), 20).unwrap();
+        round.act(&mut seats, &Action::Call, 45).unwrap();
+        let options = round.options(&seats, 70).unwrap();
+        assert_eq!(options.seat, 0);
+        assert_eq!(options.to_call, 5);
+        assert_eq!(options.min_raise_to, None);
+        round.act(&mut seats, &Action::Call, 70).unwrap();
+        assert_eq!(round.to_act(), None);
+    }
+
+    #[test]
+    fn test_no_action_when_everyone_else_is_all_in() {
+        let mut seats = seats(&[100, 50]);
+        seats[1].put(50);
+        let round = BettingRound::new(Limit::NoLimit, 2, &seats, 0);
+        assert_eq!(round.options(&seats, 50).unwrap().min_raise_to, None);
+
+        let mut seats = self::seats(&[100, 50]);
+        seats[1].put_dead(50);
+        let round = BettingRound::new(Limit::NoLimit, 2, &seats, 0);
+        assert_eq!(round.to_act(), None);
+    }
+}
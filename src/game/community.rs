@@ -0,0 +1,416 @@
+// Hold'em and Omaha hand engine, from the blinds to the showdown
+
+use super::betting::{BettingOptions, BettingRound};
+use super::{
+    blind_seats, order_from, post_forced_bets, pot, return_uncalled, Action, Event, Gameplay,
+    Limit, Seat, Stakes, Street, Variant,
+};
+use crate::deck::Deck;
+use crate::error::Error;
+use crate::evaluation::eight_or_better::EightOrBetter;
+use crate::evaluation::high::High;
+use crate::evaluation::Evaluation;
+use crate::hand::Hand;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommunityConfig {
+    pub variant: Variant,
+    pub limit: Limit,
+    pub stakes: Stakes,
+    pub button: usize,
+    // Everyone posts `stakes.ante` instead of blinds and the hand starts on the flop
+    pub bomb_pot: bool,
+}
+
+#[derive(Debug)]
+pub struct CommunityHand {
+    config: CommunityConfig,
+    seats: Vec<Seat>,
+    deck: Deck,
+    board: Hand,
+    street: Street,
+    round: BettingRound,
+    events: Vec<Event>,
+    unread: usize,
+    finished: bool,
+}
+
+impl CommunityHand {
+    // Seats every stack (0 to sit out), posts the forced bets and deals from `deck`
+    pub fn new(
+        config: CommunityConfig,
+        stacks: &[u64],
+        deck: Deck,
+    ) -> Result<CommunityHand, Error> {
+        let seats: Vec<Seat> = stacks.iter().map(|&stack| Seat::new(stack)).collect();
+        let active = seats.iter().filter(|seat| !seat.sitting_out).count();
+        let unit = match config.limit {
+            Limit::FixedLimit => config.stakes.small_bet.min(config.stakes.big_bet),
+            _ => config.stakes.big_blind,
+        };
+        if active < 2
+            || config.variant.gameplay() != Gameplay::Community
+            || config.button >= seats.len()
+            || seats[config.button].sitting_out
+            || unit == 0
+            || (config.bomb_pot && config.stakes.ante == 0)
+        {
+            return Err(Error::InvalidSetup);
+        }
+
+        let round = BettingRound::new(config.limit, unit, &seats, config.button);
+        let mut hand = CommunityHand {
+            config,
+            seats,
+            deck,
+            board: Hand::new(),
+            street: Street::Preflop,
+            round,
+            events: Vec::new(),
+            unread: 0,
+            finished: false,
+        };
+        let forced = post_forced_bets(
+            &mut hand.seats,
+            config.button,
+            &config.stakes,
+            !config.bomb_pot,
+        );
+        hand.events.extend(forced);
+        hand.deal_hole_cards()?;
+
+        if config.bomb_pot {
+            hand.start_street(Street::Flop)?;
+        } else {
+            hand.events.push(Event::Street(Street::Preflop));
+            let (_, big_blind) = blind_seats(&hand.seats, config.button);
+            hand.round = BettingRound::new(config.limit, hand.unit(), &hand.seats, big_blind + 1);
+        }
+        hand.advance()?;
+        Ok(hand)
+    }
+
+    pub fn config(&self) -> &CommunityConfig {
+        &self.config
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        &self.seats
+    }
+
+    pub fn board(&self) -> &Hand {
+        &self.board
+    }
+
+    pub fn street(&self) -> Street {
+        self.street
+    }
+
+    pub fn pot(&self) -> u64 {
+        self.seats.iter().map(|seat| seat.committed).sum()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn to_act(&self) -> Option<usize> {
+        if self.finished {
+            return None;
+        }
+        self.round.to_act()
+    }
+
+    pub fn options(&self) -> Option<BettingOptions> {
+        if self.finished {
+            return None;
+        }
+        self.round.options(&self.seats, self.pot())
+    }
+
+    // Every event since the hand started
+    pub fn history(&self) -> &[Event] {
+        &self.events
+    }
+
+    // Events since the previous call
+    pub fn take_events(&mut self) -> Vec<Event> {
+        let events = self.events[self.unread..].to_vec();
+        self.unread = self.events.len();
+        events
+    }
+
+    // Act for the player returned by `to_act`
+    pub fn act(&mut self, action: Action) -> Result<(), Error> {
+        let seat = self.to_act().ok_or(Error::InvalidAction)?;
+        let pot = self.pot();
+        self.round.act(&mut self.seats, &action, pot)?;
+        self.events.push(Event::Action { seat, action });
+        self.advance()
+    }
+
+    fn unit(&self) -> u64 {
+        match (self.config.limit, self.street) {
+            (Limit::FixedLimit, Street::Preflop | Street::Flop) => self.config.stakes.small_bet,
+            (Limit::FixedLimit, _) => self.config.stakes.big_bet,
+            _ => self.config.stakes.big_blind,
+        }
+    }
+
+    fn order(&self) -> Vec<usize> {
+        order_from(&self.seats, self.config.button)
+    }
+
+    fn deal_hole_cards(&mut self) -> Result<(), Error> {
+        let order = self.order();
+        for _ in 0..self.config.variant.hole_cards() {
+            for &seat in &order {
+                let card = self.deck.deal_card().ok_or(Error::NotEnoughCards)?;
+                self.seats[seat].hole.cards.push(card);
+            }
+        }
+        for seat in order {
+            let cards = self.seats[seat].hole.clone();
+            self.events.push(Event::Dealt {
+                seat,
+                cards,
+                face_up: false,
+            });
+        }
+        Ok(())
+    }
+
+    fn start_street(&mut self, street: Street) -> Result<(), Error> {
+        self.street = street;
+        self.events.push(Event::Street(street));
+
+        let count = if street == Street::Flop { 3 } else { 1 };
+        self.deck.deal_card().ok_or(Error::NotEnoughCards)?; // burn
+        let cards = self.deck.deal(count)?;
+        self.board.cards.extend(cards.cards.iter().copied());
+        self.events.push(Event::Board(cards));
+
+        self.round = BettingRound::new(
+            self.config.limit,
+            self.unit(),
+            &self.seats,
+            self.config.button + 1,
+        );
+        Ok(())
+    }
+
+    // Move on until a player has to act or the hand is over
+    fn advance(&mut self) -> Result<(), Error> {
+        while !self.finished && self.round.to_act().is_none() {
+            if let Some(event) = return_uncalled(&mut self.seats) {
+                self.events.push(event);
+            }
+            for seat in self.seats.iter_mut() {
+                seat.bet = 0;
+            }
+
+            if self.order().len() == 1 {
+                self.finish()?;
+                continue;
+            }
+            match self.street {
+                Street::Preflop => self.start_street(Street::Flop)?,
+                Street::Flop => self.start_street(Street::Turn)?,
+                Street::Turn => self.start_street(Street::River)?,
+                _ => self.finish()?,
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        let order = self.order();
+        let mut high: Vec<Option<u128>> = vec![None; self.seats.len()];
+        let mut low: Vec<Option<u128>> = vec![None; self.seats.len()];
+
+        if order.len() == 1 {
+            high[order[0]] = Some(0);
+        } else {
+            for &seat in &order {
+                let hole = &self.seats[seat].hole;
+                high[seat] = Some(best_high(self.config.variant, hole, &self.board)?.score);
+                if self.config.variant.is_split_pot() {
+                    low[seat] = best_low(hole, &self.board)?.map(|evaluation| evaluation.score);
+                }
+                self.events.push(Event::Showdown {
+                    seat,
+                    cards: self.seats[seat].hole.clone(),
+                });
+            }
+        }
+
+        let pots = pot::pots(&self.seats);
+        for event in pot::award(&pots, &high, &low, &order) {
+            if let Event::Award { seat, amount, .. } = event {
+                self.seats[seat].stack += amount;
+            }
+            self.events.push(event);
+        }
+        self.finished = true;
+        Ok(())
+    }
+}
+
+// Best high hand of a player, Omaha variants use exactly two hole cards
+pub fn best_high(variant: Variant, hole: &Hand, board: &Hand) -> Result<Evaluation<High>, Error> {
+    match variant {
+        Variant::Holdem => {
+            Evaluation::<High>::best_of(&[&hole.cards[..], &board.cards[..]].concat(), 5)
+        }
+        _ => Evaluation::<High>::best_omaha(&hole.cards, &board.cards),
+    }
+}
+
+// Best qualifying 8 or better low of an Omaha hi-lo player, if any
+pub fn best_low(hole: &Hand, board: &Hand) -> Result<Option<Evaluation<EightOrBetter>>, Error> {
+    let evaluation = Evaluation::<EightOrBetter>::best_omaha(&hole.cards, &board.cards)?;
+    Ok(Some(evaluation).filter(|evaluation| evaluation.score > 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::stacked_deck;
+
+    fn config(variant: Variant) -> CommunityConfig {
+        CommunityConfig {
+            variant,
+            limit: Limit::NoLimit,
+            stakes: Stakes::new(1, 2),
+            button: 0,
+            bomb_pot: false,
+        }
+    }
+
+    #[test]
+    fn test_invalid_setup() {
+        assert_eq!(
+            CommunityHand::new(config(Variant::Holdem), &[100], Deck::new()).unwrap_err(),
+            Error::InvalidSetup
+        );
+        assert_eq!(
+            CommunityHand::new(config(Variant::Holdem), &[0, 100, 100], Deck::new()).unwrap_err(),
+            Error::InvalidSetup
+        );
+    }
+
+    #[test]
+    fn test_heads_up_to_showdown() {
+        // Seat 1 is dealt first, heads-up the button is the small blind
+        let deck = stacked_deck("Ah 7c Ad 2d 3s Kh Qc 9d 4s 8h 5s 6h");
+        let mut hand = CommunityHand::new(config(Variant::Holdem), &[100, 100], deck).unwrap();
+        assert_eq!(hand.seats()[0].hole.to_string(), "7c 2d");
+        assert_eq!(hand.seats()[1].hole.to_string(), "Ah Ad");
+        assert_eq!(hand.pot(), 3);
+        assert_eq!(hand.to_act(), Some(0));
+
+        hand.act(Action::Call).unwrap();
+        assert_eq!(hand.act(Action::Call), Err(Error::InvalidAction));
+        hand.act(Action::Check).unwrap();
+        assert_eq!(hand.street(), Street::Flop);
+        assert_eq!(hand.board().to_string(), "Kh Qc 9d");
+        // Big blind acts first after the flop
+        assert_eq!(hand.to_act(), Some(1));
+        hand.act(Action::Bet(10)).unwrap();
+        hand.act(Action::Call).unwrap();
+        for _ in 0..4 {
+            hand.act(Action::Check).unwrap();
+        }
+
+        assert!(hand.is_finished());
+        assert_eq!(hand.board().to_string(), "Kh Qc 9d 8h 6h");
+        assert_eq!(hand.seats()[0].stack, 88);
+        assert_eq!(hand.seats()[1].stack, 112);
+        assert!(hand.history().contains(&Event::Award {
+            seat: 1,
+            amount: 24,
+            low: false
+        }));
+        assert_eq!(hand.act(Action::Check), Err(Error::InvalidAction));
+    }
+
+    #[test]
+    fn test_fold_to_raise() {
+        let mut hand =
+            CommunityHand::new(config(Variant::Holdem), &[100, 100, 100], Deck::new()).unwrap();
+        // Under the gun is the button three-handed
+        assert_eq!(hand.to_act(), Some(0));
+        hand.act(Action::Raise(6)).unwrap();
+        hand.act(Action::Fold).unwrap();
+        hand.act(Action::Fold).unwrap();
+
+        assert!(hand.is_finished());
+        assert_eq!(hand.board().cards.len(), 0);
+        let stacks: Vec<u64> = hand.seats().iter().map(|seat| seat.stack).collect();
+        assert_eq!(stacks, vec![103, 99, 98]);
+        let events = hand.take_events();
+        assert!(events.contains(&Event::Returned { seat: 0, amount: 4 }));
+        assert!(hand.take_events().is_empty());
+    }
+
+    #[test]
+    fn test_all_in_runs_out_the_board_with_side_pot() {
+        // Seat 1 is dealt first, then 2 and the button
+        let deck = stacked_deck("Ah Kh Qh As Kd Qd 2c 3c 4d 9s 7d 5h 8c Jc");
+        let mut hand = CommunityHand::new(config(Variant::Holdem), &[100, 20, 50], deck).unwrap();
+        hand.act(Action::Raise(100)).unwrap();
+        hand.act(Action::Call).unwrap();
+        hand.act(Action::Call).unwrap();
+
+        assert!(hand.is_finished());
+        assert_eq!(hand.board().cards.len(), 5);
+        // Aces win the main pot, kings the side pot, the rest goes back
+        let stacks: Vec<u64> = hand.seats().iter().map(|seat| seat.stack).collect();
+        assert_eq!(stacks, vec![50, 60, 60]);
+    }
+
+    #[test]
+    fn test_bomb_pot_starts_on_the_flop() {
+        let mut config = config(Variant::Omaha);
+        config.bomb_pot = true;
+        config.limit = Limit::PotLimit;
+        config.stakes.ante = 5;
+        let mut hand = CommunityHand::new(config, &[100, 100, 100], Deck::new()).unwrap();
+
+        assert_eq!(hand.street(), Street::Flop);
+        assert_eq!(hand.board().cards.len(), 3);
+        assert_eq!(hand.pot(), 15);
+        assert_eq!(hand.to_act(), Some(1));
+        assert!(!hand
+            .history()
+            .iter()
+            .any(|event| matches!(event, Event::Blind { .. })));
+        assert_eq!(hand.options().unwrap().max_raise_to, Some(15));
+        hand.act(Action::Check).unwrap();
+    }
+
+    #[test]
+    fn test_omaha_hi_lo_split() {
+        // Seat 1 makes an 8-low, seat 0 the best high with kings
+        let deck = stacked_deck("Ac 9h 2c 9d 5s Kh 4d Kc 7s 3h 7d 8c Jd Ts 2h Qh");
+        let mut config = config(Variant::OmahaHiLo);
+        config.limit = Limit::PotLimit;
+        let mut hand = CommunityHand::new(config, &[100, 100], deck).unwrap();
+        assert_eq!(hand.seats()[1].hole.to_string(), "Ac 2c 5s 4d");
+        hand.act(Action::Call).unwrap();
+        hand.act(Action::Check).unwrap();
+        for _ in 0..6 {
+            hand.act(Action::Check).unwrap();
+        }
+
+        assert!(hand.is_finished());
+        assert_eq!(hand.board().to_string(), "3h 7d 8c Ts Qh");
+        assert_eq!(hand.seats()[0].stack, 100);
+        assert_eq!(hand.seats()[1].stack, 100);
+        assert!(hand.history().contains(&Event::Award {
+            seat: 1,
+            amount: 2,
+            low: true
+        }));
+    }
+}
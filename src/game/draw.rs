@@ -0,0 +1,513 @@
+// Draw game hand engine for 5-Card Draw, triple and single draw lowball and
+// the Badugi family, from the blinds to the showdown
+
+use super::betting::{BettingOptions, BettingRound};
+use super::{
+    blind_seats, order_from, post_forced_bets, pot, return_uncalled, Action, Event, Gameplay,
+    Limit, Seat, Stakes, Street, Variant,
+};
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::error::Error;
+use crate::evaluation::badugi::Badugi;
+use crate::evaluation::high::High;
+use crate::evaluation::lowball_2to7::Lowball2to7;
+use crate::evaluation::lowball_ato5::LowballAto5;
+use crate::evaluation::split::{Badacey, Badeucey};
+use crate::evaluation::EvalHand;
+use crate::hand::Hand;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrawConfig {
+    pub variant: Variant,
+    pub limit: Limit,
+    pub stakes: Stakes,
+    pub button: usize,
+}
+
+#[derive(Debug)]
+pub struct DrawHand {
+    config: DrawConfig,
+    seats: Vec<Seat>,
+    // The stub replacements are drawn from
+    deck: Deck,
+    discards: Vec<Card>,
+    // Reshuffles the discards into a new stub
+    rng: StdRng,
+    street: Street,
+    round: BettingRound,
+    draws_done: u8,
+    // Seats yet to draw in the current draw, in order
+    drawing: Vec<usize>,
+    events: Vec<Event>,
+    unread: usize,
+    finished: bool,
+}
+
+impl DrawHand {
+    // Seats every stack (0 to sit out), posts the blinds and deals from `deck`
+    pub fn new(config: DrawConfig, stacks: &[u64], deck: Deck) -> Result<DrawHand, Error> {
+        Self::with_rng(config, stacks, deck, StdRng::from_entropy())
+    }
+
+    // Same as `new`, reshuffling the discards with `rng` for reproducible hands
+    pub fn with_rng(
+        config: DrawConfig,
+        stacks: &[u64],
+        deck: Deck,
+        rng: StdRng,
+    ) -> Result<DrawHand, Error> {
+        let seats: Vec<Seat> = stacks.iter().map(|&stack| Seat::new(stack)).collect();
+        let active = seats.iter().filter(|seat| !seat.sitting_out).count();
+        let unit = match config.limit {
+            Limit::FixedLimit => config.stakes.small_bet.min(config.stakes.big_bet),
+            _ => config.stakes.big_blind,
+        };
+        if active < 2
+            || active * config.variant.hole_cards() >= deck.cards.len()
+            || config.variant.gameplay() != Gameplay::Draw
+            || config.button >= seats.len()
+            || seats[config.button].sitting_out
+            || unit == 0
+        {
+            return Err(Error::InvalidSetup);
+        }
+
+        let round = BettingRound::new(config.limit, unit, &seats, config.button);
+        let mut hand = DrawHand {
+            config,
+            seats,
+            deck,
+            discards: Vec::new(),
+            rng,
+            street: Street::Predraw,
+            round,
+            draws_done: 0,
+            drawing: Vec::new(),
+            events: Vec::new(),
+            unread: 0,
+            finished: false,
+        };
+        let forced = post_forced_bets(&mut hand.seats, config.button, &config.stakes, true);
+        hand.events.extend(forced);
+
+        let order = hand.order();
+        for _ in 0..config.variant.hole_cards() {
+            for &seat in &order {
+                let card = hand.deck.deal_card().ok_or(Error::NotEnoughCards)?;
+                hand.seats[seat].hole.cards.push(card);
+            }
+        }
+        for seat in order {
+            let cards = hand.seats[seat].hole.clone();
+            hand.events.push(Event::Dealt {
+                seat,
+                cards,
+                face_up: false,
+            });
+        }
+
+        hand.events.push(Event::Street(Street::Predraw));
+        let (_, big_blind) = blind_seats(&hand.seats, config.button);
+        hand.round = BettingRound::new(config.limit, hand.unit(), &hand.seats, big_blind + 1);
+        hand.advance()?;
+        Ok(hand)
+    }
+
+    pub fn config(&self) -> &DrawConfig {
+        &self.config
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        &self.seats
+    }
+
+    pub fn street(&self) -> Street {
+        self.street
+    }
+
+    pub fn pot(&self) -> u64 {
+        self.seats.iter().map(|seat| seat.committed).sum()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    // Whether the player to act has to discard rather than bet
+    pub fn is_drawing(&self) -> bool {
+        !self.finished && !self.drawing.is_empty()
+    }
+
+    pub fn draws_remaining(&self) -> u8 {
+        self.config.variant.draws() - self.draws_done
+    }
+
+    pub fn to_act(&self) -> Option<usize> {
+        if self.finished {
+            return None;
+        }
+        match self.drawing.first() {
+            Some(&seat) => Some(seat),
+            None => self.round.to_act(),
+        }
+    }
+
+    // Betting options, none while drawing
+    pub fn options(&self) -> Option<BettingOptions> {
+        if self.finished || !self.drawing.is_empty() {
+            return None;
+        }
+        self.round.options(&self.seats, self.pot())
+    }
+
+    // Every event since the hand started
+    pub fn history(&self) -> &[Event] {
+        &self.events
+    }
+
+    // Events since the previous call
+    pub fn take_events(&mut self) -> Vec<Event> {
+        let events = self.events[self.unread..].to_vec();
+        self.unread = self.events.len();
+        events
+    }
+
+    // Act for the player returned by `to_act`, discarding while drawing
+    pub fn act(&mut self, action: Action) -> Result<(), Error> {
+        let seat = self.to_act().ok_or(Error::InvalidAction)?;
+        if self.drawing.is_empty() {
+            let pot = self.pot();
+            self.round.act(&mut self.seats, &action, pot)?;
+            self.events.push(Event::Action { seat, action });
+        } else {
+            let discards = match action {
+                Action::Discard(cards) => cards,
+                _ => return Err(Error::InvalidAction),
+            };
+            self.draw(seat, discards)?;
+            self.drawing.remove(0);
+            if self.drawing.is_empty() {
+                self.draws_done += 1;
+                self.start_street()?;
+            }
+        }
+        self.advance()
+    }
+
+    fn order(&self) -> Vec<usize> {
+        order_from(&self.seats, self.config.button)
+    }
+
+    // Fixed limit uses the small bet for the first half of the betting rounds
+    fn unit(&self) -> u64 {
+        let stakes = self.config.stakes;
+        match self.config.limit {
+            Limit::FixedLimit if self.draws_done < self.config.variant.draws().div_ceil(2) => {
+                stakes.small_bet
+            }
+            Limit::FixedLimit => stakes.big_bet,
+            _ => stakes.big_blind,
+        }
+    }
+
+    // Replace the discards from the stub. When the stub runs short, every
+    // previous discard except this player's is shuffled into a new stub.
+    fn draw(&mut self, seat: usize, discards: Vec<Card>) -> Result<(), Error> {
+        let hole = &self.seats[seat].hole.cards;
+        let mut unique = discards.clone();
+        unique.sort();
+        unique.dedup();
+        if unique.len() != discards.len() || discards.iter().any(|card| !hole.contains(card)) {
+            return Err(Error::InvalidAction);
+        }
+        if discards.len() > self.deck.cards.len() + self.discards.len() {
+            return Err(Error::NotEnoughCards);
+        }
+
+        let mut drawn: Vec<Card> = Vec::new();
+        while drawn.len() < discards.len() {
+            match self.deck.deal_card() {
+                Some(card) => drawn.push(card),
+                None => {
+                    self.deck.cards.append(&mut self.discards);
+                    self.deck.shuffle_with_rng(&mut self.rng);
+                    self.events.push(Event::Reshuffle {
+                        cards: self.deck.cards.len(),
+                    });
+                }
+            }
+        }
+
+        let hole = &mut self.seats[seat].hole.cards;
+        hole.retain(|card| !discards.contains(card));
+        hole.extend(drawn.iter().copied());
+        self.discards.extend(discards.iter().copied());
+        self.events.push(Event::Draw {
+            seat,
+            discarded: Hand::from(discards),
+            drawn: Hand::from(drawn),
+        });
+        Ok(())
+    }
+
+    // Betting round after the draw just completed
+    fn start_street(&mut self) -> Result<(), Error> {
+        self.street = match self.draws_done {
+            1 => Street::FirstDraw,
+            2 => Street::SecondDraw,
+            3 => Street::ThirdDraw,
+            _ => return Err(Error::InvalidAction),
+        };
+        self.events.push(Event::Street(self.street));
+        self.round = BettingRound::new(
+            self.config.limit,
+            self.unit(),
+            &self.seats,
+            self.config.button + 1,
+        );
+        Ok(())
+    }
+
+    // Move on until a player has to act or the hand is over
+    fn advance(&mut self) -> Result<(), Error> {
+        while !self.finished && self.to_act().is_none() {
+            if let Some(event) = return_uncalled(&mut self.seats) {
+                self.events.push(event);
+            }
+            for seat in self.seats.iter_mut() {
+                seat.bet = 0;
+            }
+
+            if self.order().len() == 1 || self.draws_remaining() == 0 {
+                self.finish()?;
+            } else {
+                // Everyone still in draws, including all-in players
+                self.drawing = self.order();
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        let order = self.order();
+        let mut high: Vec<Option<u128>> = vec![None; self.seats.len()];
+        let mut low: Vec<Option<u128>> = vec![None; self.seats.len()];
+
+        if order.len() == 1 {
+            high[order[0]] = Some(0);
+        } else {
+            for &seat in &order {
+                let (seat_high, seat_low) =
+                    showdown_scores(self.config.variant, &self.seats[seat].hole)?;
+                high[seat] = Some(seat_high);
+                low[seat] = seat_low;
+                self.events.push(Event::Showdown {
+                    seat,
+                    cards: self.seats[seat].hole.clone(),
+                });
+            }
+        }
+
+        let pots = pot::pots(&self.seats);
+        for event in pot::award(&pots, &high, &low, &order) {
+            if let Event::Award { seat, amount, .. } = event {
+                self.seats[seat].stack += amount;
+            }
+            self.events.push(event);
+        }
+        self.finished = true;
+        Ok(())
+    }
+}
+
+// Score of a final draw hand, and of the second half in Badacey and Badeucey
+pub fn showdown_scores(variant: Variant, hand: &Hand) -> Result<(u128, Option<u128>), Error> {
+    match variant {
+        Variant::FiveCardDraw => Ok((High::eval_hand(hand)?, None)),
+        Variant::AceToFiveTripleDraw => Ok((LowballAto5::eval_hand(hand)?, None)),
+        Variant::DeuceToSevenSingleDraw | Variant::DeuceToSevenTripleDraw => {
+            Ok((Lowball2to7::eval_hand(hand)?, None))
+        }
+        Variant::Badugi => Ok((Badugi::eval_hand(hand)?, None)),
+        Variant::Badacey => {
+            let (badugi, low) = Badacey::try_from(hand.clone())?.scores();
+            Ok((badugi, Some(low)))
+        }
+        Variant::Badeucey => {
+            let (badugi, low) = Badeucey::try_from(hand.clone())?.scores();
+            Ok((badugi, Some(low)))
+        }
+        _ => Err(Error::InvalidHand),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::stacked_deck;
+
+    fn config(variant: Variant) -> DrawConfig {
+        DrawConfig {
+            variant,
+            limit: Limit::FixedLimit,
+            stakes: Stakes::new(1, 2),
+            button: 0,
+        }
+    }
+
+    fn cards(s: &str) -> Vec<Card> {
+        Hand::try_from(s).unwrap().cards
+    }
+
+    #[test]
+    fn test_invalid_setup() {
+        assert_eq!(
+            DrawHand::new(config(Variant::Razz), &[100, 100], Deck::new()).unwrap_err(),
+            Error::InvalidSetup
+        );
+        // 11 players would leave no stub
+        assert_eq!(
+            DrawHand::new(config(Variant::FiveCardDraw), &[100; 11], Deck::new()).unwrap_err(),
+            Error::InvalidSetup
+        );
+    }
+
+    #[test]
+    fn test_single_draw() {
+        // Seat 1 is dealt first
+        let deck = stacked_deck("7c Kc 5d Kd 4h Ks 3s Qc 2c Qd 8h As");
+        let mut hand =
+            DrawHand::new(config(Variant::DeuceToSevenSingleDraw), &[100, 100], deck).unwrap();
+        assert_eq!(hand.seats()[1].hole.to_string(), "7c 5d 4h 3s 2c");
+        assert_eq!(hand.draws_remaining(), 1);
+        hand.act(Action::Call).unwrap();
+        hand.act(Action::Check).unwrap();
+
+        // Draw starts left of the button
+        assert!(hand.is_drawing());
+        assert_eq!(hand.to_act(), Some(1));
+        assert_eq!(hand.options(), None);
+        assert_eq!(hand.act(Action::Check), Err(Error::InvalidAction));
+        // Cannot discard cards not in hand, or the same card twice
+        assert_eq!(
+            hand.act(Action::Discard(cards("Ah"))),
+            Err(Error::InvalidAction)
+        );
+        hand.act(Action::Discard(vec![])).unwrap();
+        assert_eq!(
+            hand.act(Action::Discard(cards("Kc Kc"))),
+            Err(Error::InvalidAction)
+        );
+        hand.act(Action::Discard(cards("Kc Kd"))).unwrap();
+        assert_eq!(hand.seats()[0].hole.to_string(), "Ks Qc Qd 8h As");
+        assert!(hand.history().contains(&Event::Draw {
+            seat: 0,
+            discarded: Hand::try_from("Kc Kd").unwrap(),
+            drawn: Hand::try_from("8h As").unwrap(),
+        }));
+
+        // Big bet after the draw
+        assert_eq!(hand.street(), Street::FirstDraw);
+        assert_eq!(hand.to_act(), Some(1));
+        assert_eq!(hand.options().unwrap().min_raise_to, Some(4));
+        hand.act(Action::Bet(4)).unwrap();
+        hand.act(Action::Call).unwrap();
+        assert!(hand.is_finished());
+        assert_eq!(hand.seats()[1].stack, 106);
+    }
+
+    #[test]
+    fn test_triple_draw_count() {
+        let mut hand = DrawHand::new(
+            config(Variant::AceToFiveTripleDraw),
+            &[100, 100],
+            Deck::new(),
+        )
+        .unwrap();
+        let mut draws = 0;
+        while !hand.is_finished() {
+            if hand.is_drawing() {
+                draws += 1;
+                hand.act(Action::Discard(vec![])).unwrap();
+            } else if hand.options().unwrap().can_check() {
+                hand.act(Action::Check).unwrap();
+            } else {
+                hand.act(Action::Call).unwrap();
+            }
+        }
+        assert_eq!(draws, 6);
+        assert_eq!(hand.draws_remaining(), 0);
+        assert_eq!(hand.street(), Street::ThirdDraw);
+    }
+
+    #[test]
+    fn test_reshuffle_excludes_current_discards() {
+        let mut hand = DrawHand::with_rng(
+            config(Variant::DeuceToSevenTripleDraw),
+            &[1000; 8],
+            Deck::new(),
+            StdRng::seed_from_u64(7),
+        )
+        .unwrap();
+        let mut reshuffled = false;
+        while !hand.is_finished() {
+            if hand.is_drawing() {
+                let seat = hand.to_act().unwrap();
+                let discards = hand.seats()[seat].hole.cards.clone();
+                hand.act(Action::Discard(discards.clone())).unwrap();
+                // Nobody gets back the cards they just threw
+                let hole = &hand.seats()[seat].hole.cards;
+                assert!(discards.iter().all(|card| !hole.contains(card)));
+                reshuffled |= hand
+                    .take_events()
+                    .iter()
+                    .any(|event| matches!(event, Event::Reshuffle { .. }));
+            } else if hand.options().unwrap().can_check() {
+                hand.act(Action::Check).unwrap();
+            } else {
+                hand.act(Action::Call).unwrap();
+            }
+        }
+        assert!(reshuffled);
+
+        // Every card is accounted for exactly once
+        let mut all: Vec<Card> = hand.deck.cards.clone();
+        all.extend(hand.discards.iter().copied());
+        for seat in hand.seats() {
+            all.extend(seat.hole.cards.iter().copied());
+        }
+        assert_eq!(all.len(), 52);
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 52);
+    }
+
+    #[test]
+    fn test_badacey_split() {
+        // Seat 1: A-2-3-4 badugi with a 4-low pair, seat 0: 7-high A-5 low
+        let deck = stacked_deck("Ac 7c 2d 5d 3h 4h 4s 3s 4d 2c");
+        let mut hand = DrawHand::new(config(Variant::Badacey), &[100, 100], deck).unwrap();
+        hand.act(Action::Call).unwrap();
+        hand.act(Action::Check).unwrap();
+        while !hand.is_finished() {
+            if hand.is_drawing() {
+                hand.act(Action::Discard(vec![])).unwrap();
+            } else {
+                hand.act(Action::Check).unwrap();
+            }
+        }
+        assert!(hand.history().contains(&Event::Award {
+            seat: 1,
+            amount: 2,
+            low: false
+        }));
+        assert!(hand.history().contains(&Event::Award {
+            seat: 0,
+            amount: 2,
+            low: true
+        }));
+    }
+}
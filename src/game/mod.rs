@@ -0,0 +1,538 @@
+// Game engines driving a single hand from the forced bets to the showdown.
+// Engines accept player actions and expose everything that happens as a
+// stream of `Event`s, so servers and bots can plug in.
+
+use crate::card::Card;
+use crate::hand::Hand;
+
+pub mod betting;
+pub mod community;
+pub mod draw;
+pub mod pot;
+pub mod strategy;
+pub mod stud;
+pub mod table;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gameplay {
+    Community,
+    Stud,
+    Draw,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Holdem,
+    Omaha,
+    OmahaHiLo,
+    FiveCardOmaha,
+    BigO,
+    SevenCardStud,
+    StudHiLo,
+    Razz,
+    FiveCardDraw,
+    AceToFiveTripleDraw,
+    DeuceToSevenSingleDraw,
+    DeuceToSevenTripleDraw,
+    Badugi,
+    Badacey,
+    Badeucey,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 15] = [
+        Variant::Holdem,
+        Variant::Omaha,
+        Variant::OmahaHiLo,
+        Variant::FiveCardOmaha,
+        Variant::BigO,
+        Variant::SevenCardStud,
+        Variant::StudHiLo,
+        Variant::Razz,
+        Variant::FiveCardDraw,
+        Variant::AceToFiveTripleDraw,
+        Variant::DeuceToSevenSingleDraw,
+        Variant::DeuceToSevenTripleDraw,
+        Variant::Badugi,
+        Variant::Badacey,
+        Variant::Badeucey,
+    ];
+
+    pub fn gameplay(&self) -> Gameplay {
+        match self {
+            Variant::SevenCardStud | Variant::StudHiLo | Variant::Razz => Gameplay::Stud,
+            Variant::FiveCardDraw
+            | Variant::AceToFiveTripleDraw
+            | Variant::DeuceToSevenSingleDraw
+            | Variant::DeuceToSevenTripleDraw
+            | Variant::Badugi
+            | Variant::Badacey
+            | Variant::Badeucey => Gameplay::Draw,
+            _ => Gameplay::Community,
+        }
+    }
+
+    // Number of cards dealt to each player
+    pub fn hole_cards(&self) -> usize {
+        match self {
+            Variant::Holdem => 2,
+            Variant::Omaha | Variant::OmahaHiLo => 4,
+            Variant::FiveCardOmaha | Variant::BigO => 5,
+            Variant::SevenCardStud | Variant::StudHiLo | Variant::Razz => 7,
+            Variant::Badugi => 4,
+            Variant::FiveCardDraw
+            | Variant::AceToFiveTripleDraw
+            | Variant::DeuceToSevenSingleDraw
+            | Variant::DeuceToSevenTripleDraw
+            | Variant::Badacey
+            | Variant::Badeucey => 5,
+        }
+    }
+
+    // Number of draws in draw games
+    pub fn draws(&self) -> u8 {
+        match self.gameplay() {
+            Gameplay::Draw => match self {
+                Variant::FiveCardDraw | Variant::DeuceToSevenSingleDraw => 1,
+                _ => 3,
+            },
+            _ => 0,
+        }
+    }
+
+    // Whether the pot is split in two halves, e.g. high and low in hi-lo
+    pub fn is_split_pot(&self) -> bool {
+        matches!(
+            self,
+            Variant::OmahaHiLo
+                | Variant::BigO
+                | Variant::StudHiLo
+                | Variant::Badacey
+                | Variant::Badeucey
+        )
+    }
+}
+
+impl std::fmt::Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Variant::Holdem => write!(f, "Hold'em"),
+            Variant::Omaha => write!(f, "Omaha"),
+            Variant::OmahaHiLo => write!(f, "Omaha Hi-Lo 8 or Better"),
+            Variant::FiveCardOmaha => write!(f, "5-card Omaha"),
+            Variant::BigO => write!(f, "Big O"),
+            Variant::SevenCardStud => write!(f, "Seven Card Stud"),
+            Variant::StudHiLo => write!(f, "Seven Card Stud Hi-Lo 8 or Better"),
+            Variant::Razz => write!(f, "Razz"),
+            Variant::FiveCardDraw => write!(f, "5-Card Draw"),
+            Variant::AceToFiveTripleDraw => write!(f, "A-5 Triple Draw"),
+            Variant::DeuceToSevenSingleDraw => write!(f, "2-7 Single Draw"),
+            Variant::DeuceToSevenTripleDraw => write!(f, "2-7 Triple Draw"),
+            Variant::Badugi => write!(f, "Badugi"),
+            Variant::Badacey => write!(f, "Badacey"),
+            Variant::Badeucey => write!(f, "Badeucey"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    FixedLimit,
+    PotLimit,
+    NoLimit,
+}
+
+impl Limit {
+    pub const ALL: [Limit; 3] = [Limit::FixedLimit, Limit::PotLimit, Limit::NoLimit];
+}
+
+impl std::fmt::Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::FixedLimit => write!(f, "FL"),
+            Limit::PotLimit => write!(f, "PL"),
+            Limit::NoLimit => write!(f, "NL"),
+        }
+    }
+}
+
+// Forced bets and bet sizes. Fixed limit games bet `small_bet` on the early
+// streets and `big_bet` on the later ones, other limits use `big_blind` as
+// the minimum bet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stakes {
+    pub ante: u64,
+    pub small_blind: u64,
+    pub big_blind: u64,
+    pub bring_in: u64,
+    pub small_bet: u64,
+    pub big_bet: u64,
+}
+
+impl Stakes {
+    pub fn new(small_blind: u64, big_blind: u64) -> Stakes {
+        Stakes {
+            ante: 0,
+            small_blind,
+            big_blind,
+            bring_in: 0,
+            small_bet: big_blind,
+            big_bet: big_blind * 2,
+        }
+    }
+
+    pub fn stud(ante: u64, bring_in: u64, small_bet: u64, big_bet: u64) -> Stakes {
+        Stakes {
+            ante,
+            small_blind: 0,
+            big_blind: 0,
+            bring_in,
+            small_bet,
+            big_bet,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+    Predraw,
+    // Betting after the first, second and third draws
+    FirstDraw,
+    SecondDraw,
+    ThirdDraw,
+}
+
+impl Street {
+    pub const ALL: [Street; 13] = [
+        Street::Preflop,
+        Street::Flop,
+        Street::Turn,
+        Street::River,
+        Street::Third,
+        Street::Fourth,
+        Street::Fifth,
+        Street::Sixth,
+        Street::Seventh,
+        Street::Predraw,
+        Street::FirstDraw,
+        Street::SecondDraw,
+        Street::ThirdDraw,
+    ];
+}
+
+impl std::fmt::Display for Street {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Street::Preflop => write!(f, "Preflop"),
+            Street::Flop => write!(f, "Flop"),
+            Street::Turn => write!(f, "Turn"),
+            Street::River => write!(f, "River"),
+            Street::Third => write!(f, "Third street"),
+            Street::Fourth => write!(f, "Fourth street"),
+            Street::Fifth => write!(f, "Fifth street"),
+            Street::Sixth => write!(f, "Sixth street"),
+            Street::Seventh => write!(f, "Seventh street"),
+            Street::Predraw => write!(f, "Predraw"),
+            Street::FirstDraw => write!(f, "First draw"),
+            Street::SecondDraw => write!(f, "Second draw"),
+            Street::ThirdDraw => write!(f, "Third draw"),
+        }
+    }
+}
+
+// Bet and raise amounts are the total the player has put in on the street
+// after the action, i.e. "raise to".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Fold,
+    Check,
+    Call,
+    Bet(u64),
+    Raise(u64),
+    // Forced opening bet of the lowest (highest in Razz) up card in stud
+    BringIn,
+    // Raise a bring-in up to a full small bet
+    Complete,
+    // Cards thrown away in a draw, none to stand pat
+    Discard(Vec<Card>),
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Fold => write!(f, "folds"),
+            Action::Check => write!(f, "checks"),
+            Action::Call => write!(f, "calls"),
+            Action::Bet(amount) => write!(f, "bets {}", amount),
+            Action::Raise(amount) => write!(f, "raises to {}", amount),
+            Action::BringIn => write!(f, "brings in"),
+            Action::Complete => write!(f, "completes"),
+            Action::Discard(cards) if cards.is_empty() => write!(f, "stands pat"),
+            Action::Discard(cards) => write!(f, "discards {}", cards.len()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    Ante {
+        seat: usize,
+        amount: u64,
+    },
+    Blind {
+        seat: usize,
+        amount: u64,
+    },
+    Dealt {
+        seat: usize,
+        cards: Hand,
+        face_up: bool,
+    },
+    Street(Street),
+    Board(Hand),
+    Action {
+        seat: usize,
+        action: Action,
+    },
+    // Uncalled part of a bet going back to the bettor
+    Returned {
+        seat: usize,
+        amount: u64,
+    },
+    // Replacement cards for a player's discards
+    Draw {
+        seat: usize,
+        discarded: Hand,
+        drawn: Hand,
+    },
+    // Discards shuffled into a new stub when the deck runs short
+    Reshuffle {
+        cards: usize,
+    },
+    Showdown {
+        seat: usize,
+        cards: Hand,
+    },
+    // `low` is set for the low half of a split pot
+    Award {
+        seat: usize,
+        amount: u64,
+        low: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Seat {
+    pub stack: u64,
+    // Chips put in on the current street
+    pub bet: u64,
+    // Chips put in during the whole hand, including antes
+    pub committed: u64,
+    pub folded: bool,
+    pub all_in: bool,
+    // Seats without chips are not dealt in
+    pub sitting_out: bool,
+    // Face-down cards
+    pub hole: Hand,
+    // Face-up cards in stud games
+    pub up: Hand,
+}
+
+impl Seat {
+    pub fn new(stack: u64) -> Seat {
+        Seat {
+            stack,
+            sitting_out: stack == 0,
+            ..Default::default()
+        }
+    }
+
+    pub fn in_hand(&self) -> bool {
+        !self.folded && !self.sitting_out
+    }
+
+    pub fn can_act(&self) -> bool {
+        self.in_hand() && !self.all_in
+    }
+
+    // Move chips from the stack to the pot, returning the amount actually put in
+    pub(crate) fn put(&mut self, amount: u64) -> u64 {
+        let amount = amount.min(self.stack);
+        self.stack -= amount;
+        self.bet += amount;
+        self.committed += amount;
+        if self.stack == 0 {
+            self.all_in = true;
+        }
+        amount
+    }
+
+    // Dead money such as antes, which does not count towards the street's bet
+    pub(crate) fn put_dead(&mut self, amount: u64) -> u64 {
+        let amount = self.put(amount);
+        self.bet -= amount;
+        amount
+    }
+}
+
+// Next seat after `from`, going clockwise, that satisfies `predicate`
+pub(crate) fn next_seat<F>(seats: &[Seat], from: usize, predicate: F) -> Option<usize>
+where
+    F: Fn(&Seat) -> bool,
+{
+    (1..=seats.len())
+        .map(|offset| (from + offset) % seats.len())
+        .find(|&i| predicate(&seats[i]))
+}
+
+// Seats still in the hand, starting left of the button
+pub(crate) fn order_from(seats: &[Seat], button: usize) -> Vec<usize> {
+    (1..=seats.len())
+        .map(|offset| (button + offset) % seats.len())
+        .filter(|&i| seats[i].in_hand())
+        .collect()
+}
+
+// Small and big blind seats, heads-up the button posts the small blind
+pub(crate) fn blind_seats(seats: &[Seat], button: usize) -> (usize, usize) {
+    let active = |seat: &Seat| !seat.sitting_out;
+    let heads_up = seats.iter().filter(|seat| active(seat)).count() == 2;
+    let small = if heads_up {
+        button
+    } else {
+        next_seat(seats, button, active).unwrap_or(button)
+    };
+    let big = next_seat(seats, small, active).unwrap_or(small);
+    (small, big)
+}
+
+// Antes from everyone, then the blinds if `blinds` is set
+pub(crate) fn post_forced_bets(
+    seats: &mut [Seat],
+    button: usize,
+    stakes: &Stakes,
+    blinds: bool,
+) -> Vec<Event> {
+    let mut events: Vec<Event> = Vec::new();
+    if stakes.ante > 0 {
+        for seat in order_from(seats, button) {
+            let amount = seats[seat].put_dead(stakes.ante);
+            events.push(Event::Ante { seat, amount });
+        }
+    }
+    if !blinds {
+        return events;
+    }
+
+    let (small, big) = blind_seats(seats, button);
+    for (seat, blind) in [(small, stakes.small_blind), (big, stakes.big_blind)] {
+        if blind > 0 && seats[seat].can_act() {
+            let amount = seats[seat].put(blind);
+            events.push(Event::Blind { seat, amount });
+        }
+    }
+    events
+}
+
+// Give back the part of the largest bet that nobody matched
+pub(crate) fn return_uncalled(seats: &mut [Seat]) -> Option<Event> {
+    let (top, top_bet) = seats
+        .iter()
+        .enumerate()
+        .map(|(i, seat)| (i, seat.bet))
+        .max_by_key(|&(_, bet)| bet)?;
+    let second_bet = seats
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| i != top)
+        .map(|(_, seat)| seat.bet)
+        .max()
+        .unwrap_or(0);
+
+    let amount = top_bet - second_bet;
+    if amount == 0 {
+        return None;
+    }
+    let seat = &mut seats[top];
+    seat.stack += amount;
+    seat.bet -= amount;
+    seat.committed -= amount;
+    seat.all_in = false;
+    Some(Event::Returned { seat: top, amount })
+}
+
+// Deck dealing the given cards first, for the engine tests
+#[cfg(test)]
+pub(crate) fn stacked_deck(s: &str) -> crate::deck::Deck {
+    let mut cards = Hand::try_from(s).unwrap().cards;
+    let rest: Vec<_> = crate::deck::Deck::new()
+        .cards
+        .into_iter()
+        .filter(|card| !cards.contains(card))
+        .collect();
+    cards.extend(rest);
+    crate::deck::Deck { cards }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seat_put() {
+        let mut seat = Seat::new(100);
+        assert_eq!(seat.put_dead(5), 5);
+        assert_eq!(seat.put(30), 30);
+        assert_eq!((seat.stack, seat.bet, seat.committed), (65, 30, 35));
+        assert!(!seat.all_in);
+        assert_eq!(seat.put(100), 65);
+        assert!(seat.all_in);
+        assert!(!seat.can_act());
+        assert!(seat.in_hand());
+        assert!(Seat::new(0).sitting_out);
+    }
+
+    #[test]
+    fn test_next_seat() {
+        let seats = vec![Seat::new(10), Seat::new(0), Seat::new(10)];
+        assert_eq!(next_seat(&seats, 0, Seat::in_hand), Some(2));
+        assert_eq!(next_seat(&seats, 2, Seat::in_hand), Some(0));
+        assert_eq!(next_seat(&seats, 0, |seat| seat.all_in), None);
+    }
+
+    #[test]
+    fn test_blind_seats() {
+        let seats = vec![Seat::new(10), Seat::new(0), Seat::new(10), Seat::new(10)];
+        assert_eq!(blind_seats(&seats, 0), (2, 3));
+        assert_eq!(blind_seats(&seats, 3), (0, 2));
+        assert_eq!(blind_seats(&seats[..3], 2), (2, 0));
+        assert_eq!(order_from(&seats, 2), vec![3, 0, 2]);
+    }
+
+    #[test]
+    fn test_return_uncalled() {
+        let mut seats = vec![Seat::new(100), Seat::new(100), Seat::new(100)];
+        seats[0].put(10);
+        seats[1].put(50);
+        seats[2].put(20);
+        seats[2].folded = true;
+        assert_eq!(
+            return_uncalled(&mut seats),
+            Some(Event::Returned {
+                seat: 1,
+                amount: 30
+            })
+        );
+        assert_eq!((seats[1].stack, seats[1].bet), (80, 20));
+        assert_eq!(return_uncalled(&mut seats), None);
+    }
+}
@@ -0,0 +1,218 @@
+// Main and side pots, and splitting them between the winners
+
+use super::{Event, Seat};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pot {
+    pub amount: u64,
+    // Seats still in the hand that can win this pot
+    pub eligible: Vec<usize>,
+}
+
+// Main pot first, followed by the side pots created by all-in players
+pub fn pots(seats: &[Seat]) -> Vec<Pot> {
+    let mut levels: Vec<u64> = seats
+        .iter()
+        .filter(|seat| seat.in_hand())
+        .map(|seat| seat.committed)
+        .collect();
+    levels.sort();
+    levels.dedup();
+
+    let mut pots: Vec<Pot> = Vec::new();
+    let mut previous: u64 = 0;
+    for &level in &levels {
+        let amount = seats
+            .iter()
+            .map(|seat| seat.committed.min(level) - seat.committed.min(previous))
+            .sum();
+        let eligible = seats
+            .iter()
+            .enumerate()
+            .filter(|(_, seat)| seat.in_hand() && seat.committed >= level)
+            .map(|(i, _)| i)
+            .collect();
+        if amount > 0 {
+            pots.push(Pot { amount, eligible });
+        }
+        previous = level;
+    }
+
+    // Folded players' chips above every live player's commitment
+    let dead: u64 = seats
+        .iter()
+        .map(|seat| seat.committed - seat.committed.min(previous))
+        .sum();
+    if let Some(last) = pots.last_mut() {
+        last.amount += dead;
+    }
+    pots
+}
+
+// Split every pot between the best `high` scores, or half to the best
+// `high` and half to the best `low` when any eligible seat has a low.
+// `low` is the second half of any split-pot game, not necessarily a low hand.
+// Odd chips go to the high half, then to the first winners in `order`.
+pub fn award(
+    pots: &[Pot],
+    high: &[Option<u128>],
+    low: &[Option<u128>],
+    order: &[usize],
+) -> Vec<Event> {
+    let mut awards: Vec<Event> = Vec::new();
+    for pot in pots {
+        let has_low = pot.eligible.iter().any(|&i| low[i].is_some());
+        let low_amount = if has_low { pot.amount / 2 } else { 0 };
+        split(
+            pot.amount - low_amount,
+            &pot.eligible,
+            high,
+            order,
+            false,
+            &mut awards,
+        );
+        if has_low {
+            split(low_amount, &pot.eligible, low, order, true, &mut awards);
+        }
+    }
+    awards
+}
+
+fn split(
+    amount: u64,
+    eligible: &[usize],
+    scores: &[Option<u128>],
+    order: &[usize],
+    low: bool,
+    awards: &mut Vec<Event>,
+) {
+    let best = match eligible.iter().filter_map(|&i| scores[i]).max() {
+        Some(best) => best,
+        None => return,
+    };
+    let winners: Vec<usize> = order
+        .iter()
+        .copied()
+        .filter(|i| eligible.contains(i) && scores[*i] == Some(best))
+        .collect();
+    if winners.is_empty() {
+        return;
+    }
+
+    let share = amount / winners.len() as u64;
+    let odd_chips = amount % winners.len() as u64;
+    for (n, &seat) in winners.iter().enumerate() {
+        let amount = share + u64::from((n as u64) < odd_chips);
+        awards.push(Event::Award { seat, amount, low });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seats(committed: &[u64], folded: &[bool]) -> Vec<Seat> {
+        committed
+            .iter()
+            .zip(folded)
+            .map(|(&committed, &folded)| Seat {
+                committed,
+                folded,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_single_pot() {
+        let seats = seats(&[10, 10, 4], &[false, false, true]);
+        assert_eq!(
+            pots(&seats),
+            vec![Pot {
+                amount: 24,
+                eligible: vec![0, 1]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_side_pots() {
+        let seats = seats(&[50, 100, 100, 80], &[false, false, false, true]);
+        assert_eq!(
+            pots(&seats),
+            vec![
+                Pot {
+                    amount: 200,
+                    eligible: vec![0, 1, 2]
+                },
+                Pot {
+                    amount: 130,
+                    eligible: vec![1, 2]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_award_with_odd_chips() {
+        let pots = vec![Pot {
+            amount: 25,
+            eligible: vec![0, 1, 2],
+        }];
+        let high = [Some(5), Some(5), Some(3)];
+        let awards = award(&pots, &high, &[None; 3], &[1, 2, 0]);
+        assert_eq!(
+            awards,
+            vec![
+                Event::Award {
+                    seat: 1,
+                    amount: 13,
+                    low: false
+                },
+                Event::Award {
+                    seat: 0,
+                    amount: 12,
+                    low: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_award_hi_lo() {
+        let pots = vec![
+            Pot {
+                amount: 31,
+                eligible: vec![0, 1, 2],
+            },
+            Pot {
+                amount: 10,
+                eligible: vec![1, 2],
+            },
+        ];
+        let high = [Some(9), Some(5), Some(3)];
+        let low = [Some(7), None, None];
+        let awards = award(&pots, &high, &low, &[0, 1, 2]);
+        assert_eq!(
+            awards,
+            vec![
+                Event::Award {
+                    seat: 0,
+                    amount: 16,
+                    low: false
+                },
+                Event::Award {
+                    seat: 0,
+                    amount: 15,
+                    low: true
+                },
+                // No low among the side pot's players, high scoops
+                Event::Award {
+                    seat: 1,
+                    amount: 10,
+                    low: false
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,608 @@
+// Players choosing actions from what they can see of a hand, with reference
+// bots and a runner for bot-vs-bot simulations
+
+use super::betting::BettingOptions;
+use super::table::{default_limit, default_stakes, showdown_scores, GameHand};
+use super::{Action, Gameplay, Limit, Seat, Street, Variant};
+use crate::card::{Card, Rank};
+use crate::deck::Deck;
+use crate::error::Error;
+use crate::evaluation::badugi::Badugi;
+use crate::evaluation::eight_or_better::EightOrBetter;
+use crate::evaluation::high::{Category, High};
+use crate::evaluation::lowball_2to7::Lowball2to7;
+use crate::evaluation::lowball_ato5::LowballAto5;
+use crate::evaluation::strength::HandSpace;
+use crate::evaluation::EvalHand;
+use crate::hand::Hand;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+// Boards or cards still to come are sampled this many times for the strength
+// of an unfinished hand
+const SAMPLES: usize = 16;
+
+// What one player can see of a hand: their own down cards, the up cards of
+// everyone and the board
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameView {
+    pub variant: Variant,
+    pub limit: Limit,
+    pub seat: usize,
+    pub street: Street,
+    pub pot: u64,
+    // Every seat, with the down cards of the other players removed
+    pub seats: Vec<Seat>,
+    pub board: Hand,
+    // None while drawing
+    pub options: Option<BettingOptions>,
+    pub drawing: bool,
+    pub draws_remaining: u8,
+}
+
+impl GameView {
+    pub fn new(hand: &GameHand, seat: usize) -> GameView {
+        let mut seats = hand.seats().to_vec();
+        for (i, other) in seats.iter_mut().enumerate() {
+            if i != seat {
+                other.hole = Hand::new();
+            }
+        }
+        GameView {
+            variant: hand.variant(),
+            limit: hand.limit(),
+            seat,
+            street: hand.street(),
+            pot: hand.pot(),
+            seats,
+            board: hand.board().clone(),
+            options: hand.options(),
+            drawing: hand.is_drawing(),
+            draws_remaining: hand.draws_remaining(),
+        }
+    }
+
+    // The player's own cards, down cards first then up cards
+    pub fn cards(&self) -> Hand {
+        let seat = &self.seats[self.seat];
+        Hand::from([&seat.hole.cards[..], &seat.up.cards[..]].concat())
+    }
+
+    // Cards the player has not seen, which include every card still to come
+    pub fn unseen(&self) -> Vec<Card> {
+        let seen: Vec<Card> = self
+            .seats
+            .iter()
+            .flat_map(|seat| seat.hole.cards.iter().chain(&seat.up.cards))
+            .chain(&self.board.cards)
+            .copied()
+            .collect();
+        Deck::new()
+            .cards
+            .into_iter()
+            .filter(|card| !seen.contains(card))
+            .collect()
+    }
+}
+
+pub trait Strategy {
+    // A legal action for the player of `view`, who is to act. While drawing
+    // the action is `Action::Discard`, empty to stand pat.
+    fn act(&mut self, view: &GameView) -> Action;
+}
+
+// Picks uniformly among the legal actions, bets of any legal size and any
+// discards
+#[derive(Debug, Clone)]
+pub struct RandomBot {
+    rng: StdRng,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> RandomBot {
+        RandomBot {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Strategy for RandomBot {
+    fn act(&mut self, view: &GameView) -> Action {
+        let options = match view.options {
+            Some(options) if !view.drawing => options,
+            _ => {
+                let cards = view.seats[view.seat].hole.cards.clone();
+                let discards = cards.into_iter().filter(|_| self.rng.gen()).collect();
+                return Action::Discard(discards);
+            }
+        };
+        let mut actions: Vec<Action> = Vec::new();
+        if options.bring_in.is_some() {
+            actions.push(Action::BringIn);
+        } else if options.can_check() {
+            actions.push(Action::Check);
+        } else {
+            actions.push(Action::Fold);
+            actions.push(Action::Call);
+        }
+        if let (Some(min), Some(max)) = (options.min_raise_to, options.max_raise_to) {
+            // Fixed limit allows only the two sizes, e.g. on fourth street in stud
+            let to = match view.limit {
+                Limit::FixedLimit if self.rng.gen() => max,
+                Limit::FixedLimit => min,
+                _ => self.rng.gen_range(min..=max),
+            };
+            actions.push(options.bet_or_raise(to));
+        }
+        actions.swap_remove(self.rng.gen_range(0..actions.len()))
+    }
+}
+
+// Never folds and never raises: brings in, checks or calls, and stands pat
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CallingStation;
+
+impl Strategy for CallingStation {
+    fn act(&mut self, view: &GameView) -> Action {
+        match view.options {
+            _ if view.drawing => Action::Discard(Vec::new()),
+            Some(options) if options.bring_in.is_some() => Action::BringIn,
+            Some(options) if options.can_check() => Action::Check,
+            _ => Action::Call,
+        }
+    }
+}
+
+// Bets and raises the minimum with a hand strength of at least `raise`, calls
+// with at least `call` and otherwise checks or folds. Strength is a
+// percentile from 0 to 100, see `estimate_strength`. Draws follow `discards`.
+#[derive(Debug, Clone)]
+pub struct ThresholdBot {
+    call: f64,
+    raise: f64,
+    rng: StdRng,
+}
+
+impl ThresholdBot {
+    pub fn new(call: f64, raise: f64, seed: u64) -> ThresholdBot {
+        ThresholdBot {
+            call,
+            raise,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Strategy for ThresholdBot {
+    fn act(&mut self, view: &GameView) -> Action {
+        let options = match view.options {
+            Some(options) if !view.drawing => options,
+            _ => return Action::Discard(discards(view.variant, &view.seats[view.seat].hole)),
+        };
+        // Plays on as a calling station if the hand cannot be rated
+        let strength = estimate_strength(view, SAMPLES, &mut self.rng).unwrap_or(self.call);
+        match options.min_raise_to {
+            Some(min) if strength >= self.raise => return options.bet_or_raise(min),
+            _ => {}
+        }
+        match options.bring_in {
+            Some(_) => Action::BringIn,
+            None if options.can_check() => Action::Check,
+            None if strength >= self.call => Action::Call,
+            None => Action::Fold,
+        }
+    }
+}
+
+// Percentile of the player's hand at showdown among all hands of its
+// evaluator, e.g. 5-card high hands, averaged over `samples` random boards
+// or cards to come when the hand is not complete yet. In split pot games it
+// is the better of the two halves.
+pub fn estimate_strength<R: Rng + ?Sized>(
+    view: &GameView,
+    samples: usize,
+    rng: &mut R,
+) -> Result<f64, Error> {
+    let cards = view.cards();
+    let (to_player, to_board) = match view.variant.gameplay() {
+        Gameplay::Community => (0, 5usize.saturating_sub(view.board.cards.len())),
+        Gameplay::Stud => (
+            7usize.saturating_sub(cards.cards.len() + view.board.cards.len()),
+            0,
+        ),
+        Gameplay::Draw => (0, 0),
+    };
+    if to_player + to_board == 0 {
+        return percentile(view.variant, &cards, &view.board);
+    }
+
+    let mut unseen = view.unseen();
+    let mut total = 0.0;
+    for _ in 0..samples.max(1) {
+        let (dealt, _) = unseen.partial_shuffle(rng, to_player + to_board);
+        let own = Hand::from([&cards.cards[..], &dealt[..to_player]].concat());
+        let board = Hand::from([&view.board.cards[..], &dealt[to_player..]].concat());
+        total += percentile(view.variant, &own, &board)?;
+    }
+    Ok(total / samples.max(1) as f64)
+}
+
+fn percentile(variant: Variant, cards: &Hand, board: &Hand) -> Result<f64, Error> {
+    let (main, second) = showdown_scores(variant, cards, board)?;
+    let main = match variant {
+        Variant::Razz | Variant::AceToFiveTripleDraw => space_percentile::<LowballAto5>(5, main),
+        Variant::DeuceToSevenSingleDraw | Variant::DeuceToSevenTripleDraw => {
+            space_percentile::<Lowball2to7>(5, main)
+        }
+        Variant::Badugi | Variant::Badacey | Variant::Badeucey => {
+            space_percentile::<Badugi>(4, main)
+        }
+        _ => space_percentile::<High>(5, main),
+    }?;
+    let second = match (variant, second) {
+        (_, None) => 0.0,
+        (Variant::Badacey, Some(low)) => space_percentile::<LowballAto5>(5, low)?,
+        (Variant::Badeucey, Some(low)) => space_percentile::<Lowball2to7>(5, low)?,
+        (_, Some(low)) => space_percentile::<EightOrBetter>(5, low)?,
+    };
+    Ok(main.max(second))
+}
+
+fn space_percentile<T: EvalHand + 'static>(size: usize, score: u128) -> Result<f64, Error> {
+    Ok(HandSpace::cached::<T>(size)?.percentile(score))
+}
+
+// Rule of thumb discards for draw games. High hands keep pairs and better
+// and stand pat on straights and flushes. Lowball keeps one card of each
+// rank up to an eight and stands pat on a nine or better, in deuce to seven
+// breaking a straight or flush by its highest card. Badugi games keep the
+// lowest cards of different suits and ranks, and Badacey and Badeucey also
+// the lowest other cards of new ranks up to an eight for the low half.
+pub fn discards(variant: Variant, hole: &Hand) -> Vec<Card> {
+    let cards = &hole.cards;
+    // Ace low except in deuce to seven
+    let ace_low = !matches!(
+        variant,
+        Variant::DeuceToSevenSingleDraw | Variant::DeuceToSevenTripleDraw | Variant::Badeucey
+    );
+    let value = |card: &Card| {
+        if ace_low {
+            (card.rank as u8 + 1) % 13
+        } else {
+            card.rank as u8
+        }
+    };
+    let count = |rank: Rank| cards.iter().filter(|card| card.rank == rank).count();
+    // A nine, counting from 0 for the lowest card
+    let nine = (Rank::Nine as u8) + u8::from(ace_low);
+
+    match variant {
+        Variant::FiveCardDraw => {
+            let flush = cards.iter().all(|card| card.suit == cards[0].suit);
+            let mut ranks: Vec<u8> = cards.iter().map(|card| card.rank as u8).collect();
+            ranks.sort_unstable();
+            ranks.dedup();
+            let straight = ranks.len() == 5
+                && (ranks[4] - ranks[0] == 4 || ranks == [0, 1, 2, 3, Rank::Ace as u8]);
+            if flush || straight {
+                return Vec::new();
+            }
+            let paired = cards.iter().any(|card| count(card.rank) > 1);
+            let highest = cards.iter().map(|card| card.rank).max();
+            cards
+                .iter()
+                .filter(|card| {
+                    if paired {
+                        count(card.rank) == 1
+                    } else {
+                        Some(card.rank) != highest
+                    }
+                })
+                .copied()
+                .collect()
+        }
+        Variant::Badugi | Variant::Badacey | Variant::Badeucey => {
+            let mut sorted = cards.clone();
+            sorted.sort_by_key(|card| value(card));
+            let mut kept: Vec<Card> = Vec::new();
+            for card in &sorted {
+                if kept
+                    .iter()
+                    .all(|other| other.suit != card.suit && other.rank != card.rank)
+                {
+                    kept.push(*card);
+                }
+            }
+            if variant != Variant::Badugi {
+                for card in &sorted {
+                    if kept.len() < cards.len()
+                        && value(card) < nine
+                        && kept.iter().all(|other| other.rank != card.rank)
+                    {
+                        kept.push(*card);
+                    }
+                }
+                // The extra card is the last kept
+                if !ace_low && straight_or_flush(&kept) {
+                    kept.pop();
+                }
+            }
+            cards
+                .iter()
+                .filter(|card| !kept.contains(card))
+                .copied()
+                .collect()
+        }
+        _ => {
+            let mut values: Vec<u8> = cards.iter().map(value).collect();
+            values.sort_unstable();
+            values.dedup();
+            let broken = !ace_low && straight_or_flush(cards);
+            if values.len() == cards.len() && values.last() <= Some(&nine) && !broken {
+                return Vec::new();
+            }
+            let mut kept: Vec<u8> = Vec::new();
+            let mut thrown: Vec<Card> = cards
+                .iter()
+                .filter(|card| {
+                    let value = value(card);
+                    if value < nine && !kept.contains(&value) {
+                        kept.push(value);
+                        false
+                    } else {
+                        true
+                    }
+                })
+                .copied()
+                .collect();
+            if broken && thrown.is_empty() {
+                thrown.extend(cards.iter().max_by_key(|card| value(card)));
+            }
+            thrown
+        }
+    }
+}
+
+// Whether five cards are a straight or a flush, which lose in deuce to seven
+fn straight_or_flush(cards: &[Card]) -> bool {
+    cards.len() == 5
+        && High::score(&Hand::from(cards.to_vec()), false).is_ok_and(|score| {
+            matches!(
+                High::category(score),
+                Category::Straight | Category::Flush | Category::StraightFlush
+            )
+        })
+}
+
+// Plays the hand out, each seat acting through the strategy of the same index
+pub fn play_hand(hand: &mut GameHand, players: &mut [Box<dyn Strategy>]) -> Result<(), Error> {
+    while let Some(seat) = hand.to_act() {
+        let view = GameView::new(hand, seat);
+        let player = players.get_mut(seat).ok_or(Error::InvalidSetup)?;
+        hand.act(player.act(&view))?;
+    }
+    Ok(())
+}
+
+// Plays `hands` hands, one game of `variants` after the other with the
+// default limit and stakes of each, moving the button every hand. Every
+// player starts each hand with `stack` and the decks are shuffled from
+// `seed`. Returns the net winnings of each player.
+pub fn simulate(
+    variants: &[Variant],
+    players: &mut [Box<dyn Strategy>],
+    stack: u64,
+    hands: usize,
+    seed: u64,
+) -> Result<Vec<i64>, Error> {
+    if variants.is_empty() || players.is_empty() {
+        return Err(Error::InvalidSetup);
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let stacks = vec![stack; players.len()];
+    let mut results = vec![0i64; players.len()];
+    for i in 0..hands {
+        let variant = variants[i % variants.len()];
+        let mut hand = GameHand::new(
+            variant,
+            default_limit(variant),
+            default_stakes(variant),
+            i % players.len(),
+            &stacks,
+            &mut rng,
+        )?;
+        play_hand(&mut hand, players)?;
+        for (result, seat) in results.iter_mut().zip(hand.seats()) {
+            *result += seat.stack as i64 - stack as i64;
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::hand;
+
+    fn cards(s: &str) -> Vec<Card> {
+        hand(s).cards
+    }
+
+    // Badugi view for seat 0 facing a bet of 2
+    fn facing_bet(hole: &str) -> GameView {
+        let mut seats = vec![Seat::new(100), Seat::new(100)];
+        seats[0].hole = hand(hole);
+        GameView {
+            variant: Variant::Badugi,
+            limit: Limit::FixedLimit,
+            seat: 0,
+            street: Street::ThirdDraw,
+            pot: 10,
+            seats,
+            board: Hand::new(),
+            options: Some(BettingOptions {
+                seat: 0,
+                current_bet: 4,
+                to_call: 4,
+                min_raise_to: Some(8),
+                max_raise_to: Some(8),
+                bring_in: None,
+                completes: false,
+            }),
+            drawing: false,
+            draws_remaining: 0,
+        }
+    }
+
+    #[test]
+    fn test_view_hides_down_cards() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let hand = GameHand::new(
+            Variant::SevenCardStud,
+            default_limit(Variant::SevenCardStud),
+            default_stakes(Variant::SevenCardStud),
+            0,
+            &[100, 100, 100],
+            &mut rng,
+        )
+        .unwrap();
+        let view = GameView::new(&hand, 1);
+        assert_eq!(view.seats[1].hole, hand.seats()[1].hole);
+        assert!(view.seats[0].hole.cards.is_empty());
+        assert_eq!(view.seats[2].up, hand.seats()[2].up);
+        assert_eq!(view.cards().cards.len(), 3);
+        // 52 less the own three cards and the two other up cards
+        assert_eq!(view.unseen().len(), 47);
+    }
+
+    #[test]
+    fn test_threshold_bot() {
+        let mut bot = ThresholdBot::new(50.0, 90.0, 1);
+        assert_eq!(bot.act(&facing_bet("As 2h 3d 4c")), Action::Raise(8));
+        assert_eq!(bot.act(&facing_bet("Ks Kh Kd Kc")), Action::Fold);
+        let strength = estimate_strength(
+            &facing_bet("As 2h 3d 4c"),
+            SAMPLES,
+            &mut StdRng::seed_from_u64(1),
+        )
+        .unwrap();
+        assert_eq!(strength, 100.0);
+
+        let mut view = facing_bet("As 2h 3d 4c");
+        view.drawing = true;
+        view.options = None;
+        assert_eq!(bot.act(&view), Action::Discard(Vec::new()));
+    }
+
+    #[test]
+    fn test_estimate_strength_samples_board() {
+        let mut seats = vec![Seat::new(100), Seat::new(100)];
+        seats[0].hole = hand("Ah As");
+        let view = GameView {
+            variant: Variant::Holdem,
+            limit: Limit::NoLimit,
+            seat: 0,
+            street: Street::Preflop,
+            pot: 3,
+            seats,
+            board: Hand::new(),
+            options: None,
+            drawing: false,
+            draws_remaining: 0,
+        };
+        let aces = estimate_strength(&view, 64, &mut StdRng::seed_from_u64(3)).unwrap();
+        let mut trash = view.clone();
+        trash.seats[0].hole = hand("7d 2c");
+        let seven_deuce = estimate_strength(&trash, 64, &mut StdRng::seed_from_u64(3)).unwrap();
+        assert!(aces > seven_deuce);
+        assert!(aces > 90.0);
+    }
+
+    #[test]
+    fn test_discards() {
+        let discards = |variant: Variant, s: &str| discards(variant, &hand(s));
+        assert_eq!(
+            discards(Variant::FiveCardDraw, "Kc Kd 7h 4s 2c"),
+            cards("7h 4s 2c")
+        );
+        assert_eq!(
+            discards(Variant::FiveCardDraw, "Kc 9d 7h 4s 2c"),
+            cards("9d 7h 4s 2c")
+        );
+        assert!(discards(Variant::FiveCardDraw, "Ac 2d 3h 4s 5c").is_empty());
+        assert_eq!(
+            discards(Variant::AceToFiveTripleDraw, "Ac 2d 2h Ks 5c"),
+            cards("2h Ks")
+        );
+        assert!(discards(Variant::AceToFiveTripleDraw, "9c 2d 3h 4s 5c").is_empty());
+        // Aces are high in deuce to seven
+        assert_eq!(
+            discards(Variant::DeuceToSevenTripleDraw, "Ac 2d 3h 7s 8c"),
+            cards("Ac")
+        );
+        assert_eq!(discards(Variant::Badugi, "As 2s 3d 4c"), cards("2s"));
+        assert!(discards(Variant::Badugi, "Ks Qh Jd Tc").is_empty());
+        // Straights and flushes lose in deuce to seven
+        assert_eq!(
+            discards(Variant::DeuceToSevenTripleDraw, "3d 4h 5s 6c 7d"),
+            cards("7d")
+        );
+        assert_eq!(
+            discards(Variant::DeuceToSevenSingleDraw, "2c 3c 4c 5c 7c"),
+            cards("7c")
+        );
+        assert!(discards(Variant::DeuceToSevenTripleDraw, "2c 3d 4h 5s 7c").is_empty());
+        // A badugi and a wheel is pat in Badacey
+        assert!(discards(Variant::Badacey, "As 2h 3d 4c 5c").is_empty());
+        assert_eq!(discards(Variant::Badacey, "As 2h 3d 4c Kc"), cards("Kc"));
+        // The 6c would make a straight for the 2-7 half
+        assert_eq!(discards(Variant::Badeucey, "2s 3h 4d 5c 6c"), cards("6c"));
+        assert!(discards(Variant::Badeucey, "2s 3h 4d 5c 7c").is_empty());
+    }
+
+    #[test]
+    fn test_bots_play_every_variant() {
+        let mut players: Vec<Box<dyn Strategy>> = vec![
+            Box::new(RandomBot::new(1)),
+            Box::new(CallingStation),
+            Box::new(ThresholdBot::new(50.0, 90.0, 2)),
+            Box::new(RandomBot::new(3)),
+        ];
+        let results = simulate(&Variant::ALL, &mut players, 100, 150, 42).unwrap();
+        assert_eq!(results.iter().sum::<i64>(), 0);
+    }
+
+    #[test]
+    fn test_simulate_thousands_of_hands() {
+        let run = || {
+            let mut players: Vec<Box<dyn Strategy>> = vec![
+                Box::new(RandomBot::new(1)),
+                Box::new(CallingStation),
+                Box::new(RandomBot::new(2)),
+            ];
+            simulate(
+                &[Variant::Holdem, Variant::Razz, Variant::Badugi],
+                &mut players,
+                200,
+                3000,
+                7,
+            )
+            .unwrap()
+        };
+        let results = run();
+        assert_eq!(results.iter().sum::<i64>(), 0);
+        // Same seeds, same results
+        assert_eq!(results, run());
+        assert_eq!(
+            simulate(
+                &[],
+                &mut [Box::new(CallingStation) as Box<dyn Strategy>],
+                100,
+                1,
+                0
+            ),
+            Err(Error::InvalidSetup)
+        );
+    }
+}
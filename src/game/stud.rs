@@ -0,0 +1,587 @@
+// Seven card stud hand engine for Stud, Stud Hi-Lo and Razz, from the antes
+// to the showdown
+
+use super::betting::{BettingOptions, BettingRound};
+use super::{
+    order_from, post_forced_bets, pot, return_uncalled, Action, Event, Gameplay, Limit, Seat,
+    Stakes, Street, Variant,
+};
+use crate::card::{Card, Rank};
+use crate::deck::Deck;
+use crate::error::Error;
+use crate::evaluation::eight_or_better::EightOrBetter;
+use crate::evaluation::high::High;
+use crate::evaluation::lowball_ato5::LowballAto5;
+use crate::evaluation::Evaluation;
+use crate::hand::Hand;
+
+pub const MAX_PLAYERS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StudConfig {
+    pub variant: Variant,
+    pub stakes: Stakes,
+    // Dealer position, the deal and ties start to its left
+    pub button: usize,
+}
+
+#[derive(Debug)]
+pub struct StudHand {
+    config: StudConfig,
+    seats: Vec<Seat>,
+    deck: Deck,
+    burned: Vec<Card>,
+    // Community card dealt when the deck runs out on seventh street
+    board: Hand,
+    street: Street,
+    round: BettingRound,
+    // Seat that has to bring in or complete before anyone else acts
+    bring_in: Option<usize>,
+    events: Vec<Event>,
+    unread: usize,
+    finished: bool,
+}
+
+impl StudHand {
+    // Seats every stack (0 to sit out), posts the antes and deals third street from `deck`
+    pub fn new(config: StudConfig, stacks: &[u64], deck: Deck) -> Result<StudHand, Error> {
+        let seats: Vec<Seat> = stacks.iter().map(|&stack| Seat::new(stack)).collect();
+        let active = seats.iter().filter(|seat| !seat.sitting_out).count();
+        let stakes = config.stakes;
+        if !(2..=MAX_PLAYERS).contains(&active)
+            || config.variant.gameplay() != Gameplay::Stud
+            || config.button >= seats.len()
+            || stakes.small_bet == 0
+            || stakes.big_bet < stakes.small_bet
+            || stakes.bring_in == 0
+            || stakes.bring_in > stakes.small_bet
+        {
+            return Err(Error::InvalidSetup);
+        }
+
+        let round = BettingRound::new(Limit::FixedLimit, stakes.small_bet, &seats, 0);
+        let mut hand = StudHand {
+            config,
+            seats,
+            deck,
+            burned: Vec::new(),
+            board: Hand::new(),
+            street: Street::Third,
+            round,
+            bring_in: None,
+            events: Vec::new(),
+            unread: 0,
+            finished: false,
+        };
+        let antes = post_forced_bets(&mut hand.seats, config.button, &stakes, false);
+        hand.events.extend(antes);
+        hand.start_street(Street::Third)?;
+        hand.advance()?;
+        Ok(hand)
+    }
+
+    pub fn config(&self) -> &StudConfig {
+        &self.config
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        &self.seats
+    }
+
+    pub fn board(&self) -> &Hand {
+        &self.board
+    }
+
+    pub fn street(&self) -> Street {
+        self.street
+    }
+
+    pub fn pot(&self) -> u64 {
+        self.seats.iter().map(|seat| seat.committed).sum()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn to_act(&self) -> Option<usize> {
+        if self.finished {
+            return None;
+        }
+        self.round.to_act()
+    }
+
+    pub fn options(&self) -> Option<BettingOptions> {
+        if self.finished {
+            return None;
+        }
+        if let Some(seat) = self.bring_in {
+            let stakes = self.config.stakes;
+            let all_in_to = self.seats[seat].stack;
+            return Some(BettingOptions {
+                seat,
+                current_bet: 0,
+                to_call: 0,
+                min_raise_to: Some(stakes.small_bet.min(all_in_to)),
+                max_raise_to: Some(stakes.small_bet.min(all_in_to)),
+                bring_in: Some(stakes.bring_in.min(all_in_to)),
+                completes: true,
+            });
+        }
+        self.round.options(&self.seats, self.pot())
+    }
+
+    // Every event since the hand started
+    pub fn history(&self) -> &[Event] {
+        &self.events
+    }
+
+    // Events since the previous call
+    pub fn take_events(&mut self) -> Vec<Event> {
+        let events = self.events[self.unread..].to_vec();
+        self.unread = self.events.len();
+        events
+    }
+
+    // Act for the player returned by `to_act`
+    pub fn act(&mut self, action: Action) -> Result<(), Error> {
+        let seat = self.to_act().ok_or(Error::InvalidAction)?;
+        if self.bring_in.is_some() {
+            let stakes = self.config.stakes;
+            match action {
+                Action::BringIn => self.round.force_bet(&mut self.seats, stakes.bring_in)?,
+                Action::Complete => {
+                    let pot = self.pot();
+                    let to = stakes.small_bet.min(self.seats[seat].stack);
+                    self.round.act(&mut self.seats, &Action::Bet(to), pot)?
+                }
+                _ => return Err(Error::InvalidAction),
+            }
+            self.bring_in = None;
+        } else {
+            let pot = self.pot();
+            self.round.act(&mut self.seats, &action, pot)?;
+        }
+        self.events.push(Event::Action { seat, action });
+        self.advance()
+    }
+
+    fn order(&self) -> Vec<usize> {
+        order_from(&self.seats, self.config.button)
+    }
+
+    fn is_razz(&self) -> bool {
+        self.config.variant == Variant::Razz
+    }
+
+    fn unit(&self) -> u64 {
+        match self.street {
+            Street::Third | Street::Fourth => self.config.stakes.small_bet,
+            _ => self.config.stakes.big_bet,
+        }
+    }
+
+    fn deal_card(&mut self, seat: usize, face_up: bool) -> Result<(), Error> {
+        let card = self.deck.deal_card().ok_or(Error::NotEnoughCards)?;
+        if face_up {
+            self.seats[seat].up.cards.push(card);
+        } else {
+            self.seats[seat].hole.cards.push(card);
+        }
+        Ok(())
+    }
+
+    fn start_street(&mut self, street: Street) -> Result<(), Error> {
+        self.street = street;
+        self.events.push(Event::Street(street));
+        let order = self.order();
+
+        if street == Street::Third {
+            for face_up in [false, false, true] {
+                for &seat in &order {
+                    self.deal_card(seat, face_up)?;
+                }
+            }
+            for &seat in &order {
+                let down = self.seats[seat].hole.clone();
+                let up = self.seats[seat].up.clone();
+                self.events.push(Event::Dealt {
+                    seat,
+                    cards: down,
+                    face_up: false,
+                });
+                self.events.push(Event::Dealt {
+                    seat,
+                    cards: up,
+                    face_up: true,
+                });
+            }
+        } else if self.deck.cards.len() < order.len() {
+            // Not enough cards left for everyone, a single community card
+            // is shared by all, falling back on the burn cards
+            let card = self
+                .deck
+                .deal_card()
+                .or_else(|| self.burned.pop())
+                .ok_or(Error::NotEnoughCards)?;
+            self.board.cards.push(card);
+            self.events.push(Event::Board(Hand::from(vec![card])));
+        } else {
+            if self.deck.cards.len() > order.len() {
+                let burn = self.deck.deal_card().ok_or(Error::NotEnoughCards)?;
+                self.burned.push(burn);
+            }
+            let face_up = street != Street::Seventh;
+            for &seat in &order {
+                self.deal_card(seat, face_up)?;
+                let cards = if face_up {
+                    &self.seats[seat].up
+                } else {
+                    &self.seats[seat].hole
+                };
+                let card = *cards.cards.last().ok_or(Error::NotEnoughCards)?;
+                self.events.push(Event::Dealt {
+                    seat,
+                    cards: Hand::from(vec![card]),
+                    face_up,
+                });
+            }
+        }
+
+        let first = if street == Street::Third {
+            self.bring_in_seat()
+        } else {
+            self.best_showing_seat()
+        };
+        self.round = BettingRound::new(Limit::FixedLimit, self.unit(), &self.seats, first);
+        if street == Street::Third && self.round.to_act() == Some(first) {
+            self.bring_in = Some(first);
+        }
+        let open_pair = self.order().iter().any(|&seat| {
+            let up = &self.seats[seat].up.cards;
+            up.len() == 2 && up[0].rank == up[1].rank
+        });
+        if street == Street::Fourth && open_pair && self.config.variant == Variant::SevenCardStud {
+            self.round.allow_double_bet(self.config.stakes.big_bet);
+        }
+        Ok(())
+    }
+
+    // Lowest up card brings in, clubs being the lowest suit. In Razz it is
+    // the highest up card, aces being low and spades the highest suit.
+    fn bring_in_seat(&self) -> usize {
+        let order = self.order();
+        let key = |seat: &&usize| {
+            let card = self.seats[**seat].up.cards[0];
+            (rank_value(card.rank, self.is_razz()), 3 - card.suit as u8)
+        };
+        let seat = if self.is_razz() {
+            order.iter().rev().max_by_key(key)
+        } else {
+            order.iter().min_by_key(key)
+        };
+        seat.copied().unwrap_or(self.config.button)
+    }
+
+    // Best showing hand acts first, ties go to the first seat left of the dealer
+    fn best_showing_seat(&self) -> usize {
+        let order = self.order();
+        let razz = self.is_razz();
+        let key = |seat: &&usize| showing_key(&self.seats[**seat].up.cards, razz);
+        let seat = if razz {
+            order.iter().min_by_key(key)
+        } else {
+            order.iter().rev().max_by_key(key)
+        };
+        seat.copied().unwrap_or(self.config.button)
+    }
+
+    // Move on until a player has to act or the hand is over
+    fn advance(&mut self) -> Result<(), Error> {
+        while !self.finished && self.to_act().is_none() {
+            if let Some(event) = return_uncalled(&mut self.seats) {
+                self.events.push(event);
+            }
+            for seat in self.seats.iter_mut() {
+                seat.bet = 0;
+            }
+
+            if self.order().len() == 1 {
+                self.finish()?;
+                continue;
+            }
+            match self.street {
+                Street::Third => self.start_street(Street::Fourth)?,
+                Street::Fourth => self.start_street(Street::Fifth)?,
+                Street::Fifth => self.start_street(Street::Sixth)?,
+                Street::Sixth => self.start_street(Street::Seventh)?,
+                _ => self.finish()?,
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        let order = self.order();
+        let mut high: Vec<Option<u128>> = vec![None; self.seats.len()];
+        let mut low: Vec<Option<u128>> = vec![None; self.seats.len()];
+
+        if order.len() == 1 {
+            high[order[0]] = Some(0);
+        } else {
+            for &seat in &order {
+                let cards = self.cards_of(seat);
+                let (seat_high, seat_low) = showdown_scores(self.config.variant, &cards)?;
+                high[seat] = Some(seat_high);
+                low[seat] = seat_low;
+                self.events.push(Event::Showdown {
+                    seat,
+                    cards: self.seats[seat].hole.clone(),
+                });
+            }
+        }
+
+        let pots = pot::pots(&self.seats);
+        for event in pot::award(&pots, &high, &low, &order) {
+            if let Event::Award { seat, amount, .. } = event {
+                self.seats[seat].stack += amount;
+            }
+            self.events.push(event);
+        }
+        self.finished = true;
+        Ok(())
+    }
+
+    // Every card a player can use, down cards first, then up and community cards
+    pub fn cards_of(&self, seat: usize) -> Vec<Card> {
+        let seat = &self.seats[seat];
+        [
+            &seat.hole.cards[..],
+            &seat.up.cards[..],
+            &self.board.cards[..],
+        ]
+        .concat()
+    }
+}
+
+// Main score of the best five cards, and the low half's in hi-lo.
+// In Razz the main score is the A-5 low.
+pub fn showdown_scores(variant: Variant, cards: &[Card]) -> Result<(u128, Option<u128>), Error> {
+    match variant {
+        Variant::Razz => Ok((Evaluation::<LowballAto5>::best_of(cards, 5)?.score, None)),
+        Variant::StudHiLo => {
+            let high = Evaluation::<High>::best_of(cards, 5)?.score;
+            let low = Evaluation::<EightOrBetter>::best_of(cards, 5)?.score;
+            Ok((high, Some(low).filter(|&score| score > 0)))
+        }
+        _ => Ok((Evaluation::<High>::best_of(cards, 5)?.score, None)),
+    }
+}
+
+fn rank_value(rank: Rank, ace_low: bool) -> u8 {
+    if ace_low {
+        (rank as u8 + 1) % 13
+    } else {
+        rank as u8
+    }
+}
+
+// Ranking of up to four up cards: quads, trips, two pairs, one pair, then
+// high cards. Larger is better for high, smaller is better for Razz.
+fn showing_key(cards: &[Card], ace_low: bool) -> u64 {
+    let mut frequencies: [u8; 13] = [0; 13];
+    for card in cards {
+        frequencies[rank_value(card.rank, ace_low) as usize] += 1;
+    }
+
+    let mut groups: Vec<(u8, u8)> = frequencies
+        .iter()
+        .enumerate()
+        .filter(|(_, &count)| count > 0)
+        .map(|(value, &count)| (count, value as u8))
+        .collect();
+    groups.sort_by(|a, b| b.cmp(a));
+
+    let pairs = groups.iter().filter(|(count, _)| *count == 2).count() as u64;
+    let category = match groups.first().map_or(0, |(count, _)| *count) {
+        4 => 4,
+        3 => 3,
+        _ => pairs,
+    };
+    groups.iter().fold(category, |key, &(count, value)| {
+        key * 256 + (count as u64) * 16 + value as u64
+    }) << (8 * (4 - groups.len() as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::stacked_deck;
+
+    fn config(variant: Variant) -> StudConfig {
+        StudConfig {
+            variant,
+            stakes: Stakes::stud(1, 2, 5, 10),
+            button: 0,
+        }
+    }
+
+    fn key(s: &str, ace_low: bool) -> u64 {
+        showing_key(&Hand::try_from(s).unwrap().cards, ace_low)
+    }
+
+    #[test]
+    fn test_showing_key() {
+        assert!(key("Ah", false) > key("Kh", false));
+        assert!(key("2h 2c", false) > key("Ah Kc", false));
+        assert!(key("3h 3c 2d 2s", false) > key("Ah Ac Kd Qs", false));
+        assert!(key("Ah Ac Kd Qs", false) > key("Ah Ac Kd Js", false));
+        assert!(key("Ah Ac Ad", false) > key("Kh Kc Ad Ac", false));
+
+        // Razz, smaller is better
+        assert!(key("Ah", true) < key("2h", true));
+        assert!(key("Ah 2c", true) < key("Ah 3c", true));
+        assert!(key("Kh Qc", true) < key("2h 2c", true));
+    }
+
+    #[test]
+    fn test_invalid_setup() {
+        assert_eq!(
+            StudHand::new(config(Variant::Holdem), &[100, 100], Deck::new()).unwrap_err(),
+            Error::InvalidSetup
+        );
+        assert_eq!(
+            StudHand::new(config(Variant::SevenCardStud), &[100; 9], Deck::new()).unwrap_err(),
+            Error::InvalidSetup
+        );
+    }
+
+    #[test]
+    fn test_third_street_bring_in() {
+        // Dealing starts with seat 1: down, down, then up cards 9c, 2d, Kh
+        let deck = stacked_deck("Ah As Ad 8h 8s 8d 9c 2d Kh");
+        let mut hand =
+            StudHand::new(config(Variant::SevenCardStud), &[100, 100, 100], deck).unwrap();
+        assert_eq!(hand.pot(), 3);
+        assert_eq!(hand.seats()[2].up.to_string(), "2d");
+        assert_eq!(hand.seats()[2].hole.to_string(), "As 8s");
+
+        // Lowest up card brings in
+        assert_eq!(hand.to_act(), Some(2));
+        let options = hand.options().unwrap();
+        assert_eq!(options.bring_in, Some(2));
+        assert!(!options.can_check());
+        assert_eq!(hand.act(Action::Check), Err(Error::InvalidAction));
+        hand.act(Action::BringIn).unwrap();
+        assert_eq!(hand.to_act(), Some(0));
+        hand.act(Action::Complete).unwrap();
+        hand.act(Action::Call).unwrap();
+        hand.act(Action::Call).unwrap();
+
+        // Kings up acts first on fourth street
+        assert_eq!(hand.street(), Street::Fourth);
+        assert_eq!(hand.to_act(), Some(0));
+        assert_eq!(hand.seats()[0].up.cards.len(), 2);
+        assert_eq!(hand.pot(), 18);
+    }
+
+    #[test]
+    fn test_razz_bring_in_is_highest_card() {
+        let deck = stacked_deck("Ah As Ad 8h 8s 8d 9c Kd Ks");
+        let hand = StudHand::new(config(Variant::Razz), &[100, 100, 100], deck).unwrap();
+        // Two kings up, spades is the highest suit
+        assert_eq!(hand.to_act(), Some(0));
+    }
+
+    #[test]
+    fn test_fourth_street_double_bet_on_open_pair() {
+        // Seat 1 pairs its up card on fourth street
+        let deck = stacked_deck("Ah As 3d 4h 9c 2d 5h 9d 6c");
+        let mut hand = StudHand::new(config(Variant::SevenCardStud), &[100, 100], deck).unwrap();
+        hand.act(Action::BringIn).unwrap();
+        hand.act(Action::Call).unwrap();
+        assert_eq!(hand.street(), Street::Fourth);
+        assert_eq!(hand.seats()[1].up.to_string(), "9c 9d");
+        assert_eq!(hand.to_act(), Some(1));
+        let options = hand.options().unwrap();
+        assert_eq!(
+            (options.min_raise_to, options.max_raise_to),
+            (Some(5), Some(10))
+        );
+        hand.act(Action::Bet(10)).unwrap();
+        assert_eq!(hand.options().unwrap().min_raise_to, Some(20));
+
+        let mut config = config(Variant::StudHiLo);
+        config.stakes.ante = 0;
+        let deck = stacked_deck("Ah As 3d 4h 9c 2d 5h 9d 6c");
+        let mut hand = StudHand::new(config, &[100, 100], deck).unwrap();
+        hand.act(Action::BringIn).unwrap();
+        hand.act(Action::Call).unwrap();
+        assert_eq!(hand.options().unwrap().max_raise_to, Some(5));
+    }
+
+    #[test]
+    fn test_check_down_to_showdown() {
+        let deck = stacked_deck("Ah As 3d 4h 9c 2d 5h 9d 6c");
+        let mut hand = StudHand::new(config(Variant::SevenCardStud), &[100, 100], deck).unwrap();
+        hand.act(Action::BringIn).unwrap();
+        hand.act(Action::Call).unwrap();
+        while !hand.is_finished() {
+            hand.act(Action::Check).unwrap();
+        }
+
+        assert_eq!(hand.street(), Street::Seventh);
+        for seat in hand.seats() {
+            assert_eq!(seat.hole.cards.len(), 3);
+            assert_eq!(seat.up.cards.len(), 4);
+        }
+        let stacks: u64 = hand.seats().iter().map(|seat| seat.stack).sum();
+        assert_eq!(stacks, 200);
+        assert_eq!(
+            hand.history()
+                .iter()
+                .filter(|event| matches!(event, Event::Showdown { .. }))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_community_card_when_deck_runs_out() {
+        let mut hand =
+            StudHand::new(config(Variant::SevenCardStud), &[100; 8], Deck::new()).unwrap();
+        while !hand.is_finished() {
+            let options = hand.options().unwrap();
+            let action = if options.bring_in.is_some() {
+                Action::BringIn
+            } else if options.can_check() {
+                Action::Check
+            } else {
+                Action::Call
+            };
+            hand.act(action).unwrap();
+        }
+
+        assert_eq!(hand.board().cards.len(), 1);
+        for seat in 0..8 {
+            assert_eq!(hand.cards_of(seat).len(), 7);
+        }
+        let stacks: u64 = hand.seats().iter().map(|seat| seat.stack).sum();
+        assert_eq!(stacks, 800);
+    }
+
+    #[test]
+    fn test_stud_hi_lo_scores() {
+        let cards = Hand::try_from("Ah 2h 3h 4h 9h Kc Kd").unwrap().cards;
+        let (high, low) = showdown_scores(Variant::StudHiLo, &cards).unwrap();
+        assert_eq!(
+            High::category(high),
+            crate::evaluation::high::Category::Flush
+        );
+        assert!(low.is_none());
+
+        let cards = Hand::try_from("Ah 2h 3h 4h 8c Kc Kd").unwrap().cards;
+        let (_, low) = showdown_scores(Variant::StudHiLo, &cards).unwrap();
+        assert!(low.is_some());
+    }
+}
@@ -0,0 +1,375 @@
+// One hand of any variant behind a single interface, for tables that rotate
+// through games such as dealer's choice
+
+use super::betting::BettingOptions;
+use super::community::{best_high, best_low, CommunityConfig, CommunityHand};
+use super::draw::{DrawConfig, DrawHand};
+use super::stud::{StudConfig, StudHand};
+use super::{Action, Event, Gameplay, Limit, Seat, Stakes, Street, Variant};
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::error::Error;
+use crate::evaluation::badugi::Badugi;
+use crate::evaluation::eight_or_better::EightOrBetter;
+use crate::evaluation::high::High;
+use crate::evaluation::lowball_2to7::Lowball2to7;
+use crate::evaluation::lowball_ato5::LowballAto5;
+use crate::evaluation::EvalHand;
+use crate::hand::Hand;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+static NO_BOARD: Hand = Hand { cards: Vec::new() };
+
+#[derive(Debug)]
+pub enum GameHand {
+    Community(CommunityHand),
+    Stud(StudHand),
+    // Boxed as it is much larger than the others
+    Draw(Box<DrawHand>),
+}
+
+impl GameHand {
+    // Deals from a fresh deck shuffled with `rng`. Stud is always fixed limit.
+    pub fn new<R: Rng + ?Sized>(
+        variant: Variant,
+        limit: Limit,
+        stakes: Stakes,
+        button: usize,
+        stacks: &[u64],
+        rng: &mut R,
+    ) -> Result<GameHand, Error> {
+        let mut deck = Deck::new();
+        deck.shuffle_with_rng(rng);
+        match variant.gameplay() {
+            Gameplay::Community => {
+                let config = CommunityConfig {
+                    variant,
+                    limit,
+                    stakes,
+                    button,
+                    bomb_pot: false,
+                };
+                Ok(GameHand::Community(CommunityHand::new(
+                    config, stacks, deck,
+                )?))
+            }
+            Gameplay::Stud => {
+                let config = StudConfig {
+                    variant,
+                    stakes,
+                    button,
+                };
+                Ok(GameHand::Stud(StudHand::new(config, stacks, deck)?))
+            }
+            Gameplay::Draw => {
+                let config = DrawConfig {
+                    variant,
+                    limit,
+                    stakes,
+                    button,
+                };
+                let reshuffles = StdRng::seed_from_u64(rng.gen());
+                let hand = DrawHand::with_rng(config, stacks, deck, reshuffles)?;
+                Ok(GameHand::Draw(Box::new(hand)))
+            }
+        }
+    }
+
+    pub fn variant(&self) -> Variant {
+        match self {
+            GameHand::Community(hand) => hand.config().variant,
+            GameHand::Stud(hand) => hand.config().variant,
+            GameHand::Draw(hand) => hand.config().variant,
+        }
+    }
+
+    pub fn limit(&self) -> Limit {
+        match self {
+            GameHand::Community(hand) => hand.config().limit,
+            GameHand::Stud(_) => Limit::FixedLimit,
+            GameHand::Draw(hand) => hand.config().limit,
+        }
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        match self {
+            GameHand::Community(hand) => hand.seats(),
+            GameHand::Stud(hand) => hand.seats(),
+            GameHand::Draw(hand) => hand.seats(),
+        }
+    }
+
+    // Community cards, empty in draw games
+    pub fn board(&self) -> &Hand {
+        match self {
+            GameHand::Community(hand) => hand.board(),
+            GameHand::Stud(hand) => hand.board(),
+            GameHand::Draw(_) => &NO_BOARD,
+        }
+    }
+
+    pub fn street(&self) -> Street {
+        match self {
+            GameHand::Community(hand) => hand.street(),
+            GameHand::Stud(hand) => hand.street(),
+            GameHand::Draw(hand) => hand.street(),
+        }
+    }
+
+    pub fn pot(&self) -> u64 {
+        match self {
+            GameHand::Community(hand) => hand.pot(),
+            GameHand::Stud(hand) => hand.pot(),
+            GameHand::Draw(hand) => hand.pot(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        match self {
+            GameHand::Community(hand) => hand.is_finished(),
+            GameHand::Stud(hand) => hand.is_finished(),
+            GameHand::Draw(hand) => hand.is_finished(),
+        }
+    }
+
+    // Whether the player to act has to discard rather than bet
+    pub fn is_drawing(&self) -> bool {
+        match self {
+            GameHand::Draw(hand) => hand.is_drawing(),
+            _ => false,
+        }
+    }
+
+    pub fn draws_remaining(&self) -> u8 {
+        match self {
+            GameHand::Draw(hand) => hand.draws_remaining(),
+            _ => 0,
+        }
+    }
+
+    pub fn to_act(&self) -> Option<usize> {
+        match self {
+            GameHand::Community(hand) => hand.to_act(),
+            GameHand::Stud(hand) => hand.to_act(),
+            GameHand::Draw(hand) => hand.to_act(),
+        }
+    }
+
+    pub fn options(&self) -> Option<BettingOptions> {
+        match self {
+            GameHand::Community(hand) => hand.options(),
+            GameHand::Stud(hand) => hand.options(),
+            GameHand::Draw(hand) => hand.options(),
+        }
+    }
+
+    pub fn history(&self) -> &[Event] {
+        match self {
+            GameHand::Community(hand) => hand.history(),
+            GameHand::Stud(hand) => hand.history(),
+            GameHand::Draw(hand) => hand.history(),
+        }
+    }
+
+    pub fn take_events(&mut self) -> Vec<Event> {
+        match self {
+            GameHand::Community(hand) => hand.take_events(),
+            GameHand::Stud(hand) => hand.take_events(),
+            GameHand::Draw(hand) => hand.take_events(),
+        }
+    }
+
+    pub fn act(&mut self, action: Action) -> Result<(), Error> {
+        match self {
+            GameHand::Community(hand) => hand.act(action),
+            GameHand::Stud(hand) => hand.act(action),
+            GameHand::Draw(hand) => hand.act(action),
+        }
+    }
+
+    // Cards of a seat, down cards first then up cards
+    pub fn cards_of(&self, seat: usize) -> Hand {
+        let seat = &self.seats()[seat];
+        Hand::from([&seat.hole.cards[..], &seat.up.cards[..]].concat())
+    }
+}
+
+// Big bet games for hold'em and single draw, pot limit for Omaha and fixed
+// limit for the rest, as usually spread in mixed games
+pub fn default_limit(variant: Variant) -> Limit {
+    match variant {
+        Variant::Holdem | Variant::DeuceToSevenSingleDraw => Limit::NoLimit,
+        Variant::Omaha | Variant::OmahaHiLo | Variant::FiveCardOmaha | Variant::BigO => {
+            Limit::PotLimit
+        }
+        _ => Limit::FixedLimit,
+    }
+}
+
+// Blinds of 1 and 2, or in stud an ante and bring-in of 1 with bets of 2 and 4
+pub fn default_stakes(variant: Variant) -> Stakes {
+    match variant.gameplay() {
+        Gameplay::Stud => Stakes::stud(1, 1, 2, 4),
+        _ => Stakes::new(1, 2),
+    }
+}
+
+// Scores of a player's cards at showdown, the second for the other half of
+// split pot games when there is one, e.g. a qualifying low.
+// `cards` are the player's own, down and up, and `board` the community cards.
+pub fn showdown_scores(
+    variant: Variant,
+    cards: &Hand,
+    board: &Hand,
+) -> Result<(u128, Option<u128>), Error> {
+    match variant.gameplay() {
+        Gameplay::Community => {
+            let high = best_high(variant, cards, board)?.score;
+            if variant.is_split_pot() {
+                Ok((high, best_low(cards, board)?.map(|low| low.score)))
+            } else {
+                Ok((high, None))
+            }
+        }
+        Gameplay::Stud => {
+            let all: Vec<Card> = [&cards.cards[..], &board.cards[..]].concat();
+            super::stud::showdown_scores(variant, &all)
+        }
+        Gameplay::Draw => super::draw::showdown_scores(variant, cards),
+    }
+}
+
+// What a player's cards make, e.g. "Flush" or "Two pairs, 7 low" in hi-lo
+pub fn describe(variant: Variant, cards: &Hand, board: &Hand) -> Result<String, Error> {
+    let (main, second) = showdown_scores(variant, cards, board)?;
+    let second = second.unwrap_or(0);
+    Ok(match variant {
+        Variant::OmahaHiLo | Variant::BigO | Variant::StudHiLo => format!(
+            "{}, {}",
+            High::describe(main),
+            EightOrBetter::describe(second)
+        ),
+        Variant::Razz | Variant::AceToFiveTripleDraw => LowballAto5::describe(main),
+        Variant::DeuceToSevenSingleDraw | Variant::DeuceToSevenTripleDraw => {
+            Lowball2to7::describe(main)
+        }
+        Variant::Badugi => Badugi::describe(main),
+        Variant::Badacey => format!(
+            "{}, {}",
+            Badugi::describe(main),
+            LowballAto5::describe(second)
+        ),
+        Variant::Badeucey => format!(
+            "{}, {}",
+            Badugi::describe(main),
+            Lowball2to7::describe(second)
+        ),
+        _ => High::describe(main),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_variant_plays_out() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for variant in Variant::ALL {
+            let stacks = [100; 4];
+            let mut hand = GameHand::new(
+                variant,
+                Limit::FixedLimit,
+                default_stakes(variant),
+                0,
+                &stacks,
+                &mut rng,
+            )
+            .unwrap();
+            assert_eq!(hand.variant(), variant);
+            if variant.gameplay() == Gameplay::Stud {
+                assert_eq!(hand.limit(), Limit::FixedLimit);
+            }
+            while !hand.is_finished() {
+                let action = match hand.options() {
+                    _ if hand.is_drawing() => Action::Discard(Vec::new()),
+                    Some(options) if options.bring_in.is_some() => Action::BringIn,
+                    Some(options) if options.can_check() => Action::Check,
+                    _ => Action::Call,
+                };
+                hand.act(action).unwrap();
+            }
+            let total: u64 = hand.seats().iter().map(|seat| seat.stack).sum();
+            assert_eq!(total, 400, "{}", variant);
+            assert!(hand
+                .history()
+                .iter()
+                .any(|event| matches!(event, Event::Showdown { .. })));
+        }
+    }
+
+    #[test]
+    fn test_draw_hand_has_no_board() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let hand = GameHand::new(
+            Variant::Badugi,
+            Limit::FixedLimit,
+            Stakes::new(1, 2),
+            0,
+            &[100, 100],
+            &mut rng,
+        )
+        .unwrap();
+        assert!(hand.board().cards.is_empty());
+        assert_eq!(hand.draws_remaining(), 3);
+        assert_eq!(hand.cards_of(0).cards.len(), 4);
+    }
+
+    #[test]
+    fn test_describe() {
+        let hand = |s: &str| Hand::try_from(s).unwrap();
+        assert_eq!(
+            describe(Variant::Holdem, &hand("Ah Kh"), &hand("Qh Jh Th 2c 3d")).unwrap(),
+            "Straight flush"
+        );
+        assert_eq!(
+            describe(
+                Variant::OmahaHiLo,
+                &hand("Ah 2h Kc Kd"),
+                &hand("3c 5d 8s Ks 9h")
+            )
+            .unwrap(),
+            "Three of a kind, 8 low"
+        );
+        assert_eq!(
+            describe(Variant::Razz, &hand("Ah 2h 3c Kd 4s 5d 9c"), &Hand::new()).unwrap(),
+            "5 low"
+        );
+        assert_eq!(
+            describe(
+                Variant::StudHiLo,
+                &hand("Kh Kd Kc Qs Qd 9c 9h"),
+                &Hand::new()
+            )
+            .unwrap(),
+            "Full house, No low"
+        );
+        assert_eq!(
+            describe(Variant::Badeucey, &hand("2s 3h 4d 5c 7s"), &Hand::new()).unwrap(),
+            "Badugi, 7 low"
+        );
+        let (high, low) = showdown_scores(
+            Variant::OmahaHiLo,
+            &hand("Kh Kd Qc Qd"),
+            &hand("3c 5d 8s Ks 9h"),
+        )
+        .unwrap();
+        assert_eq!(High::describe(high), "Three of a kind");
+        assert_eq!(low, None);
+        assert_eq!(
+            describe(Variant::Holdem, &hand("Ah"), &Hand::new()),
+            Err(Error::InvalidHand)
+        );
+    }
+}
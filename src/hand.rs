@@ -1,4 +1,5 @@
-use crate::card::Card;
+use crate::card::{Card, OrderFirstBy, Rank, Suit};
+use crate::error::Error;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Hand {
@@ -10,12 +11,86 @@ impl Hand {
         Hand { cards: Vec::new() }
     }
 
-    pub fn sort_cards(&mut self, order_first_by: crate::card::OrderFirstBy) {
+    pub fn sort_cards(&mut self, order_first_by: OrderFirstBy) {
         self.cards.sort_by(|a, b| {
             a.ord_position(order_first_by)
                 .cmp(&b.ord_position(order_first_by))
         });
     }
+
+    // Cards of a set from `card::mask`, lowest first
+    pub fn from_mask(mask: u64) -> Hand {
+        let cards: Vec<Card> = (0..DECK_SIZE as u8)
+            .filter(|&position| mask & 1 << position != 0)
+            .map(card_at)
+            .collect();
+        Hand { cards }
+    }
+
+    // Colex rank of the cards as a set, in `0..Hand::index_count(len)`, with
+    // cards at their `ord_position` by rank
+    pub fn to_index(&self) -> Result<u64, Error> {
+        if crate::card::mask(&self.cards).count_ones() as usize != self.cards.len() {
+            return Err(Error::InvalidHand);
+        }
+        let mut positions: Vec<u8> = self
+            .cards
+            .iter()
+            .map(|card| card.ord_position(OrderFirstBy::Rank))
+            .collect();
+        positions.sort();
+        Ok(positions
+            .iter()
+            .enumerate()
+            .map(|(i, &position)| binomial(position as usize, i + 1))
+            .sum())
+    }
+
+    // Inverse of `to_index` for hands of `len` cards, lowest card first
+    pub fn from_index(index: u64, len: usize) -> Result<Hand, Error> {
+        if index >= Hand::index_count(len) {
+            return Err(Error::InvalidHand);
+        }
+        let mut cards: Vec<Card> = Vec::with_capacity(len);
+        let mut rest = index;
+        let mut position = DECK_SIZE;
+        for k in (1..=len).rev() {
+            // Highest position left whose binomial still fits
+            position -= 1;
+            while binomial(position, k) > rest {
+                position -= 1;
+            }
+            rest -= binomial(position, k);
+            cards.push(card_at(position as u8));
+        }
+        cards.reverse();
+        Ok(Hand { cards })
+    }
+
+    // Number of distinct hands of `len` cards, C(52, len)
+    pub fn index_count(len: usize) -> u64 {
+        binomial(DECK_SIZE, len)
+    }
+}
+
+const DECK_SIZE: usize = 52;
+
+pub(crate) fn binomial(n: usize, k: usize) -> u64 {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |result, i| result * (n - i) as u64 / (i + 1) as u64)
+}
+
+// Card at an `ord_position` by rank
+fn card_at(position: u8) -> Card {
+    let suit = Suit::iter()
+        .iter()
+        .find(|&&suit| suit as u8 == position % 4);
+    let rank = Rank::iter()
+        .iter()
+        .find(|&&rank| rank as u8 == position / 4);
+    Card::new(*suit.unwrap(), *rank.unwrap())
 }
 
 impl Default for Hand {
@@ -26,10 +101,10 @@ impl Default for Hand {
 
 impl std::fmt::Display for Hand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for card in &self.cards[..self.cards.len() - 1] {
-            write!(f, "{} ", card)?;
-        }
-        if let Some(last_card) = self.cards.last() {
+        if let Some((last_card, cards)) = self.cards.split_last() {
+            for card in cards {
+                write!(f, "{} ", card)?;
+            }
             write!(f, "{}", last_card)?;
         }
         Ok(())
@@ -60,6 +135,40 @@ impl TryFrom<&str> for Hand {
     }
 }
 
+// Serialized as space-separated cards, e.g. "Ac Kd", and read from either
+// that or an array of cards
+#[cfg(feature = "serde")]
+impl serde::Serialize for Hand {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum HandNotation {
+    Text(String),
+    Cards(Vec<Card>),
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Hand {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Hand, D::Error> {
+        match <HandNotation as serde::Deserialize>::deserialize(deserializer)? {
+            HandNotation::Text(s) => Hand::try_from(s.as_str())
+                .map_err(|_| serde::de::Error::custom(format!("invalid hand notation: {}", s))),
+            HandNotation::Cards(cards) => Ok(Hand { cards }),
+        }
+    }
+}
+
+// Hand from its notation, for the tests
+#[cfg(test)]
+pub(crate) fn hand(s: &str) -> Hand {
+    Hand::try_from(s).unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -80,6 +189,59 @@ mod tests {
         assert_eq!(hand.to_string(), "Kc 2d Jh");
     }
 
+    #[test]
+    fn display_empty_hand() {
+        assert_eq!(Hand::new().to_string(), "");
+    }
+
+    #[test]
+    fn index_round_trip() {
+        let hand = Hand::try_from("2d 2s").unwrap();
+        assert_eq!(hand.to_index(), Ok(1));
+        assert_eq!(Hand::try_from("Ac").unwrap().to_index(), Ok(51));
+        assert_eq!(
+            Hand::from_index(Hand::index_count(5) - 1, 5)
+                .unwrap()
+                .to_string(),
+            "Kc As Ah Ad Ac"
+        );
+
+        for len in 0..=3 {
+            for index in 0..Hand::index_count(len) {
+                let hand = Hand::from_index(index, len).unwrap();
+                assert_eq!(hand.cards.len(), len);
+                assert_eq!(hand.to_index(), Ok(index));
+            }
+        }
+    }
+
+    #[test]
+    fn index_ignores_card_order() {
+        let a = Hand::try_from("Ah 7c Td 2s").unwrap();
+        let b = Hand::try_from("2s Td Ah 7c").unwrap();
+        assert_eq!(a.to_index(), b.to_index());
+        assert_eq!(Hand::index_count(2), 1326);
+        assert_eq!(Hand::index_count(7), 133_784_560);
+    }
+
+    #[test]
+    fn from_mask() {
+        let hand = Hand::try_from("Ah 7c Td 2s").unwrap();
+        let mask = crate::card::mask(&hand.cards);
+        assert_eq!(Hand::from_mask(mask).to_string(), "2s 7c Td Ah");
+        assert_eq!(Hand::from_mask(0), Hand::new());
+        // Bits past the deck are ignored
+        assert_eq!(Hand::from_mask(u64::MAX).cards.len(), 52);
+    }
+
+    #[test]
+    fn invalid_index() {
+        let pair = Hand::try_from("Ah Ah").unwrap();
+        assert_eq!(pair.to_index(), Err(Error::InvalidHand));
+        assert_eq!(Hand::from_index(1326, 2), Err(Error::InvalidHand));
+        assert_eq!(Hand::from_index(0, 53), Err(Error::InvalidHand));
+    }
+
     #[test]
     fn from_vec_to_hand() {
         let deck = crate::deck::Deck::new();
@@ -123,4 +285,17 @@ mod tests {
         assert_eq!(hand.cards.len(), 52);
         assert_eq!(hand.to_string(), "2s 3s 4s 5s 6s 7s 8s 9s Ts Js Qs Ks As 2h 3h 4h 5h 6h 7h 8h 9h Th Jh Qh Kh Ah 2d 3d 4d 5d 6d 7d 8d 9d Td Jd Qd Kd Ad 2c 3c 4c 5c 6c 7c 8c 9c Tc Jc Qc Kc Ac");
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_hand_notation() {
+        let hand = Hand::try_from("Ac Kd 2h").unwrap();
+        assert_eq!(serde_json::to_string(&hand).unwrap(), "\"Ac Kd 2h\"");
+        assert_eq!(serde_json::from_str::<Hand>("\"Ac Kd 2h\"").unwrap(), hand);
+        assert_eq!(
+            serde_json::from_str::<Hand>("[\"Ac\", \"Kd\", \"2h\"]").unwrap(),
+            hand
+        );
+        assert!(serde_json::from_str::<Hand>("\"Ac Kx\"").is_err());
+    }
 }
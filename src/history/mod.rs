@@ -0,0 +1,518 @@
+// Hand history format, one line per fact in the order it happened:
+//
+//   dealers-choice hand history v1
+//   variant Hold'em
+//   limit NL
+//   stakes 0 1 2 0 2 4          ante, blinds, bring-in, small and big bet
+//   button 0
+//   seat 0 100                  starting stack, 0 when sitting out
+//   ante 0 1
+//   blind 1 1
+//   dealt 1 down Ac Kd          or "up" for face-up stud cards
+//   street Flop
+//   board 2c 3d 4h
+//   action 0 raise 6            fold, check, call, bet N, raise N,
+//                               bring-in, complete, discard [cards]
+//   returned 0 4
+//   draw 0 Kc Kd / 8h As        discarded / drawn, empty to stand pat
+//   reshuffle 12
+//   showdown 1 Ac Kd
+//   award 1 20 high             or "low" for the low half of a split pot
+//
+// Every hand starts with the version line. Blank lines are ignored.
+
+use crate::error::Error;
+use crate::game::{Action, Event, Limit, Stakes, Street, Variant};
+use crate::hand::Hand;
+
+pub mod pokerstars;
+
+pub const VERSION: u32 = 1;
+const HEADER: &str = "dealers-choice hand history v";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandHistory {
+    pub variant: Variant,
+    pub limit: Limit,
+    pub stakes: Stakes,
+    pub button: usize,
+    // Stacks at the start of the hand
+    pub stacks: Vec<u64>,
+    pub events: Vec<Event>,
+}
+
+impl std::fmt::Display for HandHistory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stakes = &self.stakes;
+        writeln!(f, "{}{}", HEADER, VERSION)?;
+        writeln!(f, "variant {}", self.variant)?;
+        writeln!(f, "limit {}", self.limit)?;
+        writeln!(
+            f,
+            "stakes {} {} {} {} {} {}",
+            stakes.ante,
+            stakes.small_blind,
+            stakes.big_blind,
+            stakes.bring_in,
+            stakes.small_bet,
+            stakes.big_bet
+        )?;
+        writeln!(f, "button {}", self.button)?;
+        for (seat, stack) in self.stacks.iter().enumerate() {
+            writeln!(f, "seat {} {}", seat, stack)?;
+        }
+        for event in &self.events {
+            write_event(f, event)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_event(f: &mut std::fmt::Formatter<'_>, event: &Event) -> std::fmt::Result {
+    match event {
+        Event::Ante { seat, amount } => writeln!(f, "ante {} {}", seat, amount),
+        Event::Blind { seat, amount } => writeln!(f, "blind {} {}", seat, amount),
+        Event::Dealt {
+            seat,
+            cards,
+            face_up,
+        } => {
+            let face = if *face_up { "up" } else { "down" };
+            writeln!(f, "dealt {} {} {}", seat, face, cards)
+        }
+        Event::Street(street) => writeln!(f, "street {}", street),
+        Event::Board(cards) => writeln!(f, "board {}", cards),
+        Event::Action { seat, action } => {
+            write!(f, "action {} ", seat)?;
+            match action {
+                Action::Fold => writeln!(f, "fold"),
+                Action::Check => writeln!(f, "check"),
+                Action::Call => writeln!(f, "call"),
+                Action::Bet(amount) => writeln!(f, "bet {}", amount),
+                Action::Raise(amount) => writeln!(f, "raise {}", amount),
+                Action::BringIn => writeln!(f, "bring-in"),
+                Action::Complete => writeln!(f, "complete"),
+                Action::Discard(cards) => writeln!(f, "discard {}", Hand::from(cards.clone())),
+            }
+        }
+        Event::Returned { seat, amount } => writeln!(f, "returned {} {}", seat, amount),
+        Event::Draw {
+            seat,
+            discarded,
+            drawn,
+        } => {
+            // No trailing space when standing pat
+            let line = format!("draw {} {} / {}", seat, discarded, drawn);
+            writeln!(
+                f,
+                "{}",
+                line.split_whitespace().collect::<Vec<_>>().join(" ")
+            )
+        }
+        Event::Reshuffle { cards } => writeln!(f, "reshuffle {}", cards),
+        Event::Showdown { seat, cards } => writeln!(f, "showdown {} {}", seat, cards),
+        Event::Award { seat, amount, low } => {
+            let half = if *low { "low" } else { "high" };
+            writeln!(f, "award {} {} {}", seat, amount, half)
+        }
+    }
+}
+
+impl HandHistory {
+    // Every hand in a file of hand histories, each parsed on its own so one
+    // broken hand does not lose the others
+    pub fn parse_all(s: &str) -> Vec<Result<HandHistory, Error>> {
+        let mut hands: Vec<String> = Vec::new();
+        for line in s.lines() {
+            if line.trim().starts_with(HEADER) || hands.is_empty() {
+                hands.push(String::new());
+            }
+            if let Some(hand) = hands.last_mut() {
+                hand.push_str(line);
+                hand.push('\n');
+            }
+        }
+        hands
+            .iter()
+            .filter(|hand| !hand.trim().is_empty())
+            .map(|hand| HandHistory::try_from(hand.as_str()))
+            .collect()
+    }
+}
+
+impl TryFrom<&str> for HandHistory {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<HandHistory, Error> {
+        let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+
+        let version = lines
+            .next()
+            .and_then(|line| line.strip_prefix(HEADER))
+            .ok_or(Error::InvalidHandHistory)?;
+        if number::<u32>(version)? != VERSION {
+            return Err(Error::InvalidHandHistory);
+        }
+
+        let mut header = |key: &str| -> Result<&str, Error> {
+            lines
+                .next()
+                .and_then(|line| line.strip_prefix(key))
+                .and_then(|rest| rest.strip_prefix(' '))
+                .ok_or(Error::InvalidHandHistory)
+        };
+        let variant = header("variant")?;
+        let variant = Variant::ALL
+            .into_iter()
+            .find(|v| v.to_string() == variant)
+            .ok_or(Error::InvalidHandHistory)?;
+        let limit = header("limit")?;
+        let limit = Limit::ALL
+            .into_iter()
+            .find(|l| l.to_string() == limit)
+            .ok_or(Error::InvalidHandHistory)?;
+        let stakes = header("stakes")?
+            .split_whitespace()
+            .map(number::<u64>)
+            .collect::<Result<Vec<u64>, Error>>()?;
+        let stakes = match stakes[..] {
+            [ante, small_blind, big_blind, bring_in, small_bet, big_bet] => Stakes {
+                ante,
+                small_blind,
+                big_blind,
+                bring_in,
+                small_bet,
+                big_bet,
+            },
+            _ => return Err(Error::InvalidHandHistory),
+        };
+        let button = number(header("button")?)?;
+
+        let mut history = HandHistory {
+            variant,
+            limit,
+            stakes,
+            button,
+            stacks: Vec::new(),
+            events: Vec::new(),
+        };
+        for line in lines {
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            if keyword == "seat" && history.events.is_empty() {
+                let (seat, stack) = seat_and_rest(rest)?;
+                if seat != history.stacks.len() {
+                    return Err(Error::InvalidHandHistory);
+                }
+                history.stacks.push(number(stack)?);
+            } else {
+                history.events.push(parse_event(keyword, rest)?);
+            }
+        }
+
+        let seats = history.stacks.len();
+        if history.button >= seats || history.events.iter().any(|e| seat_of(e) >= seats) {
+            return Err(Error::InvalidHandHistory);
+        }
+        Ok(history)
+    }
+}
+
+fn parse_event(keyword: &str, rest: &str) -> Result<Event, Error> {
+    let event = match keyword {
+        "street" => Event::Street(
+            Street::ALL
+                .into_iter()
+                .find(|street| street.to_string() == rest)
+                .ok_or(Error::InvalidHandHistory)?,
+        ),
+        "board" => Event::Board(cards(rest)?),
+        "reshuffle" => Event::Reshuffle {
+            cards: number(rest)?,
+        },
+        _ => {
+            let (seat, rest) = seat_and_rest(rest)?;
+            match keyword {
+                "ante" => Event::Ante {
+                    seat,
+                    amount: number(rest)?,
+                },
+                "blind" => Event::Blind {
+                    seat,
+                    amount: number(rest)?,
+                },
+                "dealt" => {
+                    let (face, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+                    let face_up = match face {
+                        "up" => true,
+                        "down" => false,
+                        _ => return Err(Error::InvalidHandHistory),
+                    };
+                    Event::Dealt {
+                        seat,
+                        cards: cards(rest)?,
+                        face_up,
+                    }
+                }
+                "action" => Event::Action {
+                    seat,
+                    action: parse_action(rest)?,
+                },
+                "returned" => Event::Returned {
+                    seat,
+                    amount: number(rest)?,
+                },
+                "draw" => {
+                    let (discarded, drawn) =
+                        rest.split_once('/').ok_or(Error::InvalidHandHistory)?;
+                    Event::Draw {
+                        seat,
+                        discarded: cards(discarded)?,
+                        drawn: cards(drawn)?,
+                    }
+                }
+                "showdown" => Event::Showdown {
+                    seat,
+                    cards: cards(rest)?,
+                },
+                "award" => {
+                    let (amount, half) = rest.split_once(' ').ok_or(Error::InvalidHandHistory)?;
+                    let low = match half {
+                        "high" => false,
+                        "low" => true,
+                        _ => return Err(Error::InvalidHandHistory),
+                    };
+                    Event::Award {
+                        seat,
+                        amount: number(amount)?,
+                        low,
+                    }
+                }
+                _ => return Err(Error::InvalidHandHistory),
+            }
+        }
+    };
+    Ok(event)
+}
+
+fn parse_action(s: &str) -> Result<Action, Error> {
+    let (keyword, rest) = s.split_once(' ').unwrap_or((s, ""));
+    let action = match keyword {
+        "fold" => Action::Fold,
+        "check" => Action::Check,
+        "call" => Action::Call,
+        "bet" => Action::Bet(number(rest)?),
+        "raise" => Action::Raise(number(rest)?),
+        "bring-in" => Action::BringIn,
+        "complete" => Action::Complete,
+        "discard" => Action::Discard(cards(rest)?.cards),
+        _ => return Err(Error::InvalidHandHistory),
+    };
+    if !rest.is_empty()
+        && !matches!(
+            action,
+            Action::Bet(_) | Action::Raise(_) | Action::Discard(_)
+        )
+    {
+        return Err(Error::InvalidHandHistory);
+    }
+    Ok(action)
+}
+
+fn seat_and_rest(s: &str) -> Result<(usize, &str), Error> {
+    let (seat, rest) = s.split_once(' ').unwrap_or((s, ""));
+    Ok((number(seat)?, rest))
+}
+
+fn number<T: std::str::FromStr>(s: &str) -> Result<T, Error> {
+    s.trim().parse().map_err(|_| Error::InvalidHandHistory)
+}
+
+// Cards in `Hand` notation, possibly none
+fn cards(s: &str) -> Result<Hand, Error> {
+    Hand::try_from(s)
+}
+
+fn seat_of(event: &Event) -> usize {
+    match event {
+        Event::Ante { seat, .. }
+        | Event::Blind { seat, .. }
+        | Event::Dealt { seat, .. }
+        | Event::Action { seat, .. }
+        | Event::Returned { seat, .. }
+        | Event::Draw { seat, .. }
+        | Event::Showdown { seat, .. }
+        | Event::Award { seat, .. } => *seat,
+        Event::Street(_) | Event::Board(_) | Event::Reshuffle { .. } => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::Deck;
+    use crate::game::community::{CommunityConfig, CommunityHand};
+    use crate::game::draw::{DrawConfig, DrawHand};
+    use crate::game::stud::{StudConfig, StudHand};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn shuffled(seed: u64) -> Deck {
+        let mut deck = Deck::new();
+        deck.shuffle_with_rng(&mut StdRng::seed_from_u64(seed));
+        deck
+    }
+
+    #[test]
+    fn test_round_trip_holdem() {
+        let stacks = [100, 0, 50, 200];
+        let config = CommunityConfig {
+            variant: Variant::Holdem,
+            limit: Limit::NoLimit,
+            stakes: Stakes::new(1, 2),
+            button: 0,
+            bomb_pot: false,
+        };
+        let mut hand = CommunityHand::new(config, &stacks, shuffled(1)).unwrap();
+        hand.act(Action::Raise(6)).unwrap();
+        hand.act(Action::Fold).unwrap();
+        hand.act(Action::Call).unwrap();
+        while !hand.is_finished() {
+            let options = hand.options().unwrap();
+            let action = if options.can_check() {
+                Action::Check
+            } else {
+                Action::Call
+            };
+            hand.act(action).unwrap();
+        }
+        let history = HandHistory {
+            variant: config.variant,
+            limit: config.limit,
+            stakes: config.stakes,
+            button: config.button,
+            stacks: stacks.to_vec(),
+            events: hand.history().to_vec(),
+        };
+        let text = history.to_string();
+        assert!(text.starts_with("dealers-choice hand history v1\nvariant Hold'em\nlimit NL\n"));
+        assert!(text.contains("\nstreet River\n"));
+        assert_eq!(HandHistory::try_from(text.as_str()).unwrap(), history);
+    }
+
+    #[test]
+    fn test_round_trip_stud() {
+        let stacks = [100, 100, 100];
+        let config = StudConfig {
+            variant: Variant::StudHiLo,
+            stakes: Stakes::stud(1, 2, 5, 10),
+            button: 2,
+        };
+        let mut hand = StudHand::new(config, &stacks, shuffled(2)).unwrap();
+        while !hand.is_finished() {
+            let options = hand.options().unwrap();
+            let action = if options.bring_in.is_some() {
+                Action::BringIn
+            } else if options.can_check() {
+                Action::Check
+            } else {
+                Action::Call
+            };
+            hand.act(action).unwrap();
+        }
+        let history = HandHistory {
+            variant: config.variant,
+            limit: Limit::FixedLimit,
+            stakes: config.stakes,
+            button: config.button,
+            stacks: stacks.to_vec(),
+            events: hand.history().to_vec(),
+        };
+        let text = history.to_string();
+        assert!(text.contains(" up "));
+        assert!(text.contains(" down "));
+        assert!(text.contains("bring-in"));
+        assert_eq!(HandHistory::try_from(text.as_str()).unwrap(), history);
+    }
+
+    #[test]
+    fn test_round_trip_draw() {
+        let stacks = [100, 100];
+        let config = DrawConfig {
+            variant: Variant::DeuceToSevenTripleDraw,
+            limit: Limit::FixedLimit,
+            stakes: Stakes::new(1, 2),
+            button: 0,
+        };
+        let mut hand =
+            DrawHand::with_rng(config, &stacks, shuffled(3), StdRng::seed_from_u64(3)).unwrap();
+        let mut pat = false;
+        while !hand.is_finished() {
+            if hand.is_drawing() {
+                let seat = hand.to_act().unwrap();
+                let discards = if pat {
+                    vec![]
+                } else {
+                    hand.seats()[seat].hole.cards[..2].to_vec()
+                };
+                pat = !pat;
+                hand.act(Action::Discard(discards)).unwrap();
+            } else if hand.options().unwrap().can_check() {
+                hand.act(Action::Check).unwrap();
+            } else {
+                hand.act(Action::Call).unwrap();
+            }
+        }
+        let history = HandHistory {
+            variant: config.variant,
+            limit: config.limit,
+            stakes: config.stakes,
+            button: config.button,
+            stacks: stacks.to_vec(),
+            events: hand.history().to_vec(),
+        };
+        let text = history.to_string();
+        assert!(text.contains(" /\n"));
+        assert_eq!(HandHistory::try_from(text.as_str()).unwrap(), history);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let valid = "dealers-choice hand history v1\nvariant Razz\nlimit FL\n\
+                     stakes 1 0 0 2 5 10\nbutton 1\nseat 0 100\nseat 1 100\n";
+        assert!(HandHistory::try_from(valid).is_ok());
+
+        let broken = [
+            "",
+            "dealers-choice hand history v2\n",
+            &valid.replace("Razz", "Canasta"),
+            &valid.replace("button 1", "button 2"),
+            &format!("{}action 0 shove\n", valid),
+            &format!("{}award 3 10 high\n", valid),
+            &format!("{}action 0 call 5\n", valid),
+        ];
+        for s in broken {
+            assert_eq!(
+                HandHistory::try_from(s).unwrap_err(),
+                Error::InvalidHandHistory,
+                "{}",
+                s
+            );
+        }
+        assert_eq!(
+            HandHistory::try_from(format!("{}board Ac Kx\n", valid).as_str()).unwrap_err(),
+            Error::InvalidCardNotation
+        );
+    }
+
+    #[test]
+    fn test_parse_all() {
+        let hand = "dealers-choice hand history v1\nvariant Badugi\nlimit FL\n\
+                    stakes 0 1 2 0 2 4\nbutton 0\nseat 0 100\nseat 1 100\n";
+        let file = format!("{}\n{}action 0 shove\n\n{}", hand, hand, hand);
+        let hands = HandHistory::parse_all(&file);
+        assert_eq!(hands.len(), 3);
+        assert!(hands[0].is_ok());
+        assert_eq!(hands[1], Err(Error::InvalidHandHistory));
+        assert!(hands[2].is_ok());
+    }
+}
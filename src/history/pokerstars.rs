@@ -0,0 +1,861 @@
+// Importer for PokerStars text hand histories. Only what the history shows
+// is imported: opponents' cards appear when they are face up or shown down,
+// and their draws only when they stand pat.
+
+use super::HandHistory;
+use crate::card::{Card, Rank};
+use crate::error::Error;
+use crate::game::{Action, Event, Gameplay, Limit, Stakes, Street, Variant};
+use crate::hand::Hand;
+
+// Games as named in the hand description, matched in this order so that
+// "5 Card Omaha" is not taken for "Omaha", nor "Omaha Hi/Lo" for "Omaha"
+const GAMES: [(&str, Variant); 11] = [
+    ("Hold'em", Variant::Holdem),
+    ("5 Card Omaha", Variant::FiveCardOmaha),
+    ("Omaha Hi/Lo", Variant::OmahaHiLo),
+    ("Omaha", Variant::Omaha),
+    ("7 Card Stud Hi/Lo", Variant::StudHiLo),
+    ("7 Card Stud", Variant::SevenCardStud),
+    ("Razz", Variant::Razz),
+    ("5 Card Draw", Variant::FiveCardDraw),
+    ("Single Draw 2-7 Lowball", Variant::DeuceToSevenSingleDraw),
+    ("Triple Draw 2-7 Lowball", Variant::DeuceToSevenTripleDraw),
+    ("Badugi", Variant::Badugi),
+];
+
+const LIMITS: [(&str, Limit); 4] = [
+    ("No Limit", Limit::NoLimit),
+    ("Pot Limit", Limit::PotLimit),
+    ("Fixed Limit", Limit::FixedLimit),
+    ("Limit", Limit::FixedLimit),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PokerStarsHand {
+    // Hand number on the site
+    pub id: String,
+    // Player names by seat, none for empty seats
+    pub players: Vec<Option<String>>,
+    pub history: HandHistory,
+}
+
+// Every hand in a file, each parsed on its own so one broken hand does not
+// lose the others
+pub fn parse_file(s: &str) -> Vec<Result<PokerStarsHand, Error>> {
+    let mut hands: Vec<Vec<&str>> = Vec::new();
+    for line in s.lines() {
+        let line = line.trim_start_matches('\u{feff}');
+        if is_hand_start(line) {
+            hands.push(Vec::new());
+        }
+        if let Some(hand) = hands.last_mut() {
+            hand.push(line);
+        }
+    }
+    hands
+        .iter()
+        .map(|lines| PokerStarsHand::try_from(lines.join("\n").as_str()))
+        .collect()
+}
+
+fn is_hand_start(line: &str) -> bool {
+    line.starts_with("PokerStars ") && (line.contains(" Hand #") || line.contains(" Game #"))
+}
+
+impl TryFrom<&str> for PokerStarsHand {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<PokerStarsHand, Error> {
+        let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+        let first = lines.next().ok_or(Error::InvalidHandHistory)?;
+        if !is_hand_start(first) {
+            return Err(Error::InvalidHandHistory);
+        }
+        let (_, rest) = first.split_once('#').ok_or(Error::InvalidHandHistory)?;
+        let (id, description) = rest.split_once(':').ok_or(Error::InvalidHandHistory)?;
+        let (variant, limit) = game(description)?;
+
+        // Table line, then the seats up to the first street
+        let table = lines.next().ok_or(Error::InvalidHandHistory)?;
+        let button = table
+            .split_once("Seat #")
+            .and_then(|(_, rest)| rest.split_whitespace().next())
+            .ok_or(Error::InvalidHandHistory)?;
+        let button = number::<usize>(button)?
+            .checked_sub(1)
+            .ok_or(Error::InvalidHandHistory)?;
+        let max_seats = table
+            .split_whitespace()
+            .find_map(|word| word.strip_suffix("-max"))
+            .map(number)
+            .transpose()?
+            .unwrap_or(0);
+
+        let lines: Vec<&str> = lines.collect();
+        let mut seat_lines: Vec<(usize, &str, &str, bool)> = Vec::new();
+        for line in lines.iter().take_while(|line| !line.starts_with("*** ")) {
+            if let Some(seat) = seat_line(line)? {
+                seat_lines.push(seat);
+            }
+        }
+        let seats = seat_lines
+            .iter()
+            .map(|&(seat, ..)| seat + 1)
+            .max()
+            .unwrap_or(0)
+            .max(max_seats)
+            .max(button + 1);
+        // Cash games count in cents, tournaments in chips
+        let cents = seat_lines
+            .iter()
+            .any(|(_, _, chips, _)| chips.contains(['$', '€', '£']));
+
+        let mut players: Vec<Option<String>> = vec![None; seats];
+        let mut stacks: Vec<u64> = vec![0; seats];
+        for &(seat, name, chips, sitting_out) in &seat_lines {
+            players[seat] = Some(name.to_string());
+            if !sitting_out {
+                stacks[seat] = amount(chips, cents)?;
+            }
+        }
+
+        let (small, big) = blinds(description, cents)?;
+        let stakes = match limit {
+            Limit::FixedLimit => Stakes {
+                small_bet: small,
+                big_bet: big,
+                ..Default::default()
+            },
+            _ => Stakes::new(small, big),
+        };
+
+        let mut parser = Parser {
+            variant,
+            cents,
+            players: &players,
+            stakes,
+            street: None,
+            discards: vec![None; seats],
+            lows: vec![None; seats],
+            events: Vec::new(),
+        };
+        for line in &lines {
+            if *line == "*** SUMMARY ***" {
+                break;
+            }
+            parser.line(line)?;
+        }
+        if variant.is_split_pot() {
+            parser.low_awards();
+        }
+
+        Ok(PokerStarsHand {
+            id: id.trim().to_string(),
+            history: HandHistory {
+                variant,
+                limit,
+                stakes: parser.stakes,
+                button,
+                stacks,
+                events: parser.events,
+            },
+            players,
+        })
+    }
+}
+
+// Variant and limit from the hand's description, e.g. "Hold'em No Limit"
+fn game(description: &str) -> Result<(Variant, Limit), Error> {
+    for (name, variant) in GAMES {
+        if let Some((_, rest)) = description.split_once(name) {
+            let rest = rest.trim_start();
+            // Hi/lo versions of games without one here, e.g. 5 Card Omaha Hi/Lo
+            if rest.starts_with("Hi/Lo") {
+                return Err(Error::UnsupportedGame);
+            }
+            let limit = LIMITS
+                .iter()
+                .find(|(name, _)| rest.starts_with(name))
+                .map(|&(_, limit)| limit)
+                .ok_or(Error::InvalidHandHistory)?;
+            return Ok((variant, limit));
+        }
+    }
+    Err(Error::UnsupportedGame)
+}
+
+// Small and big blind, or small and big bet in fixed limit, e.g. "($0.01/$0.02 USD)"
+fn blinds(description: &str, cents: bool) -> Result<(u64, u64), Error> {
+    description
+        .split(|c: char| c.is_whitespace() || "(),".contains(c))
+        .filter_map(|word| word.split_once('/'))
+        .find_map(|(small, big)| Some((amount(small, cents).ok()?, amount(big, cents).ok()?)))
+        .ok_or(Error::InvalidHandHistory)
+}
+
+// "Seat 3: name ($1.50 in chips)" as its 0-based seat, name, chips and
+// whether the player sits out
+fn seat_line(line: &str) -> Result<Option<(usize, &str, &str, bool)>, Error> {
+    let rest = match line.strip_prefix("Seat ") {
+        Some(rest) => rest,
+        None => return Ok(None),
+    };
+    let (seat, rest) = rest.split_once(": ").ok_or(Error::InvalidHandHistory)?;
+    let seat = number::<usize>(seat)?
+        .checked_sub(1)
+        .ok_or(Error::InvalidHandHistory)?;
+    let chips_end = rest.find(" in chips").ok_or(Error::InvalidHandHistory)?;
+    let chips_start = rest[..chips_end]
+        .rfind('(')
+        .ok_or(Error::InvalidHandHistory)?;
+    let name = rest[..chips_start].trim_end();
+    let sitting_out = rest.ends_with("is sitting out");
+    Ok(Some((
+        seat,
+        name,
+        &rest[chips_start + 1..chips_end],
+        sitting_out,
+    )))
+}
+
+struct Parser<'a> {
+    variant: Variant,
+    cents: bool,
+    players: &'a [Option<String>],
+    stakes: Stakes,
+    street: Option<Street>,
+    // Cards a player threw in the current draw, when shown
+    discards: Vec<Option<Hand>>,
+    // Low shown down in hi/lo, highest rank first and the ace as 1
+    lows: Vec<Option<Vec<u8>>>,
+    events: Vec<Event>,
+}
+
+impl Parser<'_> {
+    fn line(&mut self, line: &str) -> Result<(), Error> {
+        if let Some(header) = line.strip_prefix("*** ") {
+            return self.street(header);
+        }
+        if let Some(rest) = line.strip_prefix("Dealt to ") {
+            let (seat, cards) = self.player(rest, " ")?;
+            return self.dealt(seat, cards);
+        }
+        if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+            let (amount, name) = rest
+                .split_once(") returned to ")
+                .ok_or(Error::InvalidHandHistory)?;
+            let (seat, _) = self.player(name, "")?;
+            self.events.push(Event::Returned {
+                seat,
+                amount: self.amount(amount)?,
+            });
+            return Ok(());
+        }
+        if let Ok((seat, rest)) = self.player(line, " collected ") {
+            let amount = rest.split_whitespace().next().unwrap_or_default();
+            self.events.push(Event::Award {
+                seat,
+                amount: self.amount(amount)?,
+                low: false,
+            });
+            return Ok(());
+        }
+        if let Ok((seat, rest)) = self.player(line, ": ") {
+            return self.player_line(seat, rest);
+        }
+        // Chat, table moves and other lines that do not change the hand
+        Ok(())
+    }
+
+    fn street(&mut self, header: &str) -> Result<(), Error> {
+        let (name, cards) = header.split_once(" ***").unwrap_or((header, ""));
+        let stud = self.variant.gameplay() == Gameplay::Stud;
+        let street = match name {
+            "HOLE CARDS" if !stud => Street::Preflop,
+            "DEALING HANDS" => Street::Predraw,
+            "FLOP" => Street::Flop,
+            "TURN" => Street::Turn,
+            "RIVER" if stud => Street::Seventh,
+            "RIVER" => Street::River,
+            "3rd STREET" => Street::Third,
+            "4th STREET" => Street::Fourth,
+            "5th STREET" => Street::Fifth,
+            "6th STREET" => Street::Sixth,
+            "FIRST DRAW" => Street::FirstDraw,
+            "SECOND DRAW" => Street::SecondDraw,
+            "THIRD DRAW" => Street::ThirdDraw,
+            _ => return Ok(()),
+        };
+        self.street = Some(street);
+        self.discards.iter_mut().for_each(|discard| *discard = None);
+        self.events.push(Event::Street(street));
+        if let Some(board) = brackets(cards)?.pop() {
+            self.events.push(Event::Board(board));
+        }
+        Ok(())
+    }
+
+    // "[Ah Kd]" or, on later streets and draws, "[cards held] [new cards]"
+    fn dealt(&mut self, seat: usize, cards: &str) -> Result<(), Error> {
+        let mut brackets = brackets(cards)?;
+        let held = brackets.len() > 1;
+        let new = match brackets.pop() {
+            Some(new) => new,
+            None => return Ok(()),
+        };
+        match self.variant.gameplay() {
+            Gameplay::Stud => match self.street {
+                Some(Street::Third) if new.cards.len() == 3 => {
+                    let (down, up) = new.cards.split_at(2);
+                    self.deal(seat, down.to_vec(), false);
+                    self.deal(seat, up.to_vec(), true);
+                }
+                Some(Street::Seventh) => self.deal(seat, new.cards, false),
+                _ => self.deal(seat, new.cards, true),
+            },
+            Gameplay::Draw if held => {
+                let discarded = self.discards[seat].take().unwrap_or_default();
+                self.events.push(Event::Draw {
+                    seat,
+                    discarded,
+                    drawn: new,
+                });
+            }
+            _ => self.deal(seat, new.cards, false),
+        }
+        Ok(())
+    }
+
+    fn deal(&mut self, seat: usize, cards: Vec<Card>, face_up: bool) {
+        self.events.push(Event::Dealt {
+            seat,
+            cards: Hand::from(cards),
+            face_up,
+        });
+    }
+
+    fn player_line(&mut self, seat: usize, line: &str) -> Result<(), Error> {
+        let line = line.trim_end_matches(" and is all-in");
+        let (verb, rest) = split_verb(line);
+        let action = match verb {
+            "posts the ante" => {
+                let amount = self.amount(rest)?;
+                self.stakes.ante = self.stakes.ante.max(amount);
+                self.events.push(Event::Ante { seat, amount });
+                return Ok(());
+            }
+            "posts small blind" | "posts big blind" | "posts small & big blinds" => {
+                let amount = self.amount(rest)?;
+                if verb == "posts small blind" && self.stakes.small_blind == 0 {
+                    self.stakes.small_blind = amount;
+                } else if verb == "posts big blind" && self.stakes.big_blind == 0 {
+                    self.stakes.big_blind = amount;
+                }
+                self.events.push(Event::Blind { seat, amount });
+                return Ok(());
+            }
+            "shows" => {
+                let cards = brackets(rest)?.pop().ok_or(Error::InvalidHandHistory)?;
+                if self.variant.is_split_pot() {
+                    self.lows[seat] = low(rest)?;
+                }
+                self.events.push(Event::Showdown { seat, cards });
+                return Ok(());
+            }
+            "discards" => {
+                self.discards[seat] = brackets(rest)?.pop();
+                return Ok(());
+            }
+            "stands pat" => {
+                self.events.push(Event::Draw {
+                    seat,
+                    discarded: Hand::new(),
+                    drawn: Hand::new(),
+                });
+                return Ok(());
+            }
+            "brings in for" => {
+                self.stakes.bring_in = self.amount(rest)?;
+                Action::BringIn
+            }
+            "folds" => Action::Fold,
+            "checks" => Action::Check,
+            "calls" => Action::Call,
+            "bets" => Action::Bet(self.amount(rest)?),
+            "raises" => {
+                let (_, to) = rest.split_once(" to ").ok_or(Error::InvalidHandHistory)?;
+                Action::Raise(self.amount(to)?)
+            }
+            "completes it to" => Action::Complete,
+            // Mucks, sitting out and the like
+            _ => return Ok(()),
+        };
+        self.events.push(Event::Action { seat, action });
+        Ok(())
+    }
+
+    // Seat of the player `line` starts with, followed by `separator`, and
+    // the rest of the line. Longest names first in case one is a prefix of another.
+    fn player<'l>(&self, line: &'l str, separator: &str) -> Result<(usize, &'l str), Error> {
+        self.players
+            .iter()
+            .enumerate()
+            .filter_map(|(seat, name)| {
+                let rest = line.strip_prefix(name.as_deref()?)?;
+                if separator.is_empty() {
+                    return rest
+                        .is_empty()
+                        .then_some((seat, rest, name.as_ref()?.len()));
+                }
+                Some((seat, rest.strip_prefix(separator)?, name.as_ref()?.len()))
+            })
+            .max_by_key(|&(_, _, len)| len)
+            .map(|(seat, rest, _)| (seat, rest))
+            .ok_or(Error::InvalidHandHistory)
+    }
+
+    fn amount(&self, s: &str) -> Result<u64, Error> {
+        amount(s, self.cents)
+    }
+
+    // Hi/lo collect lines do not say which half they are for. The best low
+    // shown takes the low half: the second award of a player who also wins
+    // the high, or the only one when someone else collects the high.
+    fn low_awards(&mut self) {
+        let best = match self.lows.iter().flatten().min() {
+            Some(best) => best.clone(),
+            None => return,
+        };
+        let low: Vec<bool> = self
+            .lows
+            .iter()
+            .map(|low| low.as_ref() == Some(&best))
+            .collect();
+        let mut awards = vec![0; low.len()];
+        for event in &self.events {
+            if let Event::Award { seat, .. } = event {
+                awards[*seat] += 1;
+            }
+        }
+        let high_elsewhere = (0..low.len()).any(|seat| !low[seat] && awards[seat] > 0);
+        let mut seen = vec![0; low.len()];
+        for event in &mut self.events {
+            if let Event::Award {
+                seat, low: half, ..
+            } = event
+            {
+                seen[*seat] += 1;
+                *half = low[*seat] && (seen[*seat] == 2 || (awards[*seat] == 1 && high_elsewhere));
+            }
+        }
+    }
+}
+
+// Ranks of the low in "(HI: a pair of Kings; LO: 8,5,3,2,A)", none without one
+fn low(shown: &str) -> Result<Option<Vec<u8>>, Error> {
+    let low = match shown.split_once("LO: ") {
+        Some((_, low)) => low,
+        None => return Ok(None),
+    };
+    low.split([')', ';'])
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .map(|rank| {
+            let mut chars = rank.trim().chars();
+            match (chars.next(), chars.next()) {
+                (Some('A'), None) => Ok(1),
+                (Some(c), None) => Ok(Rank::try_from(c)? as u8 + 2),
+                _ => Err(Error::InvalidHandHistory),
+            }
+        })
+        .collect::<Result<Vec<u8>, Error>>()
+        .map(Some)
+}
+
+// Known verbs and the rest of a player's line, e.g. "raises" and "$2 to $4"
+fn split_verb(line: &str) -> (&str, &str) {
+    const VERBS: [&str; 7] = [
+        "posts the ante",
+        "posts small & big blinds",
+        "posts small blind",
+        "posts big blind",
+        "brings in for",
+        "completes it to",
+        "stands pat",
+    ];
+    for verb in VERBS {
+        if let Some(rest) = line.strip_prefix(verb) {
+            return (verb, rest.trim());
+        }
+    }
+    let (verb, rest) = line.split_once(' ').unwrap_or((line, ""));
+    (verb, rest.trim())
+}
+
+// Every "[...]" group as a hand
+fn brackets(s: &str) -> Result<Vec<Hand>, Error> {
+    s.split('[')
+        .skip(1)
+        .map(|group| {
+            let (cards, _) = group.split_once(']').ok_or(Error::InvalidHandHistory)?;
+            Hand::try_from(cards)
+        })
+        .collect()
+}
+
+// "$1.05" as 105 cents, or "1500" as chips
+fn amount(s: &str, cents: bool) -> Result<u64, Error> {
+    let s = s
+        .trim()
+        .trim_start_matches(['$', '€', '£'])
+        .replace(',', "");
+    if !cents {
+        return number(&s);
+    }
+    let (whole, fraction) = s.split_once('.').unwrap_or((&s, ""));
+    if fraction.len() > 2 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(Error::InvalidHandHistory);
+    }
+    let fraction = format!("{:0<2}", fraction);
+    Ok(number::<u64>(whole)? * 100 + number::<u64>(&fraction)?)
+}
+
+fn number<T: std::str::FromStr>(s: &str) -> Result<T, Error> {
+    s.trim().parse().map_err(|_| Error::InvalidHandHistory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOLDEM: &str = "\
+PokerStars Hand #210001: Hold'em No Limit ($0.01/$0.02 USD) - 2020/01/01 12:00:00 ET
+Table 'Alpha' 6-max Seat #1 is the button
+Seat 1: Alice ($2 in chips)
+Seat 3: Bob ($1.50 in chips)
+Seat 4: Bob Jr ($2.13 in chips)
+Seat 6: Carol ($3 in chips) is sitting out
+Bob: posts small blind $0.01
+Bob Jr: posts big blind $0.02
+*** HOLE CARDS ***
+Dealt to Alice [Ah Kd]
+Alice: raises $0.04 to $0.06
+Bob: folds
+Bob Jr: calls $0.04
+*** FLOP *** [2c 3d 4h]
+Bob Jr: checks
+Alice: bets $0.08
+Bob Jr: raises $0.10 to $0.18
+Alice: calls $0.10
+*** TURN *** [2c 3d 4h] [5s]
+Bob Jr: bets $1.89 and is all-in
+Alice: folds
+Uncalled bet ($1.89) returned to Bob Jr
+Bob Jr collected $0.49 from pot
+Bob Jr: doesn't show hand
+*** SUMMARY ***
+Total pot $0.49 | Rake $0
+Seat 1: Alice (button) folded on the Turn
+";
+
+    const RAZZ: &str = "\
+PokerStars Hand #210002: Razz Limit ($0.04/$0.08 USD) - 2020/01/01 12:05:00 ET
+Table 'Beta' 8-max Seat #1 is the button
+Seat 1: Alice ($1 in chips)
+Seat 2: Bob ($1 in chips)
+Alice: posts the ante $0.01
+Bob: posts the ante $0.01
+*** 3rd STREET ***
+Dealt to Alice [Ac 2d 3h]
+Dealt to Bob [Ks]
+Bob: brings in for $0.02
+Alice: completes it to $0.04
+Bob: calls $0.02
+*** 4th STREET ***
+Dealt to Alice [Ac 2d 3h] [4c]
+Dealt to Bob [Ks] [Qs]
+Alice: bets $0.04
+Bob: calls $0.04
+*** 5th STREET ***
+Dealt to Alice [Ac 2d 3h 4c] [5c]
+Dealt to Bob [Ks Qs] [Js]
+Alice: bets $0.08
+Bob: calls $0.08
+*** 6th STREET ***
+Dealt to Alice [Ac 2d 3h 4c 5c] [9h]
+Dealt to Bob [Ks Qs Js] [Ts]
+Alice: checks
+Bob: checks
+*** RIVER ***
+Dealt to Alice [Ac 2d 3h 4c 5c 9h] [Kh]
+Alice: checks
+Bob: checks
+*** SHOW DOWN ***
+Alice: shows [Ac 2d 3h 4c 5c 9h Kh] (Lo: 5,4,3,2,A)
+Bob: mucks hand
+Alice collected $0.34 from pot
+*** SUMMARY ***
+";
+
+    const TRIPLE_DRAW: &str = "\
+PokerStars Hand #210003: Triple Draw 2-7 Lowball Limit (50/100) - 2020/01/01 12:10:00 ET
+Table 'Gamma' 6-max Seat #2 is the button
+Seat 1: Alice (1000 in chips)
+Seat 2: Bob (1000 in chips)
+Bob: posts small blind 25
+Alice: posts big blind 50
+*** DEALING HANDS ***
+Dealt to Alice [2c 3d 4h Kc Kd]
+Bob: calls 25
+Alice: checks
+*** FIRST DRAW ***
+Alice: discards 2 cards [Kc Kd]
+Dealt to Alice [2c 3d 4h] [7s 8h]
+Bob: stands pat
+Alice: checks
+Bob: checks
+*** SECOND DRAW ***
+Alice: stands pat
+Bob: discards 1 card
+Alice: checks
+Bob: checks
+*** THIRD DRAW ***
+Alice: stands pat
+Bob: stands pat
+Alice: checks
+Bob: checks
+*** SHOW DOWN ***
+Alice: shows [2c 3d 4h 7s 8h] (Lo: 8,7,4,3,2)
+Bob: shows [2d 3c 4s 5d 9c] (Lo: 9,5,4,3,2)
+Alice collected 100 from pot
+*** SUMMARY ***
+";
+
+    const OMAHA_HI_LO: &str = "\
+PokerStars Hand #210004: Omaha Hi/Lo Limit ($0.10/$0.20 USD) - 2020/01/01 12:15:00 ET
+Table 'Delta' 6-max Seat #1 is the button
+Seat 1: Alice ($5 in chips)
+Seat 2: Bob ($5 in chips)
+Seat 3: Carol ($5 in chips)
+Bob: posts small blind $0.05
+Carol: posts big blind $0.10
+*** HOLE CARDS ***
+Dealt to Alice [Ah 2h Kd Qc]
+Alice: calls $0.10
+Bob: calls $0.05
+Carol: checks
+*** FLOP *** [3c 5d Kh]
+Bob: checks
+Carol: checks
+Alice: checks
+*** TURN *** [3c 5d Kh] [8s]
+Bob: checks
+Carol: checks
+Alice: checks
+*** RIVER *** [3c 5d Kh 8s] [Js]
+Bob: checks
+Carol: checks
+Alice: checks
+*** SHOW DOWN ***
+Bob: shows [Ks Kc 9d 9c] (HI: three of a kind, Kings)
+Carol: shows [As 4d Qh Qs] (HI: a pair of Queens; LO: 8,5,4,3,A)
+Alice: shows [Ah 2h Kd Qc] (HI: a pair of Kings; LO: 5,3,2,A)
+Bob collected $0.15 from pot
+Alice collected $0.15 from pot
+*** SUMMARY ***
+";
+
+    #[test]
+    fn test_holdem() {
+        let hand = PokerStarsHand::try_from(HOLDEM).unwrap();
+        assert_eq!(hand.id, "210001");
+        assert_eq!(hand.players.len(), 6);
+        assert_eq!(hand.players[3].as_deref(), Some("Bob Jr"));
+        let history = &hand.history;
+        assert_eq!(history.variant, Variant::Holdem);
+        assert_eq!(history.limit, Limit::NoLimit);
+        assert_eq!(history.stakes, Stakes::new(1, 2));
+        assert_eq!(history.button, 0);
+        assert_eq!(history.stacks, vec![200, 0, 150, 213, 0, 0]);
+        assert_eq!(
+            history.events[..4],
+            [
+                Event::Blind { seat: 2, amount: 1 },
+                Event::Blind { seat: 3, amount: 2 },
+                Event::Street(Street::Preflop),
+                Event::Dealt {
+                    seat: 0,
+                    cards: Hand::try_from("Ah Kd").unwrap(),
+                    face_up: false
+                },
+            ]
+        );
+        assert!(history.events.contains(&Event::Action {
+            seat: 3,
+            action: Action::Raise(18)
+        }));
+        assert!(history
+            .events
+            .contains(&Event::Board(Hand::try_from("5s").unwrap())));
+        assert_eq!(
+            history.events[history.events.len() - 2..],
+            [
+                Event::Returned {
+                    seat: 3,
+                    amount: 189
+                },
+                Event::Award {
+                    seat: 3,
+                    amount: 49,
+                    low: false
+                },
+            ]
+        );
+
+        // Round trip through the crate's own format
+        let text = history.to_string();
+        assert_eq!(&HandHistory::try_from(text.as_str()).unwrap(), history);
+    }
+
+    #[test]
+    fn test_razz() {
+        let history = PokerStarsHand::try_from(RAZZ).unwrap().history;
+        assert_eq!(history.variant, Variant::Razz);
+        assert_eq!(history.stakes, Stakes::stud(1, 2, 4, 8));
+        let dealt: Vec<(usize, String, bool)> = history
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Dealt {
+                    seat,
+                    cards,
+                    face_up,
+                } => Some((*seat, cards.to_string(), *face_up)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(dealt[0], (0, "Ac 2d".to_string(), false));
+        assert_eq!(dealt[1], (0, "3h".to_string(), true));
+        assert_eq!(dealt[2], (1, "Ks".to_string(), true));
+        assert_eq!(dealt.last(), Some(&(0, "Kh".to_string(), false)));
+        assert!(history.events.contains(&Event::Street(Street::Seventh)));
+        assert!(history.events.contains(&Event::Action {
+            seat: 0,
+            action: Action::Complete
+        }));
+    }
+
+    #[test]
+    fn test_triple_draw() {
+        let history = PokerStarsHand::try_from(TRIPLE_DRAW).unwrap().history;
+        assert_eq!(history.variant, Variant::DeuceToSevenTripleDraw);
+        assert_eq!(history.stakes.small_blind, 25);
+        assert_eq!(history.stakes.big_bet, 100);
+        assert_eq!(history.stacks, vec![1000, 1000, 0, 0, 0, 0]);
+        assert!(history.events.contains(&Event::Draw {
+            seat: 0,
+            discarded: Hand::try_from("Kc Kd").unwrap(),
+            drawn: Hand::try_from("7s 8h").unwrap(),
+        }));
+        let draws = history
+            .events
+            .iter()
+            .filter(|event| matches!(event, Event::Draw { .. }))
+            .count();
+        // Bob's unseen one card draw is left out
+        assert_eq!(draws, 5);
+    }
+
+    #[test]
+    fn test_omaha_hi_lo() {
+        let history = PokerStarsHand::try_from(OMAHA_HI_LO).unwrap().history;
+        assert_eq!(history.variant, Variant::OmahaHiLo);
+        assert_eq!(history.limit, Limit::FixedLimit);
+        assert_eq!(
+            history.events[history.events.len() - 2..],
+            [
+                Event::Award {
+                    seat: 1,
+                    amount: 15,
+                    low: false
+                },
+                Event::Award {
+                    seat: 0,
+                    amount: 15,
+                    low: true
+                },
+            ]
+        );
+
+        // Alice scoops with the best high and low, the second award being the low
+        let scoop = OMAHA_HI_LO
+            .replace("Ks Kc 9d 9c", "Ks Tc 9d 9c")
+            .replace("HI: three of a kind, Kings", "HI: a pair of Nines")
+            .replace("Bob collected", "Alice collected");
+        let history = PokerStarsHand::try_from(scoop.as_str()).unwrap().history;
+        let awards: Vec<(usize, bool)> = history
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Award { seat, low, .. } => Some((*seat, *low)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(awards, vec![(0, false), (0, true)]);
+        assert_eq!(low("(Lo: 5,4,3,2,A)"), Ok(None));
+        assert_eq!(low("(LO: 8,x,3,2,A)"), Err(Error::InvalidCardNotation));
+        assert_eq!(low("(LO: 8,54,3,2,A)"), Err(Error::InvalidHandHistory));
+    }
+
+    #[test]
+    fn test_parse_file_reports_errors_per_hand() {
+        let broken = HOLDEM.replace("[Ah Kd]", "[Ah Kx]");
+        let unsupported = HOLDEM.replace("Hold'em No Limit", "5 Card Omaha Hi/Lo Pot Limit");
+        let file = format!(
+            "\u{feff}{}\n\n{}\n{}\n\n{}",
+            HOLDEM, broken, unsupported, RAZZ
+        );
+        let hands = parse_file(&file);
+        assert_eq!(hands.len(), 4);
+        assert!(hands[0].is_ok());
+        assert_eq!(hands[1], Err(Error::InvalidCardNotation));
+        assert_eq!(hands[2], Err(Error::UnsupportedGame));
+        assert!(hands[3].is_ok());
+    }
+
+    #[test]
+    fn test_game() {
+        assert_eq!(
+            game("Omaha Pot Limit"),
+            Ok((Variant::Omaha, Limit::PotLimit))
+        );
+        assert_eq!(
+            game("5 Card Omaha Pot Limit"),
+            Ok((Variant::FiveCardOmaha, Limit::PotLimit))
+        );
+        assert_eq!(
+            game("Omaha Hi/Lo Pot Limit"),
+            Ok((Variant::OmahaHiLo, Limit::PotLimit))
+        );
+        assert_eq!(
+            game("7 Card Stud Hi/Lo Limit"),
+            Ok((Variant::StudHiLo, Limit::FixedLimit))
+        );
+        assert_eq!(
+            game("5 Card Omaha Hi/Lo Pot Limit"),
+            Err(Error::UnsupportedGame)
+        );
+        assert_eq!(game("Courchevel Pot Limit"), Err(Error::UnsupportedGame));
+    }
+
+    #[test]
+    fn test_amount() {
+        assert_eq!(amount("$1.05", true), Ok(105));
+        assert_eq!(amount("$2", true), Ok(200));
+        assert_eq!(amount("€0.5", true), Ok(50));
+        assert_eq!(amount("1,500", false), Ok(1500));
+        assert_eq!(amount("$0.005", true), Err(Error::InvalidHandHistory));
+    }
+}
@@ -0,0 +1,185 @@
+// Suit isomorphism: hands that only differ by a relabeling of the suits play
+// the same, e.g. AsKs and AhKh, so they can share one entry in a table
+
+use crate::card::{mask, Card, OrderFirstBy, Suit};
+use crate::error::Error;
+use crate::hand::Hand;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canonical {
+    // Cards from the highest down, after relabeling
+    pub hand: Hand,
+    pub board: Hand,
+    // Canonical suit of every suit, indexed by `Suit as usize`
+    pub suits: [Suit; 4],
+    // Same for every isomorphic hand and board, below `C(52, hand) * C(52, board)`
+    pub index: u64,
+    // Number of distinct hand and board pairs isomorphic to this one
+    pub variants: usize,
+}
+
+// Relabel the suits of `hand` and `board` into the canonical form, the one
+// with the lowest cards by `ord_position` ordered by rank
+pub fn canonicalize(hand: &Hand, board: &Hand) -> Result<Canonical, Error> {
+    let cards: Vec<Card> = hand.cards.iter().chain(&board.cards).copied().collect();
+    if mask(&cards).count_ones() as usize != cards.len() {
+        return Err(Error::InvalidHand);
+    }
+
+    let mut best: Option<(Vec<u8>, [Suit; 4])> = None;
+    let mut images: HashSet<Vec<u8>> = HashSet::new();
+    for suits in permutations() {
+        let key = key(
+            &relabel(&hand.cards, &suits),
+            &relabel(&board.cards, &suits),
+        );
+        if best.as_ref().is_none_or(|(best, _)| key < *best) {
+            best = Some((key.clone(), suits));
+        }
+        images.insert(key);
+    }
+
+    let (_, suits) = best.unwrap_or((Vec::new(), [Suit::Spades; 4]));
+    let hand = sorted(relabel(&hand.cards, &suits));
+    let board = sorted(relabel(&board.cards, &suits));
+    let index = hand.to_index()? * Hand::index_count(board.cards.len()) + board.to_index()?;
+    Ok(Canonical {
+        hand,
+        board,
+        suits,
+        index,
+        variants: images.len(),
+    })
+}
+
+// All 24 relabelings of the suits
+fn permutations() -> Vec<[Suit; 4]> {
+    let suits = Suit::iter();
+    let mut permutations: Vec<[Suit; 4]> = Vec::new();
+    for &a in suits {
+        for &b in suits.iter().filter(|&&b| b != a) {
+            for &c in suits.iter().filter(|&&c| c != a && c != b) {
+                for &d in suits.iter().filter(|&&d| d != a && d != b && d != c) {
+                    // Indexed by the original suit
+                    let mut permutation = [a; 4];
+                    for (from, to) in suits.iter().zip([a, b, c, d]) {
+                        permutation[*from as usize] = to;
+                    }
+                    permutations.push(permutation);
+                }
+            }
+        }
+    }
+    permutations
+}
+
+fn relabel(cards: &[Card], suits: &[Suit; 4]) -> Vec<Card> {
+    cards
+        .iter()
+        .map(|card| Card::new(suits[card.suit as usize], card.rank))
+        .collect()
+}
+
+fn sorted(mut cards: Vec<Card>) -> Hand {
+    cards.sort_by(|a, b| b.cmp(a));
+    Hand::from(cards)
+}
+
+// Hand then board positions, each from the highest down, as sets of cards
+fn key(hand: &[Card], board: &[Card]) -> Vec<u8> {
+    let positions = |cards: &[Card]| {
+        let mut positions: Vec<u8> = cards
+            .iter()
+            .map(|card| card.ord_position(OrderFirstBy::Rank))
+            .collect();
+        positions.sort_by(|a, b| b.cmp(a));
+        positions
+    };
+    let mut key = positions(hand);
+    key.push(u8::MAX);
+    key.extend(positions(board));
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(hand: &str, board: &str) -> Canonical {
+        canonicalize(
+            &Hand::try_from(hand).unwrap(),
+            &Hand::try_from(board).unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_permutations() {
+        let permutations = permutations();
+        assert_eq!(permutations.len(), 24);
+        let unique: HashSet<[u8; 4]> = permutations
+            .iter()
+            .map(|p| p.map(|suit| suit as u8))
+            .collect();
+        assert_eq!(unique.len(), 24);
+    }
+
+    #[test]
+    fn test_preflop_classes() {
+        let suited = canonical("Kh Ah", "");
+        assert_eq!(suited.hand.to_string(), "As Ks");
+        assert_eq!(suited.variants, 4);
+        assert_eq!(canonical("Ac Kc", "").index, suited.index);
+
+        let offsuit = canonical("Ad Kc", "");
+        assert_eq!(offsuit.variants, 12);
+        assert_ne!(offsuit.index, suited.index);
+        assert_eq!(canonical("Ah Ks", "").index, offsuit.index);
+
+        assert_eq!(canonical("Ac Ad", "").variants, 6);
+    }
+
+    #[test]
+    fn test_with_board() {
+        let flush_draw = canonical("Ah Kh", "2h 7h 9c");
+        assert_eq!(flush_draw.hand.to_string(), "As Ks");
+        assert_eq!(flush_draw.board.to_string(), "9h 7s 2s");
+        assert_eq!(flush_draw.variants, 12);
+        assert_eq!(canonical("Ad Kd", "9s 2d 7d").index, flush_draw.index);
+        // Hand and board are told apart
+        assert_ne!(canonical("Ad Kd", "9s 2s 7d").index, flush_draw.index);
+
+        let rainbow = canonical("Ah Kd", "2c 7s");
+        assert_eq!(rainbow.variants, 24);
+        // Relabeling maps every original suit to a distinct one
+        let relabeled: HashSet<u8> = rainbow.suits.iter().map(|&suit| suit as u8).collect();
+        assert_eq!(relabeled.len(), 4);
+    }
+
+    #[test]
+    fn test_duplicate_cards() {
+        let hand = Hand::try_from("Ah Kh").unwrap();
+        let board = Hand::try_from("Kh 7c 2d").unwrap();
+        assert_eq!(canonicalize(&hand, &board), Err(Error::InvalidHand));
+    }
+
+    #[test]
+    fn test_class_counts() {
+        // 1326 starting hands fall into the 169 preflop classes
+        let deck = crate::deck::Deck::new().cards;
+        let mut classes: HashSet<u64> = HashSet::new();
+        let mut total = 0;
+        for i in 0..deck.len() {
+            for j in i + 1..deck.len() {
+                let hand = Hand::from(vec![deck[i], deck[j]]);
+                let canonical = canonicalize(&hand, &Hand::new()).unwrap();
+                if classes.insert(canonical.index) {
+                    total += canonical.variants;
+                }
+            }
+        }
+        assert_eq!(classes.len(), 169);
+        assert_eq!(total, 1326);
+    }
+}
@@ -1,5 +1,15 @@
+pub mod blockers;
 pub mod card;
 pub mod deck;
+pub mod discard;
+pub mod equity;
 pub mod error;
 pub mod evaluation;
+pub mod game;
 pub mod hand;
+pub mod history;
+pub mod isomorphism;
+pub mod nuts;
+pub mod outs;
+pub mod range;
+pub mod texture;
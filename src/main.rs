@@ -1,4 +1,6 @@
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+
+mod commands;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -12,11 +14,38 @@ enum Commands {
     // TODO: Support different types,
     // for the time being, generate all
     /// Generate lookup for speedy evaluation
-    Generate {}
+    Generate {},
+
+    /// Score hands, e.g. eval --game a5-lowball "Ac 2d 3h 4s 5c" "2c 3d 4h 5s 6c"
+    Eval(commands::eval::EvalArgs),
+
+    /// Equity of hands or ranges, e.g. equity --board "Ah 7d 2c" "QQ+, AKs" JTs
+    Equity(commands::equity::EquityArgs),
+
+    /// Deal a hand from a seeded shuffle, e.g. deal --game stud --players 6 --seed 42
+    Deal(commands::deal::DealArgs),
+
+    /// Play dealer's choice against bots, e.g. play --bots 3 --games holdem,razz,badugi
+    Play(commands::play::PlayArgs),
 }
 
 fn main() {
     let cli = Cli::parse();
 
-    dbg!(cli);
+    let result = match &cli.command {
+        Some(Commands::Eval(args)) => {
+            commands::eval::run(args, std::io::stdin().lock(), &mut std::io::stdout().lock())
+        }
+        Some(Commands::Equity(args)) => commands::equity::run(args, &mut std::io::stdout().lock()),
+        Some(Commands::Deal(args)) => commands::deal::run(args, &mut std::io::stdout().lock()),
+        Some(Commands::Play(args)) => {
+            commands::play::run(args, std::io::stdin().lock(), &mut std::io::stdout().lock())
+        }
+        Some(Commands::Generate {}) => Err("generate is not implemented yet".to_string()),
+        None => Cli::command().print_help().map_err(|e| e.to_string()),
+    };
+    if let Err(message) = result {
+        eprintln!("error: {}", message);
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,166 @@
+// The nuts on a board: the best hands any hole cards can make in Hold'em and
+// Omaha, high or 8 or better low, and the next best hands down from them
+
+use crate::card::{mask, Card};
+use crate::deck::Deck;
+use crate::error::Error;
+use crate::evaluation::eight_or_better::EightOrBetter;
+use crate::evaluation::high::High;
+use crate::evaluation::{EvalHand, Evaluation};
+use crate::game::{Gameplay, Variant};
+use crate::hand::Hand;
+use std::cmp::Reverse;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Half {
+    High,
+    // 8 or better low, in hi-lo only
+    Low,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Holding {
+    pub score: u128,
+    // Hand category in words, e.g. "Straight flush" or "7 low"
+    pub description: String,
+    // Every two hole cards making it, highest card first. In Omaha these are
+    // the two cards played, whatever the other hole cards are.
+    pub combos: Vec<Hand>,
+}
+
+// The best hand possible on a flop, turn or river, none when no 8 or better
+// low is possible
+pub fn nuts(variant: Variant, board: &Hand, half: Half) -> Result<Option<Holding>, Error> {
+    Ok(ranking(variant, board, half, 1)?.into_iter().next())
+}
+
+// The `top` best hands possible on the board, best first, each with every
+// combo making it. Hands not qualifying for the low are left out.
+pub fn ranking(
+    variant: Variant,
+    board: &Hand,
+    half: Half,
+    top: usize,
+) -> Result<Vec<Holding>, Error> {
+    if variant.gameplay() != Gameplay::Community {
+        return Err(Error::UnsupportedGame);
+    }
+    if !(3..=5).contains(&board.cards.len())
+        || mask(&board.cards).count_ones() as usize != board.cards.len()
+    {
+        return Err(Error::InvalidHand);
+    }
+    let omaha = variant != Variant::Holdem;
+    match half {
+        Half::High => ranking_with::<High>(omaha, board, top),
+        Half::Low if variant.is_split_pot() => ranking_with::<EightOrBetter>(omaha, board, top),
+        Half::Low => Err(Error::UnsupportedGame),
+    }
+}
+
+fn ranking_with<T: EvalHand>(omaha: bool, board: &Hand, top: usize) -> Result<Vec<Holding>, Error> {
+    let mut scored: Vec<(u128, Hand)> = Vec::new();
+    for mut combo in Deck::new().combinations_excluding(2, &board.cards) {
+        let score = if omaha {
+            Evaluation::<T>::best_omaha(&combo.cards, &board.cards)?.score
+        } else {
+            let all: Vec<Card> = [&combo.cards[..], &board.cards[..]].concat();
+            Evaluation::<T>::best_of(&all, 5)?.score
+        };
+        // Scores of 0 do not qualify
+        if score > 0 {
+            combo.cards.sort_by(|a, b| b.cmp(a));
+            scored.push((score, combo));
+        }
+    }
+    scored.sort_by_key(|&(score, _)| Reverse(score));
+
+    let mut holdings: Vec<Holding> = Vec::new();
+    for (score, combo) in scored {
+        let len = holdings.len();
+        match holdings.last_mut() {
+            Some(holding) if holding.score == score => holding.combos.push(combo),
+            _ if len == top => break,
+            _ => holdings.push(Holding {
+                score,
+                description: T::describe(score),
+                combos: vec![combo],
+            }),
+        }
+    }
+    Ok(holdings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::hand;
+
+    #[test]
+    fn test_holdem_ranking() {
+        let ranking = ranking(Variant::Holdem, &hand("Ks Qd 7h 4c 2s"), Half::High, 6).unwrap();
+        let sizes: Vec<usize> = ranking.iter().map(|holding| holding.combos.len()).collect();
+        // Sets from the top down, then kings and queens
+        assert_eq!(sizes, vec![3, 3, 3, 3, 3, 9]);
+        assert_eq!(ranking[0].description, "Three of a kind");
+        assert!(ranking[0].combos.iter().all(|combo| combo
+            .cards
+            .iter()
+            .all(|card| card.rank == crate::card::Rank::King)));
+        assert_eq!(ranking[5].description, "Two pairs");
+    }
+
+    #[test]
+    fn test_two_card_rule() {
+        let board = hand("Ah Kh Qh Jh 2c");
+        // Any ten of hearts makes a royal flush in Hold'em
+        let holdem = nuts(Variant::Holdem, &board, Half::High).unwrap().unwrap();
+        assert_eq!(holdem.description, "Straight flush");
+        assert_eq!(holdem.combos.len(), 46);
+
+        // Omaha plays three board cards, so the best is a king-high straight flush
+        let omaha = nuts(Variant::Omaha, &board, Half::High).unwrap().unwrap();
+        assert_eq!(omaha.combos, vec![hand("Th 9h")]);
+        assert!(omaha.score < holdem.score);
+    }
+
+    #[test]
+    fn test_low() {
+        let board = hand("2h 7h 9h Jh 3c");
+        let low = nuts(Variant::OmahaHiLo, &board, Half::Low)
+            .unwrap()
+            .unwrap();
+        // Any ace with any four
+        assert_eq!(low.combos.len(), 16);
+        assert_eq!(
+            low.score,
+            EightOrBetter::eval_hand(&hand("7h 4c 3c 2h Ac")).unwrap()
+        );
+        let ranking = ranking(Variant::BigO, &hand("2h 7h 5c"), Half::Low, 3).unwrap();
+        assert_eq!(ranking.len(), 3);
+        assert!(ranking.windows(2).all(|pair| pair[0].score > pair[1].score));
+
+        // No low with two low cards on board
+        assert_eq!(
+            nuts(Variant::OmahaHiLo, &hand("Kh Qd 9c 2s 3c"), Half::Low),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let board = hand("2h 7h 9h");
+        assert_eq!(
+            nuts(Variant::Holdem, &board, Half::Low),
+            Err(Error::UnsupportedGame)
+        );
+        assert_eq!(
+            nuts(Variant::Razz, &board, Half::High),
+            Err(Error::UnsupportedGame)
+        );
+        assert_eq!(
+            nuts(Variant::Omaha, &hand("2h 7h"), Half::High),
+            Err(Error::InvalidHand)
+        );
+    }
+}
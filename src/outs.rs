@@ -0,0 +1,564 @@
+// Outs to a target hand, e.g. a flush or an 8-low, and the chance of making
+// it by the river, seventh street or the last draw
+
+use crate::card::{mask, Card, Rank, Suit};
+use crate::deck::{Combinations, Deck, CHUNKS};
+use crate::error::Error;
+use crate::evaluation::badugi::Badugi;
+use crate::evaluation::high::{Category, High};
+use crate::evaluation::lowball_2to7::Lowball2to7;
+use crate::evaluation::lowball_ato5::LowballAto5;
+use crate::evaluation::{EvalHand, Evaluation};
+use crate::game::table::showdown_scores;
+use crate::game::{Gameplay, Variant};
+use crate::hand::Hand;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    // A high hand of this category or better, e.g. a flush or better
+    High(Category),
+    // An unpaired low with this card or lower on top, e.g. an 8-low. Aces
+    // are low except in deuce to seven games.
+    Low(Rank),
+    // A badugi hand of at least this many cards, 4 for a badugi
+    Badugi(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outs {
+    // Unseen cards that make the target as the next card, e.g. the turn.
+    // Empty when one more card does not make a complete hand.
+    pub outs: Vec<Card>,
+    // Unseen cards, which the next card is one of
+    pub unseen: usize,
+    // Chance of making the target with the next card, or the next draw
+    pub next: f64,
+    // Chance of making it by the river, seventh street or the last draw
+    pub by_end: f64,
+}
+
+// Outs in community card and stud games. `cards` are the player's own, down
+// and up, and `board` the community cards so far. Every way the remaining
+// cards can come is enumerated.
+pub fn outs(
+    variant: Variant,
+    cards: &Hand,
+    board: &Hand,
+    dead: &[Card],
+    target: Target,
+) -> Result<Outs, Error> {
+    let check = Check::new(variant, target)?;
+    let unseen = unseen(&[&cards.cards[..], &board.cards[..], dead].concat())?;
+    // Cards still to come to the player or to the board
+    let seen = cards.cards.len() + board.cards.len();
+    let (to_player, to_board) = match variant.gameplay() {
+        Gameplay::Community
+            if cards.cards.len() == variant.hole_cards() && board.cards.len() <= 5 =>
+        {
+            (0, 5 - board.cards.len())
+        }
+        Gameplay::Stud if seen <= 7 => (7 - seen, 0),
+        Gameplay::Draw => return Err(Error::UnsupportedGame),
+        _ => return Err(Error::InvalidHand),
+    };
+    let deal = |extra: &[Card]| -> Result<bool, Error> {
+        let own = Hand::from([&cards.cards[..], &extra[..to_player]].concat());
+        let board = Hand::from([&board.cards[..], &extra[to_player..]].concat());
+        check.made(&own, &board)
+    };
+
+    let remaining = to_player + to_board;
+    if remaining == 0 {
+        let made = deal(&[])?;
+        return Ok(Outs {
+            outs: Vec::new(),
+            unseen: unseen.len(),
+            next: 0.0,
+            by_end: f64::from(u8::from(made)),
+        });
+    }
+
+    // The next card alone, when it completes a hand
+    let mut outs: Vec<Card> = Vec::new();
+    let next_player = usize::from(to_player > 0);
+    let complete = match variant.gameplay() {
+        Gameplay::Stud => seen + 1 >= 5,
+        _ => board.cards.len() + 1 >= 3,
+    };
+    if complete {
+        for &card in &unseen {
+            let own = Hand::from([&cards.cards[..], &[card][..next_player]].concat());
+            let next = Hand::from([&board.cards[..], &[card][next_player..]].concat());
+            if check.made(&own, &next)? {
+                outs.push(card);
+            }
+        }
+    }
+
+    let counts = Combinations::new(&unseen, remaining).map_chunks(CHUNKS, |mut extras| {
+        let mut hits: u64 = 0;
+        let mut total: u64 = 0;
+        while let Some(extra) = extras.next_cards() {
+            hits += u64::from(deal(extra)?);
+            total += 1;
+        }
+        Ok((hits, total))
+    });
+    let (mut hits, mut total) = (0, 0);
+    for count in counts {
+        let (chunk_hits, chunk_total) = count?;
+        hits += chunk_hits;
+        total += chunk_total;
+    }
+    Ok(Outs {
+        next: outs.len() as f64 / unseen.len() as f64,
+        outs,
+        unseen: unseen.len(),
+        by_end: hits as f64 / total as f64,
+    })
+}
+
+// Outs in draw games drawing to `kept` with `draws` draws left, keeping
+// `kept` to the end. The chance of the next draw enumerates every
+// replacement. With one card missing, misses are thrown again and the chance
+// by the last draw is exact. With more missing, every draw is enumerated and
+// after a miss the drawn cards giving the best chance are kept, e.g. a new
+// suit towards a badugi. Cards thrown in earlier draws are then counted as
+// unseen again.
+pub fn draw_outs(
+    variant: Variant,
+    kept: &Hand,
+    dead: &[Card],
+    target: Target,
+    draws: u8,
+) -> Result<Outs, Error> {
+    let check = Check::new(variant, target)?;
+    let unseen = unseen(&[&kept.cards[..], dead].concat())?;
+    if variant.gameplay() != Gameplay::Draw {
+        return Err(Error::UnsupportedGame);
+    }
+    let missing = variant
+        .hole_cards()
+        .checked_sub(kept.cards.len())
+        .ok_or(Error::InvalidHand)?;
+    if draws == 0 || missing > unseen.len() {
+        return Err(Error::InvalidSetup);
+    }
+    let deal =
+        |extra: &[Card]| check.made(&Hand::from([&kept.cards[..], extra].concat()), &Hand::new());
+    if missing == 0 {
+        let made = deal(&[])?;
+        return Ok(Outs {
+            outs: Vec::new(),
+            unseen: unseen.len(),
+            next: f64::from(u8::from(made)),
+            by_end: f64::from(u8::from(made)),
+        });
+    }
+
+    let mut hits: u64 = 0;
+    let mut total: u64 = 0;
+    let mut replacements = Combinations::new(&unseen, missing);
+    while let Some(extra) = replacements.next_cards() {
+        hits += u64::from(deal(extra)?);
+        total += 1;
+    }
+    let next = hits as f64 / total as f64;
+
+    let mut outs: Vec<Card> = Vec::new();
+    let by_end = if missing == 1 {
+        for &card in &unseen {
+            if deal(&[card])? {
+                outs.push(card);
+            }
+        }
+        // Each miss leaves one fewer card, none of them an out
+        let misses: f64 = (0..draws as usize)
+            .map(|i| {
+                let left = unseen.len().saturating_sub(i) as f64;
+                ((left - outs.len() as f64) / left).max(0.0)
+            })
+            .product();
+        1.0 - misses
+    } else {
+        let mut redraw = Redraw {
+            check: &check,
+            size: variant.hole_cards(),
+            dead: mask(dead),
+            chances: HashMap::new(),
+        };
+        redraw.chance(mask(&kept.cards), draws)?
+    };
+    Ok(Outs {
+        outs,
+        unseen: unseen.len(),
+        next,
+        by_end,
+    })
+}
+
+// Chances of making the target by the last draw from the cards held
+struct Redraw<'a> {
+    check: &'a Check,
+    size: usize,
+    dead: u64,
+    // By held cards and draws left
+    chances: HashMap<(u64, u8), f64>,
+}
+
+impl Redraw<'_> {
+    fn chance(&mut self, held: u64, draws: u8) -> Result<f64, Error> {
+        if let Some(&chance) = self.chances.get(&(held, draws)) {
+            return Ok(chance);
+        }
+        let deck: Vec<Card> = Deck::new()
+            .cards
+            .into_iter()
+            .filter(|card| mask(&[*card]) & (held | self.dead) == 0)
+            .collect();
+        let missing = self.size - held.count_ones() as usize;
+        let mut sum = 0.0;
+        let mut total: u64 = 0;
+        let mut replacements = Combinations::new(&deck, missing);
+        while let Some(drawn) = replacements.next_cards() {
+            let drawn = drawn.to_vec();
+            let hand = Hand::from_mask(held | mask(&drawn));
+            let mut best = f64::from(u8::from(self.check.made(&hand, &Hand::new())?));
+            if best < 1.0 && draws > 1 {
+                // Every part of the draw to keep, short of all of it
+                for keep in 0..(1u32 << missing) - 1 {
+                    let kept: Vec<Card> = drawn
+                        .iter()
+                        .enumerate()
+                        .filter(|&(i, _)| keep >> i & 1 == 1)
+                        .map(|(_, &card)| card)
+                        .collect();
+                    best = best.max(self.chance(held | mask(&kept), draws - 1)?);
+                }
+            }
+            sum += best;
+            total += 1;
+        }
+        let chance = sum / total as f64;
+        self.chances.insert((held, draws), chance);
+        Ok(chance)
+    }
+}
+
+// Cards not in `used`, checking no card is used twice
+fn unseen(used: &[Card]) -> Result<Vec<Card>, Error> {
+    if mask(used).count_ones() as usize != used.len() {
+        return Err(Error::InvalidHand);
+    }
+    Ok(Deck::new()
+        .cards
+        .into_iter()
+        .filter(|card| !used.contains(card))
+        .collect())
+}
+
+// A target resolved for a variant
+enum Check {
+    High(Variant, Category),
+    // Lowest score that makes it
+    AceToFive(Variant, u128),
+    DeuceToSeven(u128),
+    Badugi(Variant, u32),
+}
+
+impl Check {
+    fn new(variant: Variant, target: Target) -> Result<Check, Error> {
+        let high = !matches!(
+            variant,
+            Variant::Razz
+                | Variant::AceToFiveTripleDraw
+                | Variant::DeuceToSevenSingleDraw
+                | Variant::DeuceToSevenTripleDraw
+                | Variant::Badugi
+                | Variant::Badacey
+                | Variant::Badeucey
+        );
+        let ace_to_five = matches!(
+            variant,
+            Variant::OmahaHiLo
+                | Variant::BigO
+                | Variant::StudHiLo
+                | Variant::Razz
+                | Variant::AceToFiveTripleDraw
+                | Variant::Badacey
+        );
+        let deuce_to_seven = matches!(
+            variant,
+            Variant::DeuceToSevenSingleDraw | Variant::DeuceToSevenTripleDraw | Variant::Badeucey
+        );
+        let badugi = matches!(
+            variant,
+            Variant::Badugi | Variant::Badacey | Variant::Badeucey
+        );
+        match target {
+            Target::High(category) if high => Ok(Check::High(variant, category)),
+            Target::Low(rank) if ace_to_five => Ok(Check::AceToFive(
+                variant,
+                LowballAto5::eval_hand(&worst_low(rank, true)?)?,
+            )),
+            Target::Low(rank) if deuce_to_seven => Ok(Check::DeuceToSeven(Lowball2to7::eval_hand(
+                &worst_low(rank, false)?,
+            )?)),
+            Target::Badugi(size) if badugi => Ok(Check::Badugi(variant, size)),
+            _ => Err(Error::UnsupportedGame),
+        }
+    }
+
+    fn made(&self, cards: &Hand, board: &Hand) -> Result<bool, Error> {
+        match *self {
+            Check::High(variant, category) => {
+                let (high, _) = showdown_scores(variant, cards, board)?;
+                Ok(High::category(high) >= category)
+            }
+            Check::AceToFive(variant, threshold) => {
+                let score = match variant.gameplay() {
+                    Gameplay::Community => {
+                        Evaluation::<LowballAto5>::best_omaha(&cards.cards, &board.cards)?.score
+                    }
+                    Gameplay::Stud => {
+                        let all = [&cards.cards[..], &board.cards[..]].concat();
+                        Evaluation::<LowballAto5>::best_of(&all, 5)?.score
+                    }
+                    Gameplay::Draw => LowballAto5::eval_hand(cards)?,
+                };
+                Ok(score >= threshold)
+            }
+            Check::DeuceToSeven(threshold) => Ok(Lowball2to7::eval_hand(cards)? >= threshold),
+            Check::Badugi(variant, size) => {
+                let (badugi, _) = showdown_scores(variant, cards, board)?;
+                Ok(Badugi::size(badugi) >= size)
+            }
+        }
+    }
+}
+
+// The worst unpaired low with `top` on top, e.g. 8-7-6-5-4 for an A-5 8-low
+// and 8-7-6-5-3 in deuce to seven, where 8-7-6-5-4 is a straight
+fn worst_low(top: Rank, ace_low: bool) -> Result<Hand, Error> {
+    // Rank of a value counted from the lowest card
+    let rank = |value: u8| {
+        if !ace_low {
+            Rank::iter()[value as usize]
+        } else if value == 0 {
+            Rank::Ace
+        } else {
+            Rank::iter()[value as usize - 1]
+        }
+    };
+    let (top, offsets): (u8, [u8; 5]) = if ace_low {
+        ((top as u8 + 1) % 13, [0, 1, 2, 3, 4])
+    } else {
+        (top as u8, [0, 1, 2, 3, 5])
+    };
+    if top < offsets[4] {
+        return Err(Error::InvalidSetup);
+    }
+    // Two suits so that it is no flush
+    let cards: Vec<Card> = offsets
+        .iter()
+        .zip(Suit::iter().iter().cycle())
+        .map(|(&offset, &suit)| Card {
+            suit,
+            rank: rank(top - offset),
+        })
+        .collect();
+    Ok(Hand::from(cards))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::hand;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_worst_low() {
+        assert_eq!(
+            worst_low(Rank::Eight, true).unwrap(),
+            hand("8c 7d 6h 5s 4c")
+        );
+        assert_eq!(worst_low(Rank::Five, true).unwrap(), hand("5c 4d 3h 2s Ac"));
+        assert_eq!(
+            worst_low(Rank::Eight, false).unwrap(),
+            hand("8c 7d 6h 5s 3c")
+        );
+        assert_eq!(worst_low(Rank::Four, true), Err(Error::InvalidSetup));
+        assert_eq!(worst_low(Rank::Six, false), Err(Error::InvalidSetup));
+    }
+
+    #[test]
+    fn test_flush_draw() {
+        let outs = outs(
+            Variant::Holdem,
+            &hand("Ah Kh"),
+            &hand("2h 7h Tc"),
+            &[],
+            Target::High(Category::Flush),
+        )
+        .unwrap();
+        assert_eq!(outs.outs.len(), 9);
+        assert_eq!(outs.unseen, 47);
+        assert!(close(outs.next, 9.0 / 47.0));
+        // Missing on both the turn and the river, 38 * 37 of 47 * 46
+        assert!(close(outs.by_end, 1.0 - 703.0 / 1081.0));
+
+        // A known dead heart is one out fewer
+        let dead = outs_with_dead(&hand("3h").cards);
+        assert_eq!(dead.outs.len(), 8);
+        assert_eq!(dead.unseen, 46);
+    }
+
+    fn outs_with_dead(dead: &[Card]) -> Outs {
+        outs(
+            Variant::Holdem,
+            &hand("Ah Kh"),
+            &hand("2h 7h Tc"),
+            dead,
+            Target::High(Category::Flush),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_omaha_low_draw() {
+        let outs = outs(
+            Variant::OmahaHiLo,
+            &hand("Ac 2d Kh Ks"),
+            &hand("5s 8c Jd"),
+            &[],
+            Target::Low(Rank::Eight),
+        )
+        .unwrap();
+        // Any three, four, six or seven
+        assert_eq!(outs.outs.len(), 16);
+        assert!(outs.by_end > outs.next);
+        // Made already on the river or not at all
+        let river = super::outs(
+            Variant::OmahaHiLo,
+            &hand("Ac 2d Kh Ks"),
+            &hand("5s 8c Jd 3h Qc"),
+            &[],
+            Target::Low(Rank::Eight),
+        )
+        .unwrap();
+        assert_eq!(river.by_end, 1.0);
+    }
+
+    #[test]
+    fn test_stud_and_razz() {
+        // Three to a flush on third street with four cards to come
+        let stud = outs(
+            Variant::SevenCardStud,
+            &hand("Ah Kh 7h"),
+            &Hand::new(),
+            &[],
+            Target::High(Category::Flush),
+        )
+        .unwrap();
+        assert!(stud.outs.is_empty());
+        assert!(stud.by_end > 0.1 && stud.by_end < 0.3);
+
+        // Four to a 7-low on sixth street, one card to come
+        let razz = outs(
+            Variant::Razz,
+            &hand("Ac 2d 3h 7s Ks Kd"),
+            &Hand::new(),
+            &hand("4c 4d").cards,
+            Target::Low(Rank::Seven),
+        )
+        .unwrap();
+        // Two fours, four fives and four sixes
+        assert_eq!(razz.outs.len(), 10);
+        assert!(close(razz.next, razz.by_end));
+    }
+
+    #[test]
+    fn test_draw_outs() {
+        let low = draw_outs(
+            Variant::AceToFiveTripleDraw,
+            &hand("As 2d 3h 4c"),
+            &[],
+            Target::Low(Rank::Eight),
+            3,
+        )
+        .unwrap();
+        assert_eq!(low.outs.len(), 16);
+        assert_eq!(low.unseen, 48);
+        assert!(close(low.next, 16.0 / 48.0));
+        assert!(close(
+            low.by_end,
+            1.0 - (32.0 / 48.0) * (31.0 / 47.0) * (30.0 / 46.0)
+        ));
+
+        // Two to a badugi: 4-card badugi needs both a club and a spade
+        let badugi = draw_outs(Variant::Badugi, &hand("Ah 2d"), &[], Target::Badugi(4), 1).unwrap();
+        assert!(badugi.outs.is_empty());
+        assert!(badugi.next > 0.0 && badugi.next < 0.25);
+        assert!(close(badugi.next, badugi.by_end));
+        // With three draws a club or spade caught early is kept, which beats
+        // three fresh two-card draws
+        let three = draw_outs(Variant::Badugi, &hand("Ah 2d"), &[], Target::Badugi(4), 3).unwrap();
+        assert!(close(three.next, badugi.next));
+        assert!(three.by_end > 1.0 - (1.0 - three.next).powi(3) + 0.1);
+        assert!(three.by_end < 1.0);
+
+        // In deuce to seven the ace is high and 8-7-6-5-4 is a straight
+        let deuce = draw_outs(
+            Variant::DeuceToSevenTripleDraw,
+            &hand("8s 7d 6h 5c"),
+            &[],
+            Target::Low(Rank::Eight),
+            1,
+        )
+        .unwrap();
+        assert_eq!(deuce.outs.len(), 8);
+    }
+
+    #[test]
+    fn test_errors() {
+        let flop = hand("2h 7h Tc");
+        assert_eq!(
+            outs(
+                Variant::Holdem,
+                &hand("Ah Kh"),
+                &flop,
+                &[],
+                Target::Low(Rank::Eight)
+            ),
+            Err(Error::UnsupportedGame)
+        );
+        assert_eq!(
+            outs(
+                Variant::Holdem,
+                &hand("Ah 2h"),
+                &flop,
+                &[],
+                Target::High(Category::Flush)
+            ),
+            Err(Error::InvalidHand)
+        );
+        assert_eq!(
+            draw_outs(
+                Variant::Badugi,
+                &hand("Ah 2d"),
+                &[],
+                Target::High(Category::Flush),
+                1
+            ),
+            Err(Error::UnsupportedGame)
+        );
+        assert_eq!(
+            draw_outs(Variant::Badugi, &hand("Ah 2d"), &[], Target::Badugi(4), 0),
+            Err(Error::InvalidSetup)
+        );
+    }
+}
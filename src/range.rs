@@ -0,0 +1,588 @@
+// Hand ranges: sets of weighted hole-card combos written in range notation.
+//
+// Hold'em: "QQ+, AKs, A5s-A2s, KTo+, AK, 76s, AsKd", each term optionally
+// weighted with ":0.5".
+// Omaha: four or five ranks, "x" for any card, e.g. "AAxx" or "KQJTx",
+// optionally followed by a suit filter, "ds" (double suited), "ss" (single
+// suited) or "rb" (rainbow). A filter on its own, as in "AAxx, ds", applies
+// to the whole range.
+
+use crate::card::{mask, Card, OrderFirstBy, Rank, Suit};
+use crate::deck::{Combinations, Deck};
+use crate::error::Error;
+use crate::hand::Hand;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Combo {
+    // Cards from the highest rank down, spades, hearts, diamonds then clubs
+    // within a rank
+    pub hand: Hand,
+    // Share of the combo played, between 0 and 1
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Range {
+    combos: Vec<Combo>,
+    // Combo position by card mask
+    index: HashMap<u64, usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SuitFilter {
+    DoubleSuited,
+    SingleSuited,
+    Rainbow,
+}
+
+impl SuitFilter {
+    fn parse(s: &str) -> Option<SuitFilter> {
+        match s {
+            "ds" => Some(SuitFilter::DoubleSuited),
+            "ss" => Some(SuitFilter::SingleSuited),
+            "rb" => Some(SuitFilter::Rainbow),
+            _ => None,
+        }
+    }
+
+    // By the number of suits held at least twice
+    fn matches(&self, cards: &[Card]) -> bool {
+        let mut counts = [0; 4];
+        for card in cards {
+            counts[card.suit as usize] += 1;
+        }
+        let suited = counts.iter().filter(|&&count| count >= 2).count();
+        match self {
+            SuitFilter::DoubleSuited => suited >= 2,
+            SuitFilter::SingleSuited => suited == 1,
+            SuitFilter::Rainbow => suited == 0,
+        }
+    }
+}
+
+impl Range {
+    pub fn new() -> Range {
+        Range::default()
+    }
+
+    pub fn combos(&self) -> &[Combo] {
+        &self.combos
+    }
+
+    pub fn len(&self) -> usize {
+        self.combos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.combos.is_empty()
+    }
+
+    // Sum of the weights, i.e. the number of combos weighted by frequency
+    pub fn total_weight(&self) -> f64 {
+        self.combos.iter().map(|combo| combo.weight).sum()
+    }
+
+    // Weight of the combo, 0 if not in the range
+    pub fn weight(&self, cards: &[Card]) -> f64 {
+        self.index
+            .get(&mask(cards))
+            .map_or(0.0, |&i| self.combos[i].weight)
+    }
+
+    // Add a combo, replacing the weight if it is already in the range
+    pub fn add(&mut self, mut hand: Hand, weight: f64) {
+        hand.cards
+            .sort_by_key(|card| (std::cmp::Reverse(card.rank), card.suit as u8));
+        let key = mask(&hand.cards);
+        match self.index.get(&key) {
+            Some(&i) => self.combos[i].weight = weight,
+            None => {
+                self.index.insert(key, self.combos.len());
+                self.combos.push(Combo { hand, weight });
+            }
+        }
+    }
+
+    // Remove every combo holding one of the dead cards, e.g. the board
+    pub fn remove_dead(&mut self, dead: &[Card]) {
+        let dead = mask(dead);
+        self.retain(|combo| mask(&combo.hand.cards) & dead == 0);
+    }
+
+    fn retain<F: FnMut(&Combo) -> bool>(&mut self, f: F) {
+        self.combos.retain(f);
+        self.index = self
+            .combos
+            .iter()
+            .enumerate()
+            .map(|(i, combo)| (mask(&combo.hand.cards), i))
+            .collect();
+    }
+
+    // Number of hole cards of the combos, none when empty or mixed
+    fn hole_cards(&self) -> Option<usize> {
+        let size = self.combos.first()?.hand.cards.len();
+        self.combos
+            .iter()
+            .all(|combo| combo.hand.cards.len() == size)
+            .then_some(size)
+    }
+}
+
+impl TryFrom<&str> for Range {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Range, Error> {
+        let mut range = Range::new();
+        let mut filters: Vec<SuitFilter> = Vec::new();
+        for term in s.split(',').map(str::trim).filter(|term| !term.is_empty()) {
+            let (spec, weight) = match term.split_once(':') {
+                Some((spec, weight)) => {
+                    let weight: f64 = weight
+                        .trim()
+                        .parse()
+                        .map_err(|_| Error::InvalidRangeNotation)?;
+                    if !(0.0..=1.0).contains(&weight) {
+                        return Err(Error::InvalidRangeNotation);
+                    }
+                    (spec.trim(), weight)
+                }
+                None => (term, 1.0),
+            };
+            if let Some(filter) = SuitFilter::parse(spec) {
+                filters.push(filter);
+                continue;
+            }
+            for hand in expand(spec)? {
+                range.add(hand, weight);
+            }
+        }
+
+        if !filters.is_empty() {
+            range.retain(|combo| filters.iter().all(|f| f.matches(&combo.hand.cards)));
+        }
+        if range.len() > 1 && range.hole_cards().is_none() {
+            return Err(Error::InvalidRangeNotation);
+        }
+        Ok(range)
+    }
+}
+
+// Every combo a single term stands for
+fn expand(spec: &str) -> Result<Vec<Hand>, Error> {
+    let chars: Vec<char> = spec.chars().collect();
+
+    // Exact cards, e.g. "AsKd"
+    if spec.is_ascii()
+        && chars.len() >= 4
+        && chars.len().is_multiple_of(2)
+        && Card::try_from(&spec[..2]).is_ok()
+    {
+        let cards = (0..chars.len())
+            .step_by(2)
+            .map(|i| Card::try_from(&spec[i..i + 2]))
+            .collect::<Result<Vec<Card>, Error>>()
+            .map_err(|_| Error::InvalidRangeNotation)?;
+        if mask(&cards).count_ones() as usize != cards.len() {
+            return Err(Error::InvalidRangeNotation);
+        }
+        return Ok(vec![Hand::from(cards)]);
+    }
+
+    // Omaha patterns, e.g. "AAxx" or "AKQJds"
+    let ranks = chars
+        .iter()
+        .take_while(|&&c| c == 'x' || Rank::try_from(c).is_ok())
+        .count();
+    if ranks >= 4 {
+        let filter = match &spec[ranks..] {
+            "" => None,
+            suffix => Some(SuitFilter::parse(suffix).ok_or(Error::InvalidRangeNotation)?),
+        };
+        return omaha(&chars[..ranks], filter);
+    }
+
+    holdem(spec)
+}
+
+// Hold'em classes with "+" and "-" ranges, e.g. "QQ+", "A5s-A2s" or "KTo+"
+fn holdem(spec: &str) -> Result<Vec<Hand>, Error> {
+    let (first, last) = match spec.split_once('-') {
+        Some((first, last)) => (first, Some(last)),
+        None => (spec, None),
+    };
+    let (first, plus) = match first.strip_suffix('+') {
+        Some(first) => (first, true),
+        None => (first, false),
+    };
+    let (high, low, kind) = class(first)?;
+
+    let mut classes: Vec<(Rank, Rank)> = Vec::new();
+    if let Some(last) = last {
+        let (last_high, last_low, last_kind) = class(last)?;
+        if plus || last_kind != kind {
+            return Err(Error::InvalidRangeNotation);
+        }
+        if high == low {
+            // "QQ-88"
+            if last_high != last_low {
+                return Err(Error::InvalidRangeNotation);
+            }
+            let (top, bottom) = (high.max(last_high), high.min(last_high));
+            classes.extend(ranks_between(bottom, top).map(|rank| (rank, rank)));
+        } else {
+            // "A5s-A2s"
+            if last_high != high || last_low == last_high {
+                return Err(Error::InvalidRangeNotation);
+            }
+            let (top, bottom) = (low.max(last_low), low.min(last_low));
+            classes.extend(ranks_between(bottom, top).map(|rank| (high, rank)));
+        }
+    } else if plus && high == low {
+        // "QQ+"
+        classes.extend(ranks_between(high, Rank::Ace).map(|rank| (rank, rank)));
+    } else if plus {
+        // "KTo+", up to the kicker below the high card
+        let top = Rank::iter()[high as usize - 1];
+        classes.extend(ranks_between(low, top).map(|rank| (high, rank)));
+    } else {
+        classes.push((high, low));
+    }
+
+    let mut hands: Vec<Hand> = Vec::new();
+    for (high, low) in classes {
+        for &suit1 in Suit::iter() {
+            for &suit2 in Suit::iter() {
+                let suited = suit1 == suit2;
+                let wanted = match kind {
+                    Some('s') => suited,
+                    Some('o') => !suited,
+                    _ => true,
+                };
+                // Each pair once
+                if !wanted || (high == low && suit1 as u8 >= suit2 as u8) {
+                    continue;
+                }
+                hands.push(Hand::from(vec![
+                    Card::new(suit1, high),
+                    Card::new(suit2, low),
+                ]));
+            }
+        }
+    }
+    Ok(hands)
+}
+
+// High rank, low rank and "s" or "o" of a class like "AKs", "AK" or "QQ"
+fn class(s: &str) -> Result<(Rank, Rank, Option<char>), Error> {
+    let chars: Vec<char> = s.chars().collect();
+    let (ranks, kind) = match chars[..] {
+        [a, b] => ([a, b], None),
+        [a, b, k] if k == 's' || k == 'o' => ([a, b], Some(k)),
+        _ => return Err(Error::InvalidRangeNotation),
+    };
+    let a = Rank::try_from(ranks[0]).map_err(|_| Error::InvalidRangeNotation)?;
+    let b = Rank::try_from(ranks[1]).map_err(|_| Error::InvalidRangeNotation)?;
+    let (high, low) = (a.max(b), a.min(b));
+    if high == low && kind.is_some() {
+        return Err(Error::InvalidRangeNotation);
+    }
+    Ok((high, low, kind))
+}
+
+fn ranks_between(low: Rank, high: Rank) -> impl Iterator<Item = Rank> {
+    Rank::iter()[low as usize..=high as usize]
+        .iter()
+        .rev()
+        .copied()
+}
+
+// Hands holding at least the given ranks, "x" being any card
+fn omaha(pattern: &[char], filter: Option<SuitFilter>) -> Result<Vec<Hand>, Error> {
+    let mut wanted: [usize; 13] = [0; 13];
+    for &c in pattern.iter().filter(|&&c| c != 'x') {
+        let rank = Rank::try_from(c).map_err(|_| Error::InvalidRangeNotation)?;
+        wanted[rank as usize] += 1;
+        if wanted[rank as usize] > 4 {
+            return Err(Error::InvalidRangeNotation);
+        }
+    }
+
+    // Cards of the named ranks in every possible suit, then the rest from the deck
+    let mut fixed: Vec<Vec<Card>> = vec![Vec::new()];
+    for (index, &count) in wanted.iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+        let cards: Vec<Card> = Suit::iter()
+            .iter()
+            .map(|&suit| Card::new(suit, Rank::iter()[index]))
+            .collect();
+        let mut next: Vec<Vec<Card>> = Vec::new();
+        let mut combinations = Combinations::new(&cards, count);
+        while let Some(chosen) = combinations.next_cards() {
+            for partial in &fixed {
+                next.push([&partial[..], chosen].concat());
+            }
+        }
+        fixed = next;
+    }
+
+    let free = pattern.iter().filter(|&&c| c == 'x').count();
+    let deck = Deck::new();
+    let mut seen: HashSet<u64> = HashSet::new();
+    let mut hands: Vec<Hand> = Vec::new();
+    for partial in &fixed {
+        let mut combinations = deck.combinations_excluding(free, partial);
+        while let Some(chosen) = combinations.next_cards() {
+            let cards = [&partial[..], chosen].concat();
+            if filter.is_some_and(|filter| !filter.matches(&cards)) {
+                continue;
+            }
+            if seen.insert(mask(&cards)) {
+                hands.push(Hand::from(cards));
+            }
+        }
+    }
+    Ok(hands)
+}
+
+// Canonical form: Hold'em ranges grouped into classes, pairs first, then by
+// high card, with "+" and "-" for consecutive classes. Other ranges, and
+// Hold'em combos that do not fill a class, are listed one by one.
+impl std::fmt::Display for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut weights: Vec<f64> = Vec::new();
+        for combo in &self.combos {
+            if !weights.contains(&combo.weight) {
+                weights.push(combo.weight);
+            }
+        }
+        weights.sort_by(|a, b| b.total_cmp(a));
+
+        let mut terms: Vec<String> = Vec::new();
+        for weight in weights {
+            let suffix = if weight == 1.0 {
+                String::new()
+            } else {
+                format!(":{}", weight)
+            };
+            let mut rest: Vec<&Combo> = self
+                .combos
+                .iter()
+                .filter(|combo| combo.weight == weight)
+                .collect();
+            if self.hole_cards() == Some(2) {
+                terms.extend(
+                    self.holdem_terms(weight, &mut rest)
+                        .into_iter()
+                        .map(|term| term + &suffix),
+                );
+            }
+            rest.sort_by_key(|combo| std::cmp::Reverse(sort_key(&combo.hand.cards)));
+            for combo in rest {
+                let cards: String = combo.hand.cards.iter().map(Card::to_string).collect();
+                terms.push(cards + &suffix);
+            }
+        }
+        write!(f, "{}", terms.join(", "))
+    }
+}
+
+impl Range {
+    // Class terms of the combos with `weight`, leaving the others in `rest`
+    fn holdem_terms(&self, weight: f64, rest: &mut Vec<&Combo>) -> Vec<String> {
+        let full = |high: Rank, low: Rank, kind: Option<char>| {
+            let spec = format!(
+                "{}{}{}",
+                high,
+                low,
+                kind.map_or(String::new(), String::from)
+            );
+            holdem(&spec).is_ok_and(|hands| {
+                hands.iter().all(|hand| {
+                    self.index
+                        .get(&mask(&hand.cards))
+                        .is_some_and(|&i| self.combos[i].weight == weight)
+                })
+            })
+        };
+        let mut terms: Vec<String> = Vec::new();
+        let mut covered: Vec<(Rank, Rank, Option<char>)> = Vec::new();
+
+        let pairs: Vec<Rank> = Rank::iter()
+            .iter()
+            .rev()
+            .copied()
+            .filter(|&rank| full(rank, rank, None))
+            .collect();
+        for run in runs(&pairs) {
+            let (top, bottom) = (run[0], run[run.len() - 1]);
+            terms.push(if top == Rank::Ace && run.len() > 1 {
+                format!("{}{}+", bottom, bottom)
+            } else if run.len() > 1 {
+                format!("{}{}-{}{}", top, top, bottom, bottom)
+            } else {
+                format!("{}{}", top, top)
+            });
+            covered.extend(run.iter().map(|&rank| (rank, rank, None)));
+        }
+
+        for &high in Rank::iter().iter().rev() {
+            let lows = || Rank::iter()[..high as usize].iter().rev().copied();
+            let both: Vec<Rank> = lows()
+                .filter(|&low| full(high, low, Some('s')) && full(high, low, Some('o')))
+                .collect();
+            for kind in [None, Some('s'), Some('o')] {
+                let kickers: Vec<Rank> = match kind {
+                    None => both.clone(),
+                    Some(_) => lows()
+                        .filter(|low| !both.contains(low) && full(high, *low, kind))
+                        .collect(),
+                };
+                let k = kind.map_or(String::new(), String::from);
+                for run in runs(&kickers) {
+                    let (top, bottom) = (run[0], run[run.len() - 1]);
+                    terms.push(if top as usize + 1 == high as usize && run.len() > 1 {
+                        format!("{}{}{}+", high, bottom, k)
+                    } else if run.len() > 1 {
+                        format!("{}{}{}-{}{}{}", high, top, k, high, bottom, k)
+                    } else {
+                        format!("{}{}{}", high, top, k)
+                    });
+                    covered.extend(run.iter().map(|&low| (high, low, kind)));
+                }
+            }
+        }
+
+        rest.retain(|combo| {
+            let (a, b) = (combo.hand.cards[0], combo.hand.cards[1]);
+            let suited = a.suit == b.suit;
+            !covered.iter().any(|&(high, low, kind)| {
+                a.rank == high
+                    && b.rank == low
+                    && match kind {
+                        Some('s') => suited,
+                        Some('o') => !suited,
+                        _ => true,
+                    }
+            })
+        });
+        terms
+    }
+}
+
+// Consecutive ranks, from a list sorted from the highest down
+fn runs(ranks: &[Rank]) -> Vec<Vec<Rank>> {
+    let mut runs: Vec<Vec<Rank>> = Vec::new();
+    for &rank in ranks {
+        match runs.last_mut() {
+            Some(run) if *run.last().unwrap() as usize == rank as usize + 1 => run.push(rank),
+            _ => runs.push(vec![rank]),
+        }
+    }
+    runs
+}
+
+fn sort_key(cards: &[Card]) -> Vec<u8> {
+    cards
+        .iter()
+        .map(|card| card.ord_position(OrderFirstBy::Rank))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(s: &str) -> Range {
+        Range::try_from(s).unwrap()
+    }
+
+    #[test]
+    fn test_holdem_classes() {
+        assert_eq!(range("AA").len(), 6);
+        assert_eq!(range("AKs").len(), 4);
+        assert_eq!(range("AKo").len(), 12);
+        assert_eq!(range("AK").len(), 16);
+        assert_eq!(range("QQ+").len(), 18);
+        assert_eq!(range("QQ-88").len(), 30);
+        assert_eq!(range("A5s-A2s").len(), 16);
+        assert_eq!(range("KTo+").len(), 36);
+        assert_eq!(range("AsKd").len(), 1);
+        assert_eq!(range("QQ+, AKs, A5s-A2s, KTo+").len(), 74);
+        // Overlapping terms count once
+        assert_eq!(range("AK, AKs").len(), 16);
+    }
+
+    #[test]
+    fn test_invalid_notation() {
+        for s in [
+            "AAs", "AK+-AQ", "QQ-AKs", "A5s-K2s", "AKx", "AK:2", "AsAs", "AA, AAxx",
+        ] {
+            assert_eq!(
+                Range::try_from(s).unwrap_err(),
+                Error::InvalidRangeNotation,
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn test_weights() {
+        let range = range("AA, KK:0.5, AsKs:0.25");
+        assert_eq!(range.total_weight(), 6.0 + 3.0 + 0.25);
+        let cards = Hand::try_from("Kd Kh").unwrap().cards;
+        assert_eq!(range.weight(&cards), 0.5);
+        let cards = Hand::try_from("Ks As").unwrap().cards;
+        assert_eq!(range.weight(&cards), 0.25);
+        assert_eq!(range.weight(&Hand::try_from("Qs Qh").unwrap().cards), 0.0);
+    }
+
+    #[test]
+    fn test_remove_dead() {
+        let mut range = range("AA, AKs");
+        range.remove_dead(&Hand::try_from("As Kh").unwrap().cards);
+        // 3 aces left for AA, AKs loses spades and hearts
+        assert_eq!(range.len(), 3 + 2);
+        assert_eq!(range.weight(&Hand::try_from("Ac Kc").unwrap().cards), 1.0);
+    }
+
+    #[test]
+    fn test_omaha() {
+        // Exactly two, three or four aces
+        let aces = range("AAxx");
+        assert_eq!(aces.len(), 6 * 1128 + 4 * 48 + 1);
+        assert_eq!(range("AAKK").len(), 36);
+        assert_eq!(range("AAKKds").len(), 6);
+        assert_eq!(range("AAKK, ds").len(), 6);
+        assert_eq!(range("AAKKrb").len(), 6);
+        assert_eq!(range("AKQJT").len(), 1024);
+
+        let double_suited = range("AAxx, ds");
+        assert!(double_suited
+            .combos()
+            .iter()
+            .all(|combo| SuitFilter::DoubleSuited.matches(&combo.hand.cards)));
+        assert!(double_suited.len() < aces.len());
+    }
+
+    #[test]
+    fn test_canonical_form() {
+        for (s, canonical) in [
+            ("AKs, A5s-A2s, KTo+, QQ+", "QQ+, AKs, A5s-A2s, KTo+"),
+            ("JJ, TT, 99", "JJ-99"),
+            ("AKs, AKo", "AK"),
+            ("KQ, KJ, KT, K9s", "KT+, K9s"),
+            ("A2s, A3s, A4s", "A4s-A2s"),
+            ("AA:0.5, AsKd", "AsKd, AA:0.5"),
+            ("AhAs", "AsAh"),
+            ("KcKd, AcKs", "AcKs, KdKc"),
+        ] {
+            assert_eq!(range(s).to_string(), canonical, "{}", s);
+            assert_eq!(range(canonical).to_string(), canonical);
+        }
+    }
+}
@@ -0,0 +1,424 @@
+// Board texture of Hold'em and Omaha boards, e.g. paired or monotone, and
+// what a player's cards make of the board, e.g. top pair or a nut low draw
+
+use crate::card::{mask, Card, Rank, Suit};
+use crate::deck::Deck;
+use crate::error::Error;
+use crate::evaluation::eight_or_better::EightOrBetter;
+use crate::evaluation::high::{Category, High};
+use crate::evaluation::Evaluation;
+use crate::game::community::best_high;
+use crate::game::{Gameplay, Variant};
+use crate::hand::Hand;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suits {
+    // No two cards of a suit
+    Rainbow,
+    // At most two cards of any suit, so that a flush can come
+    TwoTone,
+    // Three or more cards of a suit, but not all, so that a flush is possible
+    FlushPossible,
+    // Every card of one suit
+    Monotone,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Texture {
+    // Two or more cards of a rank
+    pub paired: bool,
+    pub suits: Suits,
+    // Three ranks within five, so that a straight is possible
+    pub connected: bool,
+    // Three unpaired ranks of eight or lower, so that an 8 or better low is
+    // possible in hi-lo
+    pub low_possible: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Label {
+    // A pair in the hole higher than every board card
+    Overpair,
+    // A hole card pairing the highest board card, itself unpaired on the
+    // board, where it would make trips
+    TopPair,
+    // One more card of a suit makes a flush
+    FlushDraw,
+    // A flush draw to the highest card of the suit not on the board
+    NutFlushDraw,
+    // One more card makes an 8 or better low
+    LowDraw,
+    // A low draw where every card making the low, other than one pairing the
+    // player's cards, makes the nut low
+    NutLowDraw,
+    // The best low possible on the board
+    NutLow,
+    // The last board card pairs a low card of the player, who would have a
+    // better low with any other fresh low card instead
+    CounterfeitedLow,
+}
+
+impl std::fmt::Display for Label {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Label::Overpair => write!(f, "Overpair"),
+            Label::TopPair => write!(f, "Top pair"),
+            Label::FlushDraw => write!(f, "Flush draw"),
+            Label::NutFlushDraw => write!(f, "Nut flush draw"),
+            Label::LowDraw => write!(f, "Low draw"),
+            Label::NutLowDraw => write!(f, "Nut low draw"),
+            Label::NutLow => write!(f, "Nut low"),
+            Label::CounterfeitedLow => write!(f, "Counterfeited low"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    // Category of the best high hand
+    pub category: Category,
+    // Score of the best 8 or better low in hi-lo, 0 for no low
+    pub low: u128,
+    pub labels: Vec<Label>,
+}
+
+// Texture of a flop, turn or river
+pub fn texture(board: &Hand) -> Result<Texture, Error> {
+    check_board(board)?;
+    let mut ranks = [0; 13];
+    let mut suits = [0; 4];
+    for card in &board.cards {
+        ranks[card.rank as usize] += 1;
+        suits[card.suit as usize] += 1;
+    }
+    let suited = suits.iter().copied().max().unwrap_or(0);
+    Ok(Texture {
+        paired: ranks.iter().any(|&count| count >= 2),
+        suits: match suited {
+            0 | 1 => Suits::Rainbow,
+            2 => Suits::TwoTone,
+            _ if suited == board.cards.len() => Suits::Monotone,
+            _ => Suits::FlushPossible,
+        },
+        connected: straight_possible(&board.cards),
+        low_possible: low_ranks(&board.cards).count_ones() >= 3,
+    })
+}
+
+// What the hole cards make of the board in Hold'em and Omaha, with Omaha's
+// rule of exactly two hole cards. Low labels are given in hi-lo only.
+pub fn analyze(variant: Variant, hole: &Hand, board: &Hand) -> Result<Analysis, Error> {
+    if variant.gameplay() != Gameplay::Community {
+        return Err(Error::UnsupportedGame);
+    }
+    check_board(board)?;
+    let all = [&hole.cards[..], &board.cards[..]].concat();
+    if hole.cards.len() != variant.hole_cards() || mask(&all).count_ones() as usize != all.len() {
+        return Err(Error::InvalidHand);
+    }
+
+    let category = High::category(best_high(variant, hole, board)?.score);
+    let mut labels: Vec<Label> = Vec::new();
+
+    // Pairs
+    let top = board.cards.iter().map(|card| card.rank).max();
+    let held = |rank: Rank| hole.cards.iter().filter(|card| card.rank == rank).count();
+    let on_board = |rank: Rank| board.cards.iter().filter(|card| card.rank == rank).count();
+    let overpair = hole
+        .cards
+        .iter()
+        .any(|card| Some(card.rank) > top && held(card.rank) >= 2);
+    if overpair {
+        labels.push(Label::Overpair);
+    } else if top.is_some_and(|top| held(top) == 1 && on_board(top) == 1) {
+        labels.push(Label::TopPair);
+    }
+
+    // Flush draws, with a card still to come and no flush yet
+    if board.cards.len() < 5 && category < Category::Flush {
+        let omaha = variant != Variant::Holdem;
+        for &suit in Suit::iter() {
+            let in_hole = count_suit(&hole.cards, suit);
+            let on_board = count_suit(&board.cards, suit);
+            let draw = if omaha {
+                in_hole >= 2 && on_board == 2
+            } else {
+                in_hole >= 1 && in_hole + on_board == 4
+            };
+            if !draw {
+                continue;
+            }
+            // Highest card of the suit not on the board
+            let nut = Rank::iter()
+                .iter()
+                .rev()
+                .map(|&rank| Card::new(suit, rank))
+                .find(|card| !board.cards.contains(card));
+            if nut.is_some_and(|nut| hole.cards.contains(&nut)) {
+                labels.push(Label::NutFlushDraw);
+            } else {
+                labels.push(Label::FlushDraw);
+            }
+        }
+    }
+
+    let mut low = 0;
+    if variant.is_split_pot() {
+        low = low_score(&hole.cards, &board.cards)?;
+        labels.extend(low_labels(hole, board, low)?);
+    }
+
+    Ok(Analysis {
+        category,
+        low,
+        labels,
+    })
+}
+
+// Low draws, nut low and counterfeits in Omaha hi-lo
+fn low_labels(hole: &Hand, board: &Hand, low: u128) -> Result<Vec<Label>, Error> {
+    let mut labels: Vec<Label> = Vec::new();
+    let used = mask(&[&hole.cards[..], &board.cards[..]].concat());
+    let unseen: Vec<Card> = Deck::new()
+        .cards
+        .into_iter()
+        .filter(|card| used & mask(&[*card]) == 0)
+        .collect();
+    let held = low_ranks(&hole.cards);
+
+    if low > 0 {
+        if low == nut_low(&board.cards)? {
+            labels.push(Label::NutLow);
+        }
+    } else if board.cards.len() < 5 {
+        // Lows made by the next card, and whether each is the nut low
+        let mut draws: Vec<bool> = Vec::new();
+        for &card in &unseen {
+            let next = [&board.cards[..], &[card]].concat();
+            let score = low_score(&hole.cards, &next)?;
+            if score > 0 && low_ranks(&[card]) & held == 0 {
+                draws.push(score == nut_low(&next)?);
+            }
+        }
+        if !draws.is_empty() {
+            if draws.iter().all(|&nut| nut) {
+                labels.push(Label::NutLowDraw);
+            } else {
+                labels.push(Label::LowDraw);
+            }
+        }
+    }
+
+    // The last card against every fresh low card in its place
+    if let Some((&last, rest)) = board.cards.split_last() {
+        if rest.len() >= 3 && low_ranks(&[last]) & held != 0 {
+            let fresh = unseen
+                .iter()
+                .map(|card| (*card, low_ranks(&[*card])))
+                .filter(|&(_, rank)| rank != 0 && rank & (held | low_ranks(rest)) == 0);
+            let mut alternatives: Vec<u128> = Vec::new();
+            for (card, _) in fresh {
+                alternatives.push(low_score(&hole.cards, &[rest, &[card]].concat())?);
+            }
+            if alternatives.iter().min().is_some_and(|&worst| low < worst) {
+                labels.push(Label::CounterfeitedLow);
+            }
+        }
+    }
+    Ok(labels)
+}
+
+// Best 8 or better low with two hole cards and three board cards, 0 for none
+fn low_score(hole: &[Card], board: &[Card]) -> Result<u128, Error> {
+    Ok(Evaluation::<EightOrBetter>::best_omaha(hole, board)?.score)
+}
+
+// Best low any two hole cards make on the board, 0 when no low is possible
+fn nut_low(board: &[Card]) -> Result<u128, Error> {
+    let used = mask(board);
+    // A card of each low rank not on the board
+    let fresh: Vec<Card> = Rank::iter()
+        .iter()
+        .filter(|&&rank| rank <= Rank::Eight || rank == Rank::Ace)
+        .filter_map(|&rank| {
+            Suit::iter()
+                .iter()
+                .map(|&suit| Card::new(suit, rank))
+                .find(|card| used & mask(&[*card]) == 0)
+        })
+        .collect();
+    let mut best = 0;
+    for (i, &first) in fresh.iter().enumerate() {
+        for &second in &fresh[i + 1..] {
+            best = best.max(low_score(&[first, second], board)?);
+        }
+    }
+    Ok(best)
+}
+
+// Set of the distinct ranks of eight or lower, aces included, one bit each
+fn low_ranks(cards: &[Card]) -> u16 {
+    cards
+        .iter()
+        .filter(|card| card.rank <= Rank::Eight || card.rank == Rank::Ace)
+        .fold(0, |ranks, card| ranks | 1 << card.rank as u16)
+}
+
+fn count_suit(cards: &[Card], suit: Suit) -> usize {
+    cards.iter().filter(|card| card.suit == suit).count()
+}
+
+// Whether three of the ranks fit in five in a row, the ace also low
+fn straight_possible(cards: &[Card]) -> bool {
+    // Bit 0 is the ace low, bit 1 the deuce and bit 13 the ace high
+    let ranks: u16 = cards.iter().fold(0, |ranks, card| {
+        let ranks = ranks | 1 << (card.rank as u16 + 1);
+        match card.rank {
+            Rank::Ace => ranks | 1,
+            _ => ranks,
+        }
+    });
+    (0..=9).any(|low| (ranks >> low & 0b11111).count_ones() >= 3)
+}
+
+fn check_board(board: &Hand) -> Result<(), Error> {
+    if !(3..=5).contains(&board.cards.len())
+        || mask(&board.cards).count_ones() as usize != board.cards.len()
+    {
+        return Err(Error::InvalidHand);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::hand;
+
+    fn labels(variant: Variant, hole: &str, board: &str) -> Vec<Label> {
+        analyze(variant, &hand(hole), &hand(board)).unwrap().labels
+    }
+
+    #[test]
+    fn test_texture() {
+        let dry = texture(&hand("Kc 7d 2h")).unwrap();
+        assert!(!dry.paired && !dry.connected && !dry.low_possible);
+        assert_eq!(dry.suits, Suits::Rainbow);
+
+        let wet = texture(&hand("9s Ts Js")).unwrap();
+        assert_eq!(wet.suits, Suits::Monotone);
+        assert!(wet.connected);
+
+        // Three of a suit on the turn is no longer monotone
+        let turn = texture(&hand("Kh 7h 2h 9c")).unwrap();
+        assert_eq!(turn.suits, Suits::FlushPossible);
+        assert_eq!(
+            texture(&hand("Kh 7h 2h 9h")).unwrap().suits,
+            Suits::Monotone
+        );
+
+        let low = texture(&hand("Ah 3h 5c 5d")).unwrap();
+        assert!(low.paired && low.low_possible);
+        assert_eq!(low.suits, Suits::TwoTone);
+        // A-3-5 fit a wheel
+        assert!(low.connected);
+
+        // Paired low cards are only one rank
+        assert!(!texture(&hand("2h 2c 8d Kc")).unwrap().low_possible);
+
+        assert_eq!(texture(&hand("Ah 3h")), Err(Error::InvalidHand));
+        assert_eq!(texture(&hand("Ah 3h Ah")), Err(Error::InvalidHand));
+    }
+
+    #[test]
+    fn test_pairs() {
+        assert_eq!(
+            labels(Variant::Holdem, "Qh Qd", "Jc 7d 2h"),
+            vec![Label::Overpair]
+        );
+        assert_eq!(
+            labels(Variant::Holdem, "Ac Jd", "Jc 7d 2h"),
+            vec![Label::TopPair]
+        );
+        // A set is neither, nor trips on a paired top card
+        assert!(labels(Variant::Holdem, "Jh Js", "Jc 7d 2h").is_empty());
+        assert!(labels(Variant::Holdem, "Ac Jd", "Jc Jh 7d").is_empty());
+        assert_eq!(
+            labels(Variant::Omaha, "Kh Kd 5c 6c", "Jc 7d 2h"),
+            vec![Label::Overpair]
+        );
+    }
+
+    #[test]
+    fn test_flush_draws() {
+        assert_eq!(
+            labels(Variant::Holdem, "Ah 3h", "Kh 7h 2c"),
+            vec![Label::NutFlushDraw]
+        );
+        assert_eq!(
+            labels(Variant::Holdem, "Qh 3h", "Kh 7h 2c"),
+            vec![Label::FlushDraw]
+        );
+        // With the ace on board the king is the nuts
+        assert_eq!(
+            labels(Variant::Holdem, "Kh 3h", "Ah 7h 2c 9d"),
+            vec![Label::NutFlushDraw]
+        );
+        // No draw on the river
+        assert!(labels(Variant::Holdem, "Qh 3h", "Kh 7h 2c 9d Td").is_empty());
+        // Omaha needs two hole cards of the suit
+        assert!(labels(Variant::Omaha, "Ah Tc Td 9s", "Kh 7h 2c").is_empty());
+        assert_eq!(
+            labels(Variant::Omaha, "Ah 3h Td 9s", "Kh 7h 2c").as_slice(),
+            [Label::NutFlushDraw]
+        );
+    }
+
+    #[test]
+    fn test_low_labels() {
+        let draw = analyze(Variant::OmahaHiLo, &hand("Ac 2d Ks Qs"), &hand("7h 8c Jd")).unwrap();
+        assert_eq!(draw.low, 0);
+        assert_eq!(draw.labels, vec![Label::NutLowDraw]);
+        assert_eq!(
+            labels(Variant::OmahaHiLo, "Ac 3d Ks Qs", "7h 8c Jd"),
+            vec![Label::LowDraw]
+        );
+
+        let nut = analyze(Variant::OmahaHiLo, &hand("Ac 2d Ks Qs"), &hand("7h 8c 4d")).unwrap();
+        assert!(nut.low > 0);
+        assert_eq!(nut.labels, vec![Label::NutLow]);
+        assert!(labels(Variant::OmahaHiLo, "Ac 3d Ks Qs", "7h 8c 4d").is_empty());
+
+        // An ace on the turn leaves A-2 without a low
+        let counterfeited = analyze(
+            Variant::OmahaHiLo,
+            &hand("Ac 2d Ks Qs"),
+            &hand("3h 4c Td Ah"),
+        )
+        .unwrap();
+        assert_eq!(counterfeited.low, 0);
+        assert!(counterfeited.labels.contains(&Label::CounterfeitedLow));
+        // A-2-3 still makes a wheel
+        assert!(!labels(Variant::BigO, "Ac 2d 3s Ks Qs", "4h 5c Td Ah")
+            .contains(&Label::CounterfeitedLow));
+
+        // No low labels outside hi-lo
+        assert!(labels(Variant::Omaha, "Ac 2d Ks Qs", "7h 8c 4d").is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            analyze(Variant::Razz, &hand("Ac 2d"), &hand("7h 8c 4d")),
+            Err(Error::UnsupportedGame)
+        );
+        assert_eq!(
+            analyze(Variant::Omaha, &hand("Ac 2d"), &hand("7h 8c 4d")),
+            Err(Error::InvalidHand)
+        );
+        assert_eq!(
+            analyze(Variant::Holdem, &hand("Ac 7h"), &hand("7h 8c 4d")),
+            Err(Error::InvalidHand)
+        );
+    }
+}